@@ -0,0 +1,11 @@
+-- Interactions (calls, coffees, messages) logged against a contact
+CREATE TABLE interactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    contact_id INTEGER NOT NULL,
+    occurred_at TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    note TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY (contact_id) REFERENCES contacts (id) ON DELETE CASCADE
+);
+
+CREATE INDEX interactions_contact_id_occurred_at ON interactions (contact_id, occurred_at);
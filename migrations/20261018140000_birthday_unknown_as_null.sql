@@ -0,0 +1,3 @@
+-- Unknown birthdays were stored as the 0001-01-01 sentinel; store NULL instead.
+-- Birthdays without a year are stored as --MM-DD.
+UPDATE contacts SET birthday = NULL WHERE birthday = '0001-01-01';
@@ -0,0 +1,67 @@
+-- Full-text index over names, email, phone digits and interaction notes.
+-- The rowid of each entry is the contact id; triggers keep it in sync.
+CREATE VIRTUAL TABLE contacts_fts USING fts5 (
+    first_name,
+    last_name,
+    display_name,
+    email,
+    phone_digits,
+    notes,
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO contacts_fts (rowid, first_name, last_name, display_name, email, phone_digits, notes)
+SELECT
+    c.id,
+    COALESCE(c.first_name, ''),
+    COALESCE(c.last_name, ''),
+    COALESCE(c.display_name, ''),
+    COALESCE(c.email, ''),
+    replace(replace(replace(replace(replace(replace(COALESCE(c.phone_number, ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''),
+    COALESCE((SELECT group_concat(i.note, ' ') FROM interactions i WHERE i.contact_id = c.id), '')
+FROM contacts c;
+
+CREATE TRIGGER contacts_fts_insert AFTER INSERT ON contacts BEGIN
+    INSERT INTO contacts_fts (rowid, first_name, last_name, display_name, email, phone_digits, notes)
+    VALUES (
+        new.id,
+        COALESCE(new.first_name, ''),
+        COALESCE(new.last_name, ''),
+        COALESCE(new.display_name, ''),
+        COALESCE(new.email, ''),
+        replace(replace(replace(replace(replace(replace(COALESCE(new.phone_number, ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''),
+        ''
+    );
+END;
+
+CREATE TRIGGER contacts_fts_update AFTER UPDATE OF first_name, last_name, display_name, email, phone_number ON contacts BEGIN
+    UPDATE contacts_fts SET
+        first_name = COALESCE(new.first_name, ''),
+        last_name = COALESCE(new.last_name, ''),
+        display_name = COALESCE(new.display_name, ''),
+        email = COALESCE(new.email, ''),
+        phone_digits = replace(replace(replace(replace(replace(replace(COALESCE(new.phone_number, ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '')
+    WHERE rowid = new.id;
+END;
+
+CREATE TRIGGER contacts_fts_delete AFTER DELETE ON contacts BEGIN
+    DELETE FROM contacts_fts WHERE rowid = old.id;
+END;
+
+CREATE TRIGGER interactions_fts_insert AFTER INSERT ON interactions BEGIN
+    UPDATE contacts_fts
+    SET notes = COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = new.contact_id), '')
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER interactions_fts_update AFTER UPDATE OF note ON interactions BEGIN
+    UPDATE contacts_fts
+    SET notes = COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = new.contact_id), '')
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER interactions_fts_delete AFTER DELETE ON interactions BEGIN
+    UPDATE contacts_fts
+    SET notes = COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = old.contact_id), '')
+    WHERE rowid = old.contact_id;
+END;
@@ -0,0 +1,25 @@
+-- Contacts folded into another by `merge`, so their old IDs stay traceable
+CREATE TABLE contact_merges (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kept_id INTEGER NOT NULL,
+    dropped_id INTEGER NOT NULL,
+    dropped_name TEXT NOT NULL DEFAULT '',
+    merged_at TEXT NOT NULL,
+    FOREIGN KEY (kept_id) REFERENCES contacts (id) ON DELETE CASCADE
+);
+
+CREATE INDEX contact_merges_kept_id ON contact_merges (kept_id);
+
+-- Merging moves interactions to the kept contact, so keep the search index's
+-- notes right for both contacts when an interaction changes hands
+DROP TRIGGER interactions_fts_update;
+
+CREATE TRIGGER interactions_fts_update AFTER UPDATE OF note, contact_id ON interactions BEGIN
+    UPDATE contacts_fts
+    SET notes = COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = old.contact_id), '')
+    WHERE rowid = old.contact_id;
+
+    UPDATE contacts_fts
+    SET notes = COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = new.contact_id), '')
+    WHERE rowid = new.contact_id;
+END;
@@ -0,0 +1,5 @@
+-- The ID a contact has in the system it was imported from, so re-importing
+-- the same file updates contacts instead of duplicating them
+ALTER TABLE contacts ADD COLUMN external_id TEXT;
+
+CREATE UNIQUE INDEX contacts_external_id ON contacts (external_id) WHERE external_id IS NOT NULL;
@@ -0,0 +1,20 @@
+-- The phone number in E.164 form, such as +12015550123, kept alongside the
+-- number as it was entered
+ALTER TABLE contacts ADD COLUMN phone_e164 TEXT;
+
+-- Numbers saved so far were checked against the North American plan, so a
+-- valid ten-digit number, with or without its leading 1, maps to +1
+UPDATE contacts
+SET phone_e164 = '+1' || substr(digits, -10)
+FROM (
+    SELECT
+        id AS contact_id,
+        replace(replace(replace(replace(replace(replace(phone_number, ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '') AS digits
+    FROM contacts
+    WHERE phone_number IS NOT NULL
+)
+WHERE contacts.id = contact_id
+  AND digits NOT GLOB '*[^0-9]*'
+  AND (length(digits) = 10 OR (length(digits) = 11 AND digits GLOB '1*'))
+  AND substr(digits, -10, 1) BETWEEN '2' AND '9'
+  AND substr(digits, -7, 1) BETWEEN '2' AND '9';
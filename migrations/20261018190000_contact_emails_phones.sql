@@ -0,0 +1,29 @@
+-- Contacts can have several email addresses and phone numbers, each labelled
+-- home, work, mobile or other. The preferred one is also kept in the
+-- contacts table's email and phone_number columns.
+CREATE TABLE contact_emails (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    contact_id INTEGER NOT NULL,
+    label TEXT NOT NULL DEFAULT 'other' CHECK (label IN ('home', 'work', 'mobile', 'other')),
+    email TEXT NOT NULL COLLATE NOCASE,
+    is_preferred BOOLEAN NOT NULL DEFAULT FALSE,
+    UNIQUE (contact_id, email),
+    FOREIGN KEY (contact_id) REFERENCES contacts (id) ON DELETE CASCADE
+);
+
+CREATE TABLE contact_phones (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    contact_id INTEGER NOT NULL,
+    label TEXT NOT NULL DEFAULT 'other' CHECK (label IN ('home', 'work', 'mobile', 'other')),
+    phone_number TEXT NOT NULL,
+    phone_e164 TEXT,
+    is_preferred BOOLEAN NOT NULL DEFAULT FALSE,
+    UNIQUE (contact_id, phone_number),
+    FOREIGN KEY (contact_id) REFERENCES contacts (id) ON DELETE CASCADE
+);
+
+INSERT INTO contact_emails (contact_id, email, is_preferred)
+SELECT id, trim(email), TRUE FROM contacts WHERE trim(COALESCE(email, '')) != '';
+
+INSERT INTO contact_phones (contact_id, phone_number, phone_e164, is_preferred)
+SELECT id, trim(phone_number), phone_e164, TRUE FROM contacts WHERE trim(COALESCE(phone_number, '')) != '';
@@ -0,0 +1,14 @@
+-- Postal addresses of contacts, split into the parts mail is sorted by
+CREATE TABLE contact_addresses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    contact_id INTEGER NOT NULL,
+    label TEXT NOT NULL DEFAULT 'other' CHECK (label IN ('home', 'work', 'other')),
+    street TEXT NOT NULL DEFAULT '',
+    locality TEXT NOT NULL DEFAULT '',
+    region TEXT NOT NULL DEFAULT '',
+    postal_code TEXT NOT NULL DEFAULT '',
+    country TEXT NOT NULL DEFAULT '',
+    FOREIGN KEY (contact_id) REFERENCES contacts (id) ON DELETE CASCADE
+);
+
+CREATE INDEX contact_addresses_contact_id ON contact_addresses (contact_id);
@@ -0,0 +1,96 @@
+-- Free-form Markdown notes about a contact. Editing a note keeps the text it
+-- replaced in note_revisions, so nothing written is ever lost.
+CREATE TABLE notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    contact_id INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (contact_id) REFERENCES contacts (id) ON DELETE CASCADE
+);
+
+CREATE INDEX notes_contact_id_created_at ON notes (contact_id, created_at);
+
+CREATE TABLE note_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    note_id INTEGER NOT NULL,
+    body TEXT NOT NULL,
+    written_at TEXT NOT NULL,
+    FOREIGN KEY (note_id) REFERENCES notes (id) ON DELETE CASCADE
+);
+
+CREATE INDEX note_revisions_note_id ON note_revisions (note_id);
+
+-- The search index's notes column now holds interaction notes followed by
+-- contact notes, so every trigger that fills it is rewritten
+DROP TRIGGER interactions_fts_insert;
+DROP TRIGGER interactions_fts_update;
+DROP TRIGGER interactions_fts_delete;
+
+CREATE TRIGGER interactions_fts_insert AFTER INSERT ON interactions BEGIN
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = new.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = new.contact_id), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER interactions_fts_update AFTER UPDATE OF note, contact_id ON interactions BEGIN
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = old.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = old.contact_id), '')
+    )
+    WHERE rowid = old.contact_id;
+
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = new.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = new.contact_id), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER interactions_fts_delete AFTER DELETE ON interactions BEGIN
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = old.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = old.contact_id), '')
+    )
+    WHERE rowid = old.contact_id;
+END;
+
+CREATE TRIGGER notes_fts_insert AFTER INSERT ON notes BEGIN
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = new.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = new.contact_id), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER notes_fts_update AFTER UPDATE OF body, contact_id ON notes BEGIN
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = old.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = old.contact_id), '')
+    )
+    WHERE rowid = old.contact_id;
+
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = new.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = new.contact_id), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER notes_fts_delete AFTER DELETE ON notes BEGIN
+    UPDATE contacts_fts
+    SET notes = trim(
+        COALESCE((SELECT group_concat(note, ' ') FROM interactions WHERE contact_id = old.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(body, ' ') FROM notes WHERE contact_id = old.contact_id), '')
+    )
+    WHERE rowid = old.contact_id;
+END;
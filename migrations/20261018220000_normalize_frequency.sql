@@ -0,0 +1,46 @@
+-- Before cadences were parsed, frequency held free text or a number of days.
+-- Keep every value that reads as a cadence (see Cadence::from_str) and clear
+-- the rest, so a contact never fails to load because of its frequency.
+
+-- A bare number counted days
+UPDATE contacts
+SET frequency = CASE
+    WHEN frequency >= 1 THEN 'every ' || CAST(frequency AS INTEGER) || ' days'
+END
+WHERE typeof(frequency) IN ('integer', 'real');
+
+UPDATE contacts
+SET frequency = lower(trim(replace(replace(replace(frequency, char(9), ' '), '  ', ' '), '  ', ' ')))
+WHERE typeof(frequency) = 'text';
+
+UPDATE contacts
+SET frequency = NULL
+WHERE typeof(frequency) NOT IN ('text', 'null')
+   OR (
+    typeof(frequency) = 'text'
+    AND frequency NOT IN (
+        'daily', 'weekly', 'biweekly', 'fortnightly', 'monthly', 'quarterly', 'yearly', 'annually'
+    )
+    -- every week
+    AND NOT (
+        frequency LIKE 'every %'
+        AND substr(frequency, 7) IN ('d', 'day', 'days', 'w', 'week', 'weeks', 'm', 'month', 'months', 'y', 'year', 'years')
+    )
+    -- every 2 weeks
+    AND NOT (
+        frequency LIKE 'every %'
+        AND instr(substr(frequency, 7), ' ') > 1
+        AND substr(substr(frequency, 7), instr(substr(frequency, 7), ' ') + 1)
+            IN ('d', 'day', 'days', 'w', 'week', 'weeks', 'm', 'month', 'months', 'y', 'year', 'years')
+        AND trim(substr(substr(frequency, 7), 1, instr(substr(frequency, 7), ' ') - 1), '0123456789') = ''
+        AND instr(substr(frequency, 7), ' ') <= 10
+        AND CAST(substr(substr(frequency, 7), 1, instr(substr(frequency, 7), ' ') - 1) AS INTEGER) > 0
+    )
+    -- 2w
+    AND NOT (
+        ltrim(frequency, '0123456789')
+            IN ('d', 'day', 'days', 'w', 'week', 'weeks', 'm', 'month', 'months', 'y', 'year', 'years')
+        AND length(frequency) - length(ltrim(frequency, '0123456789')) BETWEEN 1 AND 9
+        AND CAST(frequency AS INTEGER) > 0
+    )
+);
@@ -0,0 +1,98 @@
+-- Index every email address and phone number of a contact, not only the
+-- preferred ones kept in the contacts table, so search finds secondary ones.
+-- The preferred value comes first and is not repeated from the lists.
+UPDATE contacts_fts SET
+    email = trim(
+        COALESCE((SELECT email FROM contacts WHERE id = contacts_fts.rowid), '')
+        || ' ' || COALESCE((SELECT group_concat(email, ' ') FROM contact_emails WHERE contact_id = contacts_fts.rowid AND email != trim(COALESCE((SELECT email FROM contacts WHERE id = contacts_fts.rowid), ''))), '')
+    ),
+    phone_digits = trim(
+        replace(replace(replace(replace(replace(replace(COALESCE((SELECT phone_number FROM contacts WHERE id = contacts_fts.rowid), ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '')
+        || ' ' || COALESCE((SELECT group_concat(replace(replace(replace(replace(replace(replace(phone_number, ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''), ' ') FROM contact_phones WHERE contact_id = contacts_fts.rowid AND phone_number != trim(COALESCE((SELECT phone_number FROM contacts WHERE id = contacts_fts.rowid), ''))), '')
+    );
+
+DROP TRIGGER contacts_fts_update;
+
+CREATE TRIGGER contacts_fts_update AFTER UPDATE OF first_name, last_name, display_name, email, phone_number ON contacts BEGIN
+    UPDATE contacts_fts SET
+        first_name = COALESCE(new.first_name, ''),
+        last_name = COALESCE(new.last_name, ''),
+        display_name = COALESCE(new.display_name, ''),
+        email = trim(
+            COALESCE(new.email, '')
+            || ' ' || COALESCE((SELECT group_concat(email, ' ') FROM contact_emails WHERE contact_id = new.id AND email != trim(COALESCE(new.email, ''))), '')
+        ),
+        phone_digits = trim(
+            replace(replace(replace(replace(replace(replace(COALESCE(new.phone_number, ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '')
+            || ' ' || COALESCE((SELECT group_concat(replace(replace(replace(replace(replace(replace(phone_number, ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''), ' ') FROM contact_phones WHERE contact_id = new.id AND phone_number != trim(COALESCE(new.phone_number, ''))), '')
+        )
+    WHERE rowid = new.id;
+END;
+
+CREATE TRIGGER contact_emails_fts_insert AFTER INSERT ON contact_emails BEGIN
+    UPDATE contacts_fts
+    SET email = trim(
+        COALESCE((SELECT email FROM contacts WHERE id = new.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(email, ' ') FROM contact_emails WHERE contact_id = new.contact_id AND email != trim(COALESCE((SELECT email FROM contacts WHERE id = new.contact_id), ''))), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER contact_emails_fts_update AFTER UPDATE OF email, contact_id ON contact_emails BEGIN
+    UPDATE contacts_fts
+    SET email = trim(
+        COALESCE((SELECT email FROM contacts WHERE id = old.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(email, ' ') FROM contact_emails WHERE contact_id = old.contact_id AND email != trim(COALESCE((SELECT email FROM contacts WHERE id = old.contact_id), ''))), '')
+    )
+    WHERE rowid = old.contact_id;
+
+    UPDATE contacts_fts
+    SET email = trim(
+        COALESCE((SELECT email FROM contacts WHERE id = new.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(email, ' ') FROM contact_emails WHERE contact_id = new.contact_id AND email != trim(COALESCE((SELECT email FROM contacts WHERE id = new.contact_id), ''))), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER contact_emails_fts_delete AFTER DELETE ON contact_emails BEGIN
+    UPDATE contacts_fts
+    SET email = trim(
+        COALESCE((SELECT email FROM contacts WHERE id = old.contact_id), '')
+        || ' ' || COALESCE((SELECT group_concat(email, ' ') FROM contact_emails WHERE contact_id = old.contact_id AND email != trim(COALESCE((SELECT email FROM contacts WHERE id = old.contact_id), ''))), '')
+    )
+    WHERE rowid = old.contact_id;
+END;
+
+CREATE TRIGGER contact_phones_fts_insert AFTER INSERT ON contact_phones BEGIN
+    UPDATE contacts_fts
+    SET phone_digits = trim(
+        replace(replace(replace(replace(replace(replace(COALESCE((SELECT phone_number FROM contacts WHERE id = new.contact_id), ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '')
+        || ' ' || COALESCE((SELECT group_concat(replace(replace(replace(replace(replace(replace(phone_number, ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''), ' ') FROM contact_phones WHERE contact_id = new.contact_id AND phone_number != trim(COALESCE((SELECT phone_number FROM contacts WHERE id = new.contact_id), ''))), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER contact_phones_fts_update AFTER UPDATE OF phone_number, contact_id ON contact_phones BEGIN
+    UPDATE contacts_fts
+    SET phone_digits = trim(
+        replace(replace(replace(replace(replace(replace(COALESCE((SELECT phone_number FROM contacts WHERE id = old.contact_id), ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '')
+        || ' ' || COALESCE((SELECT group_concat(replace(replace(replace(replace(replace(replace(phone_number, ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''), ' ') FROM contact_phones WHERE contact_id = old.contact_id AND phone_number != trim(COALESCE((SELECT phone_number FROM contacts WHERE id = old.contact_id), ''))), '')
+    )
+    WHERE rowid = old.contact_id;
+
+    UPDATE contacts_fts
+    SET phone_digits = trim(
+        replace(replace(replace(replace(replace(replace(COALESCE((SELECT phone_number FROM contacts WHERE id = new.contact_id), ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '')
+        || ' ' || COALESCE((SELECT group_concat(replace(replace(replace(replace(replace(replace(phone_number, ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''), ' ') FROM contact_phones WHERE contact_id = new.contact_id AND phone_number != trim(COALESCE((SELECT phone_number FROM contacts WHERE id = new.contact_id), ''))), '')
+    )
+    WHERE rowid = new.contact_id;
+END;
+
+CREATE TRIGGER contact_phones_fts_delete AFTER DELETE ON contact_phones BEGIN
+    UPDATE contacts_fts
+    SET phone_digits = trim(
+        replace(replace(replace(replace(replace(replace(COALESCE((SELECT phone_number FROM contacts WHERE id = old.contact_id), ''), ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', '')
+        || ' ' || COALESCE((SELECT group_concat(replace(replace(replace(replace(replace(replace(phone_number, ' ', ''), '-', ''), '(', ''), ')', ''), '+', ''), '.', ''), ' ') FROM contact_phones WHERE contact_id = old.contact_id AND phone_number != trim(COALESCE((SELECT phone_number FROM contacts WHERE id = old.contact_id), ''))), '')
+    )
+    WHERE rowid = old.contact_id;
+END;
@@ -1,80 +1,298 @@
+use chrono::{Local, Utc};
 use nbd::{
     db::{self, ContactRepo, Repo},
     models::{self, ContactBuilder},
+    utils,
 };
+use regex::RegexBuilder;
 use sqlx::SqlitePool;
-use tabled::Table;
+use std::{
+    io::{BufRead, IsTerminal, Write},
+    path::Path,
+};
 
-use crate::commander::{CreateCommand, DeleteCommand, EditCommand, GetCommand, ImportCommand};
+use crate::commander::{
+    AddressArgs, AddressCommand, BackupCommand, BirthdaysCommand, BooksCommand, ConfigCommand,
+    CreateCommand, CsvProfile, DbCommand, DeleteCommand, DueCommand, EditCommand,
+    ExportCalendarCommand, ExportCommand, ExportFormat, GetCommand, HistoryCommand, ImportCommand,
+    ImportFormat, LabelsCommand, LogCommand, MergeCommand, NoteCommand, OnDuplicate, OutputFormat,
+    PhoneFormat, RestoreCommand, SearchCommand, ShowCommand, TagCommand, TransferCommand,
+    VcardVersion,
+};
+use crate::output;
 
 pub struct Actions {
     data_repo: db::Repo<SqlitePool>,
+    config: utils::Config,
 }
 
 impl Actions {
-    pub const fn new(data_repo: Repo<SqlitePool>) -> Self {
-        Self { data_repo }
+    pub const fn new(data_repo: Repo<SqlitePool>, config: utils::Config) -> Self {
+        Self { data_repo, config }
+    }
+
+    /// Region of phone numbers given without a country code
+    fn region(&self) -> &str {
+        &self.data_repo.region
+    }
+
+    /// The editor command notes and contacts are edited in
+    fn editor(&self) -> String {
+        utils::editor_command(self.config.editor())
+    }
+
+    /// Finds the contact a command names, by ID or else by fuzzy name match.
+    /// A number that is no contact's ID is tried as a name unless `by_id`.
+    async fn resolve_contact(
+        &self,
+        contact: &str,
+        by_id: bool,
+    ) -> Result<models::IndexedContact, anyhow::Error> {
+        let id = contact.trim().parse::<i64>();
+
+        if by_id {
+            let id = id.map_err(|_| anyhow::anyhow!("--id expects a numeric ID, not {contact}"))?;
+            return Ok(self.data_repo.get_contact_by_id(id).await?);
+        }
+
+        if let Ok(id) = id {
+            match self.data_repo.get_contact_by_id(id).await {
+                Err(nbd::Error::NotFound(_)) => {}
+                found => return Ok(found?),
+            }
+        }
+
+        let contacts = self.data_repo.get_all_contacts().await?;
+
+        match (utils::resolve_contact_by_name(contact, &contacts), id) {
+            (Err(nbd::Error::NoMatch(_)), Ok(id)) => Err(nbd::Error::NotFound(id).into()),
+            (resolved, _) => Ok(resolved?),
+        }
     }
 
     pub async fn create_contact(&self, command: &CreateCommand) -> Result<(), anyhow::Error> {
+        let emails = parse_emails(&command.email)?;
+        let phones = parse_phones(&command.phone_number, self.region())?;
+        let frequency = command
+            .frequency
+            .clone()
+            .or_else(|| self.config.cadence().map(|cadence| cadence.to_string()));
+
         let contact = models::Contact::builder()
             .first_name(command.first_name.as_deref().unwrap_or(""))
             .last_name(command.last_name.as_deref().unwrap_or(""))
-            .email(command.email.as_deref().unwrap_or(""))
-            .phone_number(command.phone_number.as_deref().unwrap_or(""))
+            .email(emails.first().map_or("", |email| email.value.as_str()))
+            .phone_number(phones.first().map_or("", |phone| phone.value.as_str()))
             .birthday(command.birthday.as_deref().unwrap_or(""))
+            .frequency(frequency.as_deref().unwrap_or(""))
+            .region(self.region())
             .build()?;
 
         let id = self.data_repo.save_contact(contact).await?;
 
+        self.add_contact_methods(id, &emails, &phones).await?;
+
         println!("Successfully saved contact {id}");
 
         Ok(())
     }
 
     pub async fn edit_contact(&self, command: &EditCommand) -> Result<(), anyhow::Error> {
-        let contact = ContactBuilder::new(
-            command.id,
-            command.first_name.clone(),
-            command.last_name.clone(),
-            command.email.clone(),
-            command.phone_number.clone(),
-            command.display_name.clone(),
-            None,
-        )?;
-
-        self.data_repo.update_contact(contact).await?;
+        let contact = self
+            .resolve_contact(&command.contact, command.by_id)
+            .await?;
+
+        if command.interactive {
+            return self.edit_contact_interactively(&contact).await;
+        }
+
+        let id = contact.id;
+        let mut builder = ContactBuilder::builder().id(id).region(self.region());
+
+        if let Some(first_name) = &command.first_name {
+            builder = builder.first_name(first_name.clone());
+        }
+        if let Some(last_name) = &command.last_name {
+            builder = builder.last_name(last_name.clone());
+        }
+        if let Some(display_name) = &command.display_name {
+            builder = builder.display_name(display_name.clone());
+        }
+        if let Some(frequency) = &command.frequency {
+            builder = builder.frequency(frequency.parse()?);
+        }
+
+        let emails = parse_emails(&command.email)?;
+        let phones = parse_phones(&command.phone_number, self.region())?;
+        let changes_methods = !(emails.is_empty()
+            && phones.is_empty()
+            && command.remove_email.is_empty()
+            && command.remove_phone.is_empty());
+
+        match builder.build() {
+            Ok(contact) => self.data_repo.update_contact(contact).await?,
+            Err(nbd::Error::EmptyUpdate) if changes_methods => {}
+            Err(error) => return Err(error.into()),
+        }
+
+        self.add_contact_methods(id, &emails, &phones).await?;
+
+        for email in &command.remove_email {
+            if !self.data_repo.remove_email(id, email).await? {
+                println!("Contact {id} has no email {email}");
+            }
+        }
+        for phone_number in &command.remove_phone {
+            if !self.data_repo.remove_phone(id, phone_number).await? {
+                println!("Contact {id} has no phone number {phone_number}");
+            }
+        }
 
         println!("Contact updated");
 
         Ok(())
     }
 
-    pub async fn show_all_contacts(&self) -> Result<(), anyhow::Error> {
-        let contacts = self.data_repo.get_all_contacts().await?;
+    /// Opens the contact as a document in the user's editor until it is
+    /// valid, cancelled or left unchanged, then saves the changed fields.
+    async fn edit_contact_interactively(
+        &self,
+        contact: &models::IndexedContact,
+    ) -> Result<(), anyhow::Error> {
+        let mut document = utils::contact_to_document(contact);
+
+        let update = loop {
+            document = utils::edit_text(&document, "toml", &self.editor())?;
+
+            match utils::document_to_update(contact, &document, self.region()) {
+                Ok(Some(update)) => break update,
+                Ok(None) => {
+                    println!("Edit cancelled");
+                    return Ok(());
+                }
+                Err(nbd::Error::EmptyUpdate) => {
+                    println!("Nothing changed");
+                    return Ok(());
+                }
+                Err(error) => document = utils::document_with_error(&document, &error),
+            }
+        };
+
+        self.data_repo.update_contact(update).await?;
 
-        if contacts.is_empty() {
+        println!("Contact updated");
+
+        Ok(())
+    }
+
+    /// Saves labelled emails and phone numbers, the first of each as the
+    /// contact's preferred one.
+    async fn add_contact_methods(
+        &self,
+        id: i64,
+        emails: &[models::Labeled],
+        phones: &[models::Labeled],
+    ) -> Result<(), anyhow::Error> {
+        for (index, email) in emails.iter().enumerate() {
+            self.data_repo.add_email(id, email, index == 0).await?;
+        }
+        for (index, phone) in phones.iter().enumerate() {
+            self.data_repo.add_phone(id, phone, index == 0).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn show_all_contacts(
+        &self,
+        command: &ShowCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let mut contacts = if command.tag.is_empty() && command.not_tag.is_empty() {
+            self.data_repo.get_all_contacts().await?
+        } else {
+            self.data_repo
+                .get_contacts_by_tags(&command.tag, &command.not_tag)
+                .await?
+        };
+
+        for contact in &mut contacts {
+            format_phone_number(contact, command.phone_format, self.region());
+        }
+
+        let sort = match &command.sort {
+            Some(field) => utils::parse_sort_field(field)?,
+            None => self.config.show_sort(),
+        };
+        sort_contacts(&mut contacts, sort);
+
+        let columns = match &command.columns {
+            Some(columns) => utils::parse_show_columns(columns)?,
+            None => self.config.show_columns(),
+        };
+
+        if contacts.is_empty() && format == OutputFormat::Table {
             println!("No contacts yet!");
         } else {
-            let table = Table::new(contacts);
-            println!("{table}");
+            output::print_contact_columns(&contacts, &columns, format, self.config.date_format())?;
         }
 
         Ok(())
     }
 
-    pub async fn get_contact(&self, command: &GetCommand) -> Result<(), anyhow::Error> {
-        let id = command.id;
+    pub async fn get_contact(
+        &self,
+        command: &GetCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let mut contact = self
+            .resolve_contact(&command.contact, command.by_id)
+            .await?;
 
-        let contact = self.data_repo.get_contact_by_id(id).await?;
+        format_phone_number(&mut contact, command.phone_format, self.region());
 
-        println!("{contact:?}");
+        let mut details = output::ContactDetails {
+            emails: self.data_repo.get_emails(contact.id).await?,
+            phones: self.data_repo.get_phones(contact.id).await?,
+            addresses: self.data_repo.get_addresses(contact.id).await?,
+            notes: self.data_repo.get_notes(contact.id).await?,
+            contact,
+        };
+        for phone in &mut details.phones {
+            phone.phone_number = utils::format_phone_number(
+                &phone.phone_number,
+                phone.phone_e164.as_deref(),
+                phone_format(command.phone_format),
+                self.region(),
+            );
+        }
+
+        output::print_contact_details(&details, format, self.config.date_format())?;
+
+        Ok(())
+    }
+
+    pub async fn search_contacts(
+        &self,
+        command: &SearchCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let hits = self.data_repo.search(&command.terms.join(" ")).await?;
+
+        if hits.is_empty() && format == OutputFormat::Table {
+            println!("No contacts match");
+        } else {
+            output::print_search_hits(&hits, format)?;
+        }
 
         Ok(())
     }
 
     pub async fn delete_contact(&self, command: &DeleteCommand) -> Result<(), anyhow::Error> {
-        let id = command.id;
+        let id = self
+            .resolve_contact(&command.contact, command.by_id)
+            .await?
+            .id;
 
         let contact_id = self.data_repo.delete_contact_by_id(id).await?;
 
@@ -83,14 +301,1025 @@ impl Actions {
         Ok(())
     }
 
+    /// Copies contacts of this book into `target` with their tags, emails,
+    /// phones, addresses, notes and interactions, then deletes them here
+    /// when `remove` is set. Every contact is found before any is copied.
+    pub async fn copy_contacts(
+        &self,
+        command: &TransferCommand,
+        target: &Repo<SqlitePool>,
+        remove: bool,
+    ) -> Result<(), anyhow::Error> {
+        let mut contacts = Vec::new();
+        for contact in &command.contacts {
+            contacts.push(self.resolve_contact(contact, command.by_id).await?);
+        }
+
+        for indexed in contacts {
+            let id = indexed.id;
+            let display_name = indexed.contact.display_name.clone();
+            let new_id = self.copy_contact(indexed, target).await?;
+
+            if remove {
+                self.data_repo.delete_contact_by_id(id).await?;
+                println!("Moved {display_name} to {} as contact {new_id}", command.to);
+            } else {
+                println!(
+                    "Copied {display_name} to {} as contact {new_id}",
+                    command.to
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn copy_contact(
+        &self,
+        indexed: models::IndexedContact,
+        target: &Repo<SqlitePool>,
+    ) -> Result<i64, anyhow::Error> {
+        let id = indexed.id;
+        let contact = indexed.contact;
+
+        let copy = models::OptionalContact {
+            first_name: Some(contact.first_name),
+            last_name: Some(contact.last_name),
+            display_name: Some(contact.display_name),
+            email: Some(contact.email),
+            phone_number: Some(contact.phone_number),
+            birthday: contact.birthday,
+            starred: Some(contact.starred),
+            is_archived: Some(contact.is_archived),
+            created_at: Some(contact.created_at),
+            last_seen_at: contact.last_seen_at,
+            frequency: contact.frequency,
+            last_reminder_at: contact.last_reminder_at,
+            external_id: contact.external_id,
+            tags: self.data_repo.get_tags(id).await?,
+            emails: self
+                .data_repo
+                .get_emails(id)
+                .await?
+                .into_iter()
+                .map(|email| models::Labeled::new(email.label, &email.email))
+                .collect(),
+            phones: self
+                .data_repo
+                .get_phones(id)
+                .await?
+                .into_iter()
+                .map(|phone| models::Labeled::new(phone.label, &phone.phone_number))
+                .collect(),
+            addresses: self
+                .data_repo
+                .get_addresses(id)
+                .await?
+                .into_iter()
+                .map(|address| address.address)
+                .collect(),
+            notes: self
+                .data_repo
+                .get_notes(id)
+                .await?
+                .into_iter()
+                .map(|note| note.body)
+                .collect(),
+            cleared: Vec::new(),
+        };
+
+        let new_id = target.save_optional_contact(copy).await?;
+
+        for interaction in self.data_repo.get_interactions(id).await? {
+            target
+                .log_interaction(
+                    new_id,
+                    &interaction.kind,
+                    &interaction.note,
+                    interaction.occurred_at,
+                )
+                .await?;
+        }
+
+        Ok(new_id)
+    }
+
+    pub async fn show_duplicates(&self, format: OutputFormat) -> Result<(), anyhow::Error> {
+        let contacts = self.data_repo.get_all_contacts().await?;
+        let duplicates: Vec<output::DuplicateRow> = utils::find_duplicates(&contacts)
+            .iter()
+            .map(output::DuplicateRow::from)
+            .collect();
+
+        if duplicates.is_empty() && format == OutputFormat::Table {
+            println!("No duplicates found");
+        } else {
+            output::print_records(&duplicates, format)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn merge_contacts(&self, command: &MergeCommand) -> Result<(), anyhow::Error> {
+        let mut take = command
+            .take
+            .iter()
+            .map(|field| field.parse())
+            .collect::<Result<Vec<models::ContactField>, _>>()?;
+
+        if let Some(field) = take
+            .iter()
+            .find(|field| !models::ContactField::MERGEABLE.contains(field))
+        {
+            anyhow::bail!("{field} cannot be taken from another contact")
+        }
+
+        let keep = self.data_repo.get_contact_by_id(command.keep_id).await?;
+        let drop = self.data_repo.get_contact_by_id(command.drop_id).await?;
+
+        if !command.yes && std::io::stdin().is_terminal() {
+            for field in keep.contact.conflicts_with(&drop.contact) {
+                if !take.contains(&field) && ask_to_take(field, &keep, &drop)? {
+                    take.push(field);
+                }
+            }
+        }
+
+        self.data_repo
+            .merge_contacts(command.keep_id, command.drop_id, &take)
+            .await?;
+
+        println!(
+            "Merged contact {} into {}",
+            command.drop_id, command.keep_id
+        );
+
+        Ok(())
+    }
+
     pub async fn import_contacts(&self, command: &ImportCommand) -> Result<(), anyhow::Error> {
-        let number_of_imports = self
+        let format = command.format.map(|format| match format {
+            ImportFormat::Csv => utils::ImportFormat::Csv,
+            ImportFormat::Vcard => utils::ImportFormat::Vcard,
+        });
+
+        let on_duplicate = match command.on_duplicate {
+            OnDuplicate::Skip => utils::OnDuplicate::Skip,
+            OnDuplicate::Update => utils::OnDuplicate::Update,
+            OnDuplicate::Create => utils::OnDuplicate::Create,
+        };
+
+        let mapping = match (&command.profile, &command.mapping) {
+            (_, Some(path)) => utils::HeaderMapping::from_file(Path::new(path))?,
+            (Some(profile), None) => utils::HeaderMapping::from_profile(match profile {
+                CsvProfile::Google => utils::CsvProfile::Google,
+                CsvProfile::Outlook => utils::CsvProfile::Outlook,
+                CsvProfile::Apple => utils::CsvProfile::Apple,
+            }),
+            (None, None) => utils::HeaderMapping::default(),
+        };
+
+        let options = utils::ImportOptions {
+            format,
+            tag_separator: command.tag_separator.clone(),
+            mapping,
+            dry_run: command.dry_run,
+            skip_invalid: command.skip_invalid,
+            on_duplicate,
+        };
+
+        let report = self
             .data_repo
-            .import_contacts_by_csv(&command.filename)
+            .import_contacts(&command.filename, &options)
+            .await?;
+
+        for row in &report.rejected {
+            eprintln!("Skipped {row}");
+        }
+
+        if !report.unmapped_columns.is_empty() {
+            eprintln!(
+                "Warning: ignored {} unmapped column(s): {}",
+                report.unmapped_columns.len(),
+                report.unmapped_columns.join(", ")
+            );
+        }
+
+        let summary = format!(
+            "{} created, {} updated, {} skipped, {} invalid",
+            report.created,
+            report.updated,
+            report.skipped,
+            report.rejected.len()
+        );
+
+        if command.dry_run {
+            println!(
+                "Dry run: would import {} contact ({summary}); nothing was saved",
+                report.imported()
+            );
+        } else {
+            println!(
+                "Successfully imported {} contact ({summary})",
+                report.imported()
+            );
+        }
+
+        Ok(())
+    }
+
+    pub async fn export_contacts(&self, command: &ExportCommand) -> Result<(), anyhow::Error> {
+        let mut contacts = if command.ids.is_empty() {
+            self.data_repo.get_all_contacts().await?
+        } else {
+            let mut contacts = Vec::with_capacity(command.ids.len());
+            for id in &command.ids {
+                contacts.push(self.data_repo.get_contact_by_id(*id).await?);
+            }
+            contacts
+        };
+
+        if command.exclude_archived {
+            contacts.retain(|indexed| !indexed.contact.is_archived);
+        }
+
+        let exported = match command.format {
+            ExportFormat::Vcard => {
+                if !command.columns.is_empty() {
+                    anyhow::bail!("--columns only applies to CSV exports")
+                }
+
+                let version = match command.vcard_version {
+                    VcardVersion::V3 => utils::VcardVersion::V3,
+                    VcardVersion::V4 => utils::VcardVersion::V4,
+                };
+
+                let mut cards = Vec::with_capacity(contacts.len());
+                for indexed in &contacts {
+                    cards.push(self.data_repo.get_contact_card(indexed.id).await?);
+                }
+
+                utils::contacts_to_vcards(&cards, version).into_bytes()
+            }
+            ExportFormat::Csv => {
+                let columns = command
+                    .columns
+                    .iter()
+                    .map(|column| column.parse())
+                    .collect::<Result<Vec<models::ContactField>, _>>()?;
+
+                let mut output = Vec::new();
+                utils::write_contacts_csv(&contacts, &columns, &mut output)?;
+                output
+            }
+        };
+
+        match &command.file {
+            Some(filename) => {
+                std::fs::write(filename, exported)?;
+                println!("Successfully exported {} contact", contacts.len());
+            }
+            None => std::io::stdout().write_all(&exported)?,
+        }
+
+        Ok(())
+    }
+
+    pub async fn manage_tags(
+        &self,
+        command: &TagCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        match command {
+            TagCommand::Add(command) => {
+                for tag in &command.tags {
+                    self.data_repo.add_tag(command.id, tag).await?;
+                }
+
+                println!("Tagged contact {}", command.id);
+            }
+            TagCommand::Rm(command) => {
+                for tag in &command.tags {
+                    if !self.data_repo.remove_tag(command.id, tag).await? {
+                        println!("Contact {} is not tagged {tag}", command.id);
+                    }
+                }
+            }
+            TagCommand::Ls(command) => {
+                if let Some(id) = command.id {
+                    self.data_repo.get_contact_by_id(id).await?;
+                    let tags = self.data_repo.get_tags(id).await?;
+                    output::print_tag_names(&tags, format)?;
+                } else {
+                    let tags = self.data_repo.get_all_tags().await?;
+                    output::print_records(&tags, format)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn manage_addresses(
+        &self,
+        command: &AddressCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        match command {
+            AddressCommand::Add(command) => {
+                let contact_id = self
+                    .resolve_contact(&command.contact, command.by_id)
+                    .await?
+                    .id;
+                let address =
+                    apply_address_args(models::PostalAddress::default(), &command.address)?;
+
+                let id = self.data_repo.add_address(contact_id, &address).await?;
+
+                println!("Added address {id} to contact {contact_id}");
+            }
+            AddressCommand::Edit(command) => {
+                let existing = self.data_repo.get_address(command.id).await?;
+                let address = apply_address_args(existing.address, &command.address)?;
+
+                self.data_repo.update_address(command.id, &address).await?;
+
+                println!("Address updated");
+            }
+            AddressCommand::Rm(command) => {
+                for id in &command.ids {
+                    if !self.data_repo.remove_address(*id).await? {
+                        println!("There is no address {id}");
+                    }
+                }
+            }
+            AddressCommand::Ls(command) => {
+                let contact_id = self
+                    .resolve_contact(&command.contact, command.by_id)
+                    .await?
+                    .id;
+                let addresses = self.data_repo.get_addresses(contact_id).await?;
+
+                output::print_records(&addresses, format)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints one label per contact that has an address, using the address
+    /// with the label asked for when there is one and the oldest otherwise.
+    /// Archived contacts are left out.
+    pub async fn print_labels(
+        &self,
+        command: &LabelsCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let preferred_label = command
+            .label
+            .as_deref()
+            .map(str::parse::<models::Label>)
+            .transpose()?;
+
+        let contacts = if command.tag.is_empty() && command.not_tag.is_empty() {
+            self.data_repo.get_all_contacts().await?
+        } else {
+            self.data_repo
+                .get_contacts_by_tags(&command.tag, &command.not_tag)
+                .await?
+        };
+        let addresses = self.data_repo.get_all_addresses().await?;
+
+        let mut labels = Vec::new();
+        let mut skipped: usize = 0;
+        for contact in contacts
+            .iter()
+            .filter(|contact| !contact.contact.is_archived)
+        {
+            let mut own = addresses
+                .iter()
+                .filter(|address| address.contact_id == contact.id);
+            let first = own.clone().next();
+            let chosen = own
+                .find(|address| Some(address.address.label) == preferred_label)
+                .or(first);
+
+            match chosen {
+                Some(address) => labels.push(output::MailingLabel::new(
+                    &contact.contact,
+                    &address.address,
+                )),
+                None => skipped = skipped.saturating_add(1),
+            }
+        }
+
+        output::print_mailing_labels(&labels, format)?;
+
+        if skipped > 0 {
+            eprintln!("Skipped {skipped} contact(s) without an address");
+        }
+
+        Ok(())
+    }
+
+    pub async fn manage_notes(
+        &self,
+        command: &NoteCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        match command {
+            NoteCommand::Add(command) => {
+                let contact_id = self
+                    .resolve_contact(&command.contact, command.by_id)
+                    .await?
+                    .id;
+                let body = note_text(&command.text, "", &self.editor())?;
+
+                let id = self.data_repo.add_note(contact_id, &body).await?;
+
+                println!("Added note {id} to contact {contact_id}");
+            }
+            NoteCommand::Edit(command) => {
+                let note = self.data_repo.get_note(command.id).await?;
+                let body = note_text(&command.text, &note.body, &self.editor())?;
+
+                self.data_repo.update_note(command.id, &body).await?;
+
+                println!("Note updated");
+            }
+            NoteCommand::Ls(command) => {
+                let contact_id = self
+                    .resolve_contact(&command.contact, command.by_id)
+                    .await?
+                    .id;
+                let notes = self.data_repo.get_notes(contact_id).await?;
+
+                if notes.is_empty() && format == OutputFormat::Table {
+                    println!("No notes yet!");
+                } else {
+                    output::print_notes(&notes, format, self.config.date_format())?;
+                }
+            }
+            NoteCommand::Rm(command) => {
+                for id in &command.ids {
+                    if !self.data_repo.remove_note(*id).await? {
+                        println!("There is no note {id}");
+                    }
+                }
+            }
+            NoteCommand::Grep(command) => {
+                let pattern = RegexBuilder::new(&command.pattern)
+                    .case_insensitive(true)
+                    .build()?;
+                let contacts = self.data_repo.get_all_contacts().await?;
+                let notes = self.data_repo.get_all_notes().await?;
+
+                let matches: Vec<output::NoteMatch> = notes
+                    .iter()
+                    .flat_map(|note| {
+                        let contact = contacts
+                            .iter()
+                            .find(|contact| contact.id == note.contact_id)
+                            .map(|contact| contact.contact.display_name.clone())
+                            .unwrap_or_default();
+                        note.body
+                            .lines()
+                            .filter(|line| pattern.is_match(line))
+                            .map(move |line| output::NoteMatch {
+                                note: note.id,
+                                contact: contact.clone(),
+                                line: line.trim().to_owned(),
+                            })
+                    })
+                    .collect();
+
+                if matches.is_empty() && format == OutputFormat::Table {
+                    println!("No notes match {}", command.pattern);
+                } else {
+                    output::print_records(&matches, format)?;
+                }
+            }
+            NoteCommand::History(command) => {
+                let note = self.data_repo.get_note(command.id).await?;
+                let mut revisions = self.data_repo.get_note_revisions(command.id).await?;
+                revisions.push(models::NoteRevision {
+                    note_id: note.id,
+                    written_at: note.updated_at,
+                    body: note.body,
+                });
+
+                output::print_revisions(&revisions, format, self.config.date_format())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn log_interaction(&self, command: &LogCommand) -> Result<(), anyhow::Error> {
+        let occurred_at = match &command.at {
+            Some(at) => utils::parse_timestamp(at)?,
+            None => Utc::now(),
+        };
+
+        self.data_repo
+            .log_interaction(command.id, &command.kind, &command.note, occurred_at)
             .await?;
 
-        println!("Successfully imported {number_of_imports} contact");
+        println!("Logged {} with contact {}", command.kind, command.id);
 
         Ok(())
     }
+
+    pub async fn show_history(
+        &self,
+        command: &HistoryCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        self.data_repo.get_contact_by_id(command.id).await?;
+
+        let interactions = self.data_repo.get_interactions(command.id).await?;
+
+        if interactions.is_empty() && format == OutputFormat::Table {
+            println!("No interactions logged yet");
+        } else {
+            output::print_interactions(&interactions, format, self.config.date_format())?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn show_due_contacts(
+        &self,
+        command: &DueCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let now = Utc::now();
+
+        let due_by = match &command.within {
+            Some(within) => within
+                .parse::<models::Cadence>()?
+                .after(now)
+                .ok_or_else(|| anyhow::anyhow!("--within {within} is too far ahead"))?,
+            None => now,
+        };
+
+        let contacts = self.data_repo.get_due_contacts(due_by).await?;
+
+        if contacts.is_empty() && format == OutputFormat::Table {
+            println!("Nobody is due a catch-up");
+        } else {
+            let due: Vec<output::DueContact> = contacts
+                .iter()
+                .filter_map(|indexed| {
+                    output::DueContact::new(indexed, now, self.config.date_format())
+                })
+                .collect();
+            output::print_records(&due, format)?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn show_birthdays(
+        &self,
+        command: &BirthdaysCommand,
+        format: OutputFormat,
+    ) -> Result<(), anyhow::Error> {
+        let now = Utc::now();
+        let today = now.with_timezone(&Local).date_naive();
+        let until = command
+            .within
+            .parse::<models::Cadence>()?
+            .after(now)
+            .ok_or_else(|| anyhow::anyhow!("--within {} is too far ahead", command.within))?
+            .with_timezone(&Local)
+            .date_naive();
+
+        let contacts = self.data_repo.get_all_contacts().await?;
+
+        let mut upcoming: Vec<output::UpcomingBirthday> = contacts
+            .iter()
+            .filter(|indexed| !indexed.contact.is_archived)
+            .filter_map(|indexed| {
+                output::UpcomingBirthday::new(indexed, today, self.config.date_format())
+            })
+            .filter(|birthday| birthday.date <= until)
+            .collect();
+
+        upcoming.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+
+        if upcoming.is_empty() && format == OutputFormat::Table {
+            println!("No birthdays in the next {}", command.within);
+        } else {
+            output::print_records(&upcoming, format)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the calendar of the open book, named `book` in event UIDs.
+    pub async fn export_calendar(
+        &self,
+        command: &ExportCalendarCommand,
+        book: &str,
+    ) -> Result<(), anyhow::Error> {
+        let contacts = self.data_repo.get_all_contacts().await?;
+
+        let calendar = utils::contacts_to_icalendar(&contacts, book, Utc::now());
+        std::fs::write(&command.filename, calendar)?;
+
+        println!(
+            "Successfully exported {} calendar event",
+            utils::count_calendar_events(&contacts)
+        );
+
+        Ok(())
+    }
+}
+
+/// Asks which of two conflicting values a merge keeps; true takes the
+/// dropped contact's.
+fn ask_to_take(
+    field: models::ContactField,
+    keep: &models::IndexedContact,
+    drop: &models::IndexedContact,
+) -> Result<bool, anyhow::Error> {
+    let mut stdout = std::io::stdout().lock();
+    let mut answers = std::io::stdin().lock().lines();
+
+    writeln!(stdout, "{field} differs:")?;
+    writeln!(stdout, "  1) {} (contact {})", field.value(keep), keep.id)?;
+    writeln!(stdout, "  2) {} (contact {})", field.value(drop), drop.id)?;
+
+    loop {
+        write!(stdout, "Keep which? [1]: ")?;
+        stdout.flush()?;
+
+        match answers.next().transpose()?.as_deref().map(str::trim) {
+            None | Some("" | "1") => return Ok(false),
+            Some("2") => return Ok(true),
+            Some(_) => writeln!(stdout, "Please answer 1 or 2")?,
+        }
+    }
+}
+
+/// Orders contacts by `field`: IDs by number and anything else by its text,
+/// ignoring case, with empty values last. Ties keep their order.
+fn sort_contacts(contacts: &mut [models::IndexedContact], field: models::ContactField) {
+    if field == models::ContactField::Id {
+        contacts.sort_by_key(|contact| contact.id);
+        return;
+    }
+
+    contacts.sort_by_cached_key(|contact| {
+        let value = field.value(contact).to_lowercase();
+        (value.is_empty(), value)
+    });
+}
+
+/// Runs `config get|set|list|path` against the settings file at `path`.
+pub fn manage_config(
+    command: &ConfigCommand,
+    path: &Path,
+    format: OutputFormat,
+) -> Result<(), anyhow::Error> {
+    match command {
+        ConfigCommand::Get(command) => {
+            let setting = command.key.parse()?;
+            if let Some(value) = utils::Config::load(path)?.get(setting) {
+                println!("{value}");
+            }
+        }
+        ConfigCommand::Set(command) => {
+            let setting: utils::Setting = command.key.parse()?;
+            let mut config = utils::Config::load(path)?;
+            config.set(setting, &command.value)?;
+            config.save(path)?;
+
+            match config.get(setting) {
+                Some(value) => println!("Set {} to {value}", setting.as_str()),
+                None => println!("Unset {}", setting.as_str()),
+            }
+        }
+        ConfigCommand::List => {
+            let config = utils::Config::load(path)?;
+            let settings: Vec<output::SettingRow> = utils::Setting::ALL
+                .into_iter()
+                .map(|setting| output::SettingRow {
+                    setting: setting.as_str(),
+                    value: config.get(setting).unwrap_or_default(),
+                })
+                .collect();
+            output::print_records(&settings, format)?;
+        }
+        ConfigCommand::Path => println!("{}", path.display()),
+    }
+
+    Ok(())
+}
+
+/// Runs `books ls|rename|rm` against the books of `config_dir`, keeping the
+/// `book` setting of the config file at `config_path` pointed at a book that
+/// exists. `books copy|move` need the open book, so they go through
+/// [`Actions::copy_contacts`] instead.
+pub async fn manage_books(
+    command: &BooksCommand,
+    config_dir: &Path,
+    config_path: &Path,
+    format: OutputFormat,
+) -> Result<(), anyhow::Error> {
+    let mut config = utils::Config::load(config_path)?;
+
+    match command {
+        BooksCommand::Ls => {
+            let mut books = Vec::new();
+            for name in utils::list_books(config_dir)? {
+                let path = utils::build_book_path(config_dir, &name)?;
+                let pool = db::setup::open(&path).await?;
+                let contacts = Repo::new(pool.clone()).get_all_contacts().await?.len();
+                pool.close().await;
+
+                books.push(output::BookRow {
+                    default: name == config.book(),
+                    name,
+                    contacts,
+                    path: path.display().to_string(),
+                });
+            }
+
+            if books.is_empty() && format == OutputFormat::Table {
+                println!("No books yet; run `nbd-cli init` to create one");
+            } else {
+                output::print_records(&books, format)?;
+            }
+        }
+        BooksCommand::Rename(command) => {
+            db::setup::rename_book(config_dir, &command.from, &command.to)?;
+
+            if config.book() == command.from {
+                config.set(utils::Setting::Book, &command.to)?;
+                config.save(config_path)?;
+            }
+
+            println!("Renamed book {} to {}", command.from, command.to);
+        }
+        BooksCommand::Rm(command) => {
+            if !command.yes {
+                if !std::io::stdin().is_terminal() {
+                    anyhow::bail!("Pass --yes to delete the book {}", command.name);
+                }
+                if !ask_to_confirm(&format!("Delete the book {}?", command.name))? {
+                    println!("Kept book {}", command.name);
+                    return Ok(());
+                }
+            }
+
+            db::setup::remove_book(config_dir, &command.name)?;
+
+            if config.book() == command.name {
+                config.set(utils::Setting::Book, "")?;
+                config.save(config_path)?;
+            }
+
+            println!("Deleted book {}", command.name);
+        }
+        BooksCommand::Copy(_) | BooksCommand::Move(_) => {}
+    }
+
+    Ok(())
+}
+
+/// Runs `db status|migrate|check` against the book at `db_path`, which is
+/// opened without migrating it so pending migrations can be seen first.
+/// Tables write dates in `date_format`.
+pub async fn manage_database(
+    command: &DbCommand,
+    db_path: &Path,
+    format: OutputFormat,
+    date_format: &str,
+) -> Result<(), anyhow::Error> {
+    let pool = db::setup::connect(db_path).await?;
+
+    match command {
+        DbCommand::Status => {
+            let migrations: Vec<output::MigrationRow> = db::maintenance::migration_status(&pool)
+                .await?
+                .iter()
+                .map(|status| output::MigrationRow::new(status, date_format))
+                .collect();
+            output::print_records(&migrations, format)?;
+        }
+        DbCommand::Migrate => {
+            let migrated = db::maintenance::migrate(&pool, db_path).await?;
+
+            if let Some(backup) = &migrated.backup {
+                println!("Backed up {} to {}", db_path.display(), backup.display());
+            }
+            match migrated.applied.len() {
+                0 => println!("The book is up to date"),
+                1 => println!("Applied 1 migration"),
+                count => println!("Applied {count} migrations"),
+            }
+        }
+        DbCommand::Check => {
+            let problems = db::maintenance::check(&pool).await?;
+
+            if problems.is_empty() {
+                println!("No problems found");
+            } else {
+                for problem in &problems {
+                    println!("{problem}");
+                }
+                anyhow::bail!(
+                    "Found {} problem(s) in {}",
+                    problems.len(),
+                    db_path.display()
+                );
+            }
+        }
+    }
+
+    pool.close().await;
+
+    Ok(())
+}
+
+/// Copies the book at `db_path` into its backups folder, then deletes the
+/// scheduled backups that fall outside the retention rules.
+pub async fn backup_book(command: &BackupCommand, db_path: &Path) -> Result<(), anyhow::Error> {
+    let pool = db::setup::connect(db_path).await?;
+    let backup = db::maintenance::backup(&pool, db_path, db::maintenance::SCHEDULED_BACKUP).await?;
+    pool.close().await;
+
+    println!("Backed up {} to {}", db_path.display(), backup.display());
+
+    for pruned in db::maintenance::prune_backups(db_path, command.keep_daily, command.keep_weekly)?
+    {
+        println!("Removed old backup {}", pruned.path.display());
+    }
+
+    Ok(())
+}
+
+/// Swaps the backup `command` names in for the book at `db_path`. A name
+/// that is no file is looked up in the backups folder of the book.
+pub async fn restore_book(command: &RestoreCommand, db_path: &Path) -> Result<(), anyhow::Error> {
+    let backup = if command.backup.exists() {
+        command.backup.clone()
+    } else {
+        db::maintenance::backups_dir(db_path).join(&command.backup)
+    };
+
+    let restored = db::maintenance::restore(db_path, &backup).await?;
+
+    if let Some(replaced) = &restored.replaced {
+        println!("Backed up {} to {}", db_path.display(), replaced.display());
+    }
+    println!(
+        "Restored {} (schema version {}) to {}",
+        backup.display(),
+        restored.version,
+        db_path.display()
+    );
+    match restored.migrated.applied.len() {
+        0 => {}
+        1 => println!("Applied 1 migration"),
+        count => println!("Applied {count} migrations"),
+    }
+
+    Ok(())
+}
+
+/// Opens the book named `name` to copy contacts into from the book at
+/// `source`, reading phone numbers in `region`.
+pub async fn open_target_book(
+    name: &str,
+    source: &Path,
+    region: &str,
+) -> Result<Repo<SqlitePool>, anyhow::Error> {
+    let path = utils::build_book_path(&utils::get_config_dir(), name)?;
+
+    if !path.exists() {
+        return Err(nbd::Error::BookNotFound(name.to_owned()).into());
+    }
+    if path == source {
+        return Err(nbd::Error::InvalidBook {
+            name: name.to_owned(),
+            reason: "the contacts are already in this book".to_owned(),
+        }
+        .into());
+    }
+
+    Ok(Repo::new(db::setup::open(&path).await?).with_region(region))
+}
+
+/// Asks a yes-or-no `question`; anything but yes counts as no.
+fn ask_to_confirm(question: &str) -> Result<bool, anyhow::Error> {
+    let mut stdout = std::io::stdout().lock();
+    write!(stdout, "{question} [y/N]: ")?;
+    stdout.flush()?;
+
+    let answer = std::io::stdin().lock().lines().next().transpose()?;
+
+    Ok(matches!(
+        answer.as_deref().map(str::trim),
+        Some("y" | "Y" | "yes")
+    ))
+}
+
+/// Rewrites a contact's phone number in the style asked for on the command
+/// line, leaving numbers that do not parse in `region` as they were entered.
+fn format_phone_number(contact: &mut models::IndexedContact, format: PhoneFormat, region: &str) {
+    contact.contact.phone_number = utils::format_phone_number(
+        &contact.contact.phone_number,
+        contact.contact.phone_e164.as_deref(),
+        phone_format(format),
+        region,
+    );
+}
+
+const fn phone_format(format: PhoneFormat) -> utils::PhoneFormat {
+    match format {
+        PhoneFormat::Original => utils::PhoneFormat::Original,
+        PhoneFormat::National => utils::PhoneFormat::National,
+        PhoneFormat::International => utils::PhoneFormat::International,
+        PhoneFormat::E164 => utils::PhoneFormat::E164,
+    }
+}
+
+/// The text of a note given on the command line, or else written in
+/// `editor` starting from `current`.
+fn note_text(words: &[String], current: &str, editor: &str) -> nbd::Result<String> {
+    let text = if words.is_empty() {
+        utils::edit_text(current, "md", editor)?
+    } else {
+        words.join(" ")
+    };
+
+    if text.trim().is_empty() {
+        return Err(nbd::Error::EmptyNote);
+    }
+
+    Ok(text)
+}
+
+/// Overwrites the parts of `address` given on the command line.
+fn apply_address_args(
+    mut address: models::PostalAddress,
+    args: &AddressArgs,
+) -> nbd::Result<models::PostalAddress> {
+    let parts = [
+        (&args.street, &mut address.street),
+        (&args.locality, &mut address.locality),
+        (&args.region, &mut address.region),
+        (&args.postal_code, &mut address.postal_code),
+        (&args.country, &mut address.country),
+    ];
+
+    let mut changed = args.label.is_some();
+    for (argument, part) in parts {
+        if let Some(value) = argument {
+            value.trim().clone_into(part);
+            changed = true;
+        }
+    }
+
+    if !changed {
+        return Err(nbd::Error::EmptyUpdate);
+    }
+    if let Some(label) = &args.label {
+        address.label = models::PostalAddress::parse_label(label)?;
+    }
+
+    Ok(address)
+}
+
+/// Reads `label:address` arguments, checking every address before anything
+/// is saved.
+fn parse_emails(values: &[String]) -> nbd::Result<Vec<models::Labeled>> {
+    values
+        .iter()
+        .map(|value| {
+            let email = value.parse::<models::Labeled>()?;
+            if utils::is_not_valid_email(&email.value) {
+                return Err(nbd::Error::InvalidEmail(email.value));
+            }
+            Ok(email)
+        })
+        .collect()
+}
+
+/// Reads `label:number` arguments, checking every number in `region` before
+/// anything is saved.
+fn parse_phones(values: &[String], region: &str) -> nbd::Result<Vec<models::Labeled>> {
+    values
+        .iter()
+        .map(|value| {
+            let phone = value.parse::<models::Labeled>()?;
+            if utils::is_not_valid_phone_number(&phone.value, region) {
+                return Err(nbd::Error::InvalidPhone(phone.value));
+            }
+            Ok(phone)
+        })
+        .collect()
 }
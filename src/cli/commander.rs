@@ -1,34 +1,118 @@
-use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Output format of commands that print contacts; defaults to the output
+    /// setting, or table
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<OutputFormat>,
+
+    /// Contact book to open instead of contacts.db in the config directory
+    #[arg(long, global = true, value_name = "PATH")]
+    pub db: Option<PathBuf>,
+
+    /// Named book of the config directory to open, unless --db is given;
+    /// defaults to the book setting, or the default book
+    #[arg(long, global = true, value_name = "NAME")]
+    pub book: Option<String>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Jsonl,
+    Csv,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Initialize a new contact book
+    /// Initialize a new contact book, or the book named by --book
     Init,
 
     /// Create a contact
     Create(CreateCommand),
 
-    /// Edit a contact by ID
+    /// Edit a contact by ID or name
     Edit(EditCommand),
 
     /// Get all contacts
-    Show,
+    Show(ShowCommand),
 
     /// Get a contact
     Get(GetCommand),
 
+    /// Search contacts by name, email, phone or notes
+    Search(SearchCommand),
+
     /// Delete a contact
     Delete(DeleteCommand),
 
-    /// Import contact via CSV
+    /// List contacts that look like duplicates
+    Dedupe,
+
+    /// Merge a duplicate contact into another
+    Merge(MergeCommand),
+
+    /// Import contacts via CSV or vCard
     Import(ImportCommand),
+
+    /// Export contacts as vCard or CSV
+    Export(ExportCommand),
+
+    /// Manage contact tags
+    #[command(subcommand)]
+    Tag(TagCommand),
+
+    /// Manage postal addresses
+    #[command(subcommand)]
+    Address(AddressCommand),
+
+    /// Print mailing labels for contacts with an address
+    Labels(LabelsCommand),
+
+    /// Manage notes about contacts
+    #[command(subcommand)]
+    Note(NoteCommand),
+
+    /// Log an interaction with a contact
+    Log(LogCommand),
+
+    /// List past interactions with a contact
+    History(HistoryCommand),
+
+    /// List contacts who are due a catch-up
+    Due(DueCommand),
+
+    /// List upcoming birthdays
+    Birthdays(BirthdaysCommand),
+
+    /// Export birthdays and reminders as an iCalendar file
+    ExportCalendar(ExportCalendarCommand),
+
+    /// Read and change settings in config.toml
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
+    /// List, rename and remove books, or copy contacts between them
+    #[command(subcommand)]
+    Books(BooksCommand),
+
+    /// Inspect, migrate and check the database of the open book
+    #[command(subcommand)]
+    Db(DbCommand),
+
+    /// Save a copy of the open book and prune old copies
+    Backup(BackupCommand),
+
+    /// Replace the open book with a backup
+    Restore(RestoreCommand),
 }
 
 #[derive(Args)]
@@ -42,20 +126,33 @@ pub struct CreateCommand {
     #[arg(short, long, value_name = "Display Name")]
     pub display_name: Option<String>,
 
+    /// Email address, optionally labelled as in work:ada@example.com;
+    /// repeatable, the first is preferred
     #[arg(short, long)]
-    pub email: Option<String>,
+    pub email: Vec<String>,
 
+    /// Phone number, optionally labelled as in mobile:201-555-0123;
+    /// repeatable, the first is preferred
     #[arg(short, long, value_name = "Phone")]
-    pub phone_number: Option<String>,
+    pub phone_number: Vec<String>,
 
     #[arg(short, long, value_name = "Birthday")]
     pub birthday: Option<String>,
+
+    /// How often to keep in touch, such as monthly or "every 2 weeks"
+    #[arg(long, value_name = "CADENCE")]
+    pub frequency: Option<String>,
 }
 
 #[derive(Args, Debug)]
 pub struct EditCommand {
-    /// ID of contact to edit
-    pub id: i64,
+    /// ID or name of contact to edit
+    #[arg(value_name = "CONTACT")]
+    pub contact: String,
+
+    /// Treat CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
 
     #[arg(short, long, value_name = "First Name")]
     pub first_name: Option<String>,
@@ -66,27 +163,576 @@ pub struct EditCommand {
     #[arg(short, long, value_name = "Display Name")]
     pub display_name: Option<String>,
 
+    /// Add an email address, optionally labelled as in work:ada@example.com;
+    /// repeatable, the first becomes preferred
     #[arg(short, long)]
-    pub email: Option<String>,
+    pub email: Vec<String>,
 
+    /// Add a phone number, optionally labelled as in mobile:201-555-0123;
+    /// repeatable, the first becomes preferred
     #[arg(short, long, value_name = "Phone")]
-    pub phone_number: Option<String>,
+    pub phone_number: Vec<String>,
+
+    /// Remove an email address; repeatable
+    #[arg(long, value_name = "EMAIL")]
+    pub remove_email: Vec<String>,
+
+    /// Remove a phone number; repeatable
+    #[arg(long, value_name = "Phone")]
+    pub remove_phone: Vec<String>,
+
+    /// How often to keep in touch, such as monthly or "every 2 weeks"
+    #[arg(long, value_name = "CADENCE")]
+    pub frequency: Option<String>,
+
+    /// Edit every field as a TOML document in $VISUAL or $EDITOR
+    #[arg(
+        short,
+        long,
+        conflicts_with_all = [
+            "first_name", "last_name", "display_name", "email", "phone_number",
+            "remove_email", "remove_phone", "frequency",
+        ]
+    )]
+    pub interactive: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ShowCommand {
+    /// Only show contacts with this tag; repeat to require several
+    #[arg(long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Hide contacts with this tag; repeatable
+    #[arg(long, value_name = "TAG")]
+    pub not_tag: Vec<String>,
+
+    /// Style to write phone numbers in
+    #[arg(long, value_enum, default_value_t = PhoneFormat::Original)]
+    pub phone_format: PhoneFormat,
+
+    /// Comma-separated table columns to print; defaults to the `show_columns`
+    /// setting, or all of them
+    #[arg(long, value_name = "COLUMNS")]
+    pub columns: Option<String>,
+
+    /// Field to sort contacts by; defaults to the `show_sort` setting, or id
+    #[arg(long, value_name = "FIELD")]
+    pub sort: Option<String>,
 }
 
 #[derive(Args, Debug)]
 pub struct GetCommand {
-    /// ID of contact to get
-    pub id: i64,
+    /// ID or name of contact to get
+    #[arg(value_name = "CONTACT")]
+    pub contact: String,
+
+    /// Treat CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
+
+    /// Style to write phone numbers in
+    #[arg(long, value_enum, default_value_t = PhoneFormat::Original)]
+    pub phone_format: PhoneFormat,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PhoneFormat {
+    /// As it was entered
+    Original,
+    /// As dialled within its region, e.g. (201) 555-0123
+    National,
+    /// With its country code, e.g. +1 201-555-0123
+    International,
+    /// Digits only with the country code, e.g. +12015550123
+    E164,
+}
+
+#[derive(Args, Debug)]
+pub struct SearchCommand {
+    /// Words to look for; contacts must match all of them
+    #[arg(required = true)]
+    pub terms: Vec<String>,
 }
 
 #[derive(Args, Debug)]
 pub struct DeleteCommand {
-    /// ID of contact to delete
-    pub id: i64,
+    /// ID or name of contact to delete
+    #[arg(value_name = "CONTACT")]
+    pub contact: String,
+
+    /// Treat CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct MergeCommand {
+    /// ID of contact to keep
+    #[arg(value_name = "KEEP_ID")]
+    pub keep_id: i64,
+
+    /// ID of contact to fold into it and delete
+    #[arg(value_name = "DROP_ID")]
+    pub drop_id: i64,
+
+    /// Take this field from the dropped contact; repeatable
+    #[arg(long, value_name = "FIELD")]
+    pub take: Vec<String>,
+
+    /// Keep the kept contact's value of every other conflicting field
+    /// instead of asking
+    #[arg(short, long)]
+    pub yes: bool,
 }
 
 #[derive(Args, Debug)]
 pub struct ImportCommand {
-    /// name of CSV file
+    /// name of CSV or vCard file
     pub filename: String,
+
+    /// File format, detected from the extension when omitted
+    #[arg(long, value_enum)]
+    pub format: Option<ImportFormat>,
+
+    /// Separator between tags in the CSV tags column
+    #[arg(long, value_name = "SEP", default_value = nbd::utils::DEFAULT_TAG_SEPARATOR)]
+    pub tag_separator: String,
+
+    /// Read the CSV export of an address book by its own headers
+    #[arg(long, value_enum, conflicts_with = "mapping")]
+    pub profile: Option<CsvProfile>,
+
+    /// TOML file mapping CSV headers to contact fields
+    #[arg(long, value_name = "FILE")]
+    pub mapping: Option<String>,
+
+    /// Report what would be imported without saving anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Import the valid rows and report the invalid ones, instead of
+    /// importing nothing
+    #[arg(long)]
+    pub skip_invalid: bool,
+
+    /// What to do with rows that match an existing contact
+    #[arg(long, value_enum, default_value_t = OnDuplicate::Update)]
+    pub on_duplicate: OnDuplicate,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ImportFormat {
+    Csv,
+    Vcard,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CsvProfile {
+    Google,
+    Outlook,
+    Apple,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum OnDuplicate {
+    /// Leave the existing contact alone
+    Skip,
+    /// Fill the existing contact in with the row's values
+    Update,
+    /// Add the row as a new contact anyway
+    Create,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportCommand {
+    /// IDs of contacts to export, the whole book when omitted
+    pub ids: Vec<i64>,
+
+    /// File to write to, standard output when omitted
+    #[arg(long, value_name = "FILE")]
+    pub file: Option<String>,
+
+    /// Export format
+    #[arg(long, value_enum, default_value_t = ExportFormat::Vcard)]
+    pub format: ExportFormat,
+
+    /// vCard version to write
+    #[arg(long, value_enum, default_value_t = VcardVersion::V4)]
+    pub vcard_version: VcardVersion,
+
+    /// Comma-separated CSV columns to write, every column when omitted
+    #[arg(long, value_delimiter = ',', value_name = "COLUMNS")]
+    pub columns: Vec<String>,
+
+    /// Leave archived contacts out of the export
+    #[arg(long)]
+    pub exclude_archived: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    Vcard,
+    Csv,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum VcardVersion {
+    #[value(name = "3.0")]
+    V3,
+    #[value(name = "4.0")]
+    V4,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TagCommand {
+    /// Tag a contact
+    Add(TagContactCommand),
+
+    /// Remove tags from a contact
+    Rm(TagContactCommand),
+
+    /// List the tags of a contact, or every tag with its number of contacts
+    Ls(TagListCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct TagContactCommand {
+    /// ID of contact to tag
+    pub id: i64,
+
+    /// Tags to add or remove
+    #[arg(required = true)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct TagListCommand {
+    /// ID of contact whose tags to list, every tag when omitted
+    pub id: Option<i64>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AddressCommand {
+    /// Add an address to a contact
+    Add(AddAddressCommand),
+
+    /// Change parts of an address
+    Edit(EditAddressCommand),
+
+    /// Remove addresses by ID
+    Rm(RemoveAddressCommand),
+
+    /// List the addresses of a contact with their IDs
+    Ls(ListAddressesCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct AddAddressCommand {
+    /// ID or name of contact the address belongs to
+    #[arg(value_name = "CONTACT")]
+    pub contact: String,
+
+    /// Treat CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
+
+    #[command(flatten)]
+    pub address: AddressArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct EditAddressCommand {
+    /// ID of the address, as listed by `address ls`
+    pub id: i64,
+
+    #[command(flatten)]
+    pub address: AddressArgs,
+}
+
+#[derive(Args, Debug)]
+pub struct RemoveAddressCommand {
+    /// IDs of the addresses, as listed by `address ls`
+    #[arg(required = true)]
+    pub ids: Vec<i64>,
+}
+
+#[derive(Args, Debug)]
+pub struct ListAddressesCommand {
+    /// ID or name of contact whose addresses to list
+    #[arg(value_name = "CONTACT")]
+    pub contact: String,
+
+    /// Treat CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct AddressArgs {
+    /// home, work or other
+    #[arg(long)]
+    pub label: Option<String>,
+
+    #[arg(long)]
+    pub street: Option<String>,
+
+    /// City, town or village
+    #[arg(long)]
+    pub locality: Option<String>,
+
+    /// State, province or county
+    #[arg(long)]
+    pub region: Option<String>,
+
+    #[arg(long)]
+    pub postal_code: Option<String>,
+
+    #[arg(long)]
+    pub country: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LabelsCommand {
+    /// Only include contacts with this tag; repeat to require several
+    #[arg(long, value_name = "TAG")]
+    pub tag: Vec<String>,
+
+    /// Leave out contacts with this tag; repeatable
+    #[arg(long, value_name = "TAG")]
+    pub not_tag: Vec<String>,
+
+    /// Use the address with this label when a contact has several
+    #[arg(long)]
+    pub label: Option<String>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum NoteCommand {
+    /// Write a note about a contact
+    Add(AddNoteCommand),
+
+    /// Rewrite a note, keeping its earlier text in its history
+    Edit(EditNoteCommand),
+
+    /// List the notes about a contact
+    Ls(ListNotesCommand),
+
+    /// Remove notes by ID
+    Rm(RemoveNotesCommand),
+
+    /// Find notes matching a regular expression, ignoring case
+    Grep(GrepNotesCommand),
+
+    /// Show the earlier texts of a note
+    History(NoteHistoryCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct AddNoteCommand {
+    /// ID or name of contact the note is about
+    #[arg(value_name = "CONTACT")]
+    pub contact: String,
+
+    /// Treat CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
+
+    /// Text of the note in Markdown; opens $EDITOR when omitted
+    pub text: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct EditNoteCommand {
+    /// ID of the note, as listed by `note ls`
+    pub id: i64,
+
+    /// New text of the note; opens $EDITOR on the current text when omitted
+    pub text: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct ListNotesCommand {
+    /// ID or name of contact whose notes to list
+    #[arg(value_name = "CONTACT")]
+    pub contact: String,
+
+    /// Treat CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RemoveNotesCommand {
+    /// IDs of the notes, as listed by `note ls`
+    #[arg(required = true)]
+    pub ids: Vec<i64>,
+}
+
+#[derive(Args, Debug)]
+pub struct GrepNotesCommand {
+    /// Regular expression to look for
+    pub pattern: String,
+}
+
+#[derive(Args, Debug)]
+pub struct NoteHistoryCommand {
+    /// ID of the note, as listed by `note ls`
+    pub id: i64,
+}
+
+#[derive(Args, Debug)]
+pub struct LogCommand {
+    /// ID of contact the interaction was with
+    pub id: i64,
+
+    /// Kind of interaction, such as call, coffee, message or email
+    #[arg(short, long)]
+    pub kind: String,
+
+    /// Free-text note about the interaction
+    #[arg(short, long, default_value = "")]
+    pub note: String,
+
+    /// When it happened, as YYYY-MM-DD, "YYYY-MM-DD HH:MM" or RFC 3339; now when omitted
+    #[arg(long, value_name = "WHEN")]
+    pub at: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct HistoryCommand {
+    /// ID of contact whose interactions to list
+    pub id: i64,
+}
+
+#[derive(Args, Debug)]
+pub struct DueCommand {
+    /// Also list contacts due within this span, such as 7d or 2w
+    #[arg(long, value_name = "SPAN")]
+    pub within: Option<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct BirthdaysCommand {
+    /// How far ahead to look, such as 30d or 2m
+    #[arg(long, value_name = "SPAN", default_value = "30d")]
+    pub within: String,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportCalendarCommand {
+    /// name of the .ics file to write
+    pub filename: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the value of a setting
+    Get(GetConfigCommand),
+
+    /// Change a setting
+    Set(SetConfigCommand),
+
+    /// List every setting and its value
+    List,
+
+    /// Print where the config file is
+    Path,
+}
+
+#[derive(Args, Debug)]
+pub struct GetConfigCommand {
+    /// Name of the setting, such as region or `date_format`
+    pub key: String,
+}
+
+#[derive(Args, Debug)]
+pub struct SetConfigCommand {
+    /// Name of the setting, such as region or `date_format`
+    pub key: String,
+
+    /// New value of the setting; an empty string unsets it
+    pub value: String,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BooksCommand {
+    /// List the books of the config directory and their contacts
+    Ls,
+
+    /// Rename a book
+    Rename(RenameBookCommand),
+
+    /// Delete a book and everything in it
+    Rm(RemoveBookCommand),
+
+    /// Copy contacts into another book
+    Copy(TransferCommand),
+
+    /// Move contacts into another book
+    Move(TransferCommand),
+}
+
+#[derive(Args, Debug)]
+pub struct RenameBookCommand {
+    /// Current name of the book
+    pub from: String,
+
+    /// New name of the book
+    pub to: String,
+}
+
+#[derive(Args, Debug)]
+pub struct RemoveBookCommand {
+    /// Name of the book to delete
+    pub name: String,
+
+    /// Delete without asking
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct TransferCommand {
+    /// IDs or names of the contacts of the open book
+    #[arg(value_name = "CONTACT", required = true)]
+    pub contacts: Vec<String>,
+
+    /// Name of the book to put them in
+    #[arg(long, value_name = "BOOK")]
+    pub to: String,
+
+    /// Treat each CONTACT as an ID, never as a name
+    #[arg(long = "id")]
+    pub by_id: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DbCommand {
+    /// List the migrations of the book, applied and pending
+    Status,
+
+    /// Back the book up, then apply its pending migrations
+    Migrate,
+
+    /// Look for damage and rows whose contact is gone
+    Check,
+}
+
+#[derive(Args, Debug)]
+pub struct BackupCommand {
+    /// Days to keep the newest backup of
+    #[arg(long, value_name = "DAYS", default_value_t = nbd::db::maintenance::KEEP_DAILY)]
+    pub keep_daily: usize,
+
+    /// Weeks to keep the newest backup of
+    #[arg(long, value_name = "WEEKS", default_value_t = nbd::db::maintenance::KEEP_WEEKLY)]
+    pub keep_weekly: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct RestoreCommand {
+    /// Backup file, or its name in the backups folder of the book
+    #[arg(value_name = "BACKUP")]
+    pub backup: PathBuf,
 }
@@ -1,47 +1,148 @@
-use std::env;
+use std::process::ExitCode;
 
 mod actions;
 mod commander;
+mod output;
 
 use actions::Actions;
-use clap::Parser;
-use commander::{Cli, Commands};
+use clap::{Parser, ValueEnum};
+use commander::{BooksCommand, Cli, Commands, OutputFormat};
 use nbd::db::Repo;
-use sqlx::SqlitePool;
 
 #[tokio::main]
-async fn main() -> anyhow::Result<()> {
+async fn main() -> ExitCode {
     dotenvy::dotenv().ok();
 
     let cli = Cli::parse();
 
-    if matches!(cli.command, Commands::Init) {
-        let config_dir = nbd::utils::get_config_dir();
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("Error: {error:?}");
+            exit_code(&error)
+        }
+    }
+}
+
+/// Maps library errors to distinct exit codes so scripts can tell them
+/// apart; anything else exits with 1.
+fn exit_code(error: &anyhow::Error) -> ExitCode {
+    let code = match error.downcast_ref::<nbd::Error>() {
+        Some(
+            nbd::Error::NotFound(_)
+            | nbd::Error::AddressNotFound(_)
+            | nbd::Error::NoteNotFound(_)
+            | nbd::Error::BookNotFound(_)
+            | nbd::Error::NoMatch(_),
+        ) => 3,
+        Some(nbd::Error::InvalidEmail(_)) => 4,
+        Some(nbd::Error::InvalidPhone(_)) => 5,
+        Some(nbd::Error::InvalidDate(_)) => 6,
+        Some(nbd::Error::EmptyUpdate | nbd::Error::EmptyNote) => 7,
+        Some(nbd::Error::MissingId) => 8,
+        Some(nbd::Error::UnknownField(_)) => 9,
+        Some(nbd::Error::CsvRow { .. } | nbd::Error::InvalidRows(_)) => 10,
+        Some(nbd::Error::InvalidFile(_)) => 11,
+        Some(nbd::Error::Database(_)) => 12,
+        Some(nbd::Error::Io(_)) => 13,
+        Some(nbd::Error::InvalidCadence(_)) => 14,
+        Some(nbd::Error::AmbiguousName { .. }) => 15,
+        Some(nbd::Error::SelfMerge(_)) => 16,
+        Some(nbd::Error::InvalidLabel(_) | nbd::Error::InvalidAddressLabel(_)) => 17,
+        Some(nbd::Error::NotInitialized(_)) => 18,
+        Some(nbd::Error::UnknownSetting(_) | nbd::Error::InvalidSetting { .. }) => 19,
+        Some(nbd::Error::InvalidBook { .. }) => 20,
+        Some(_) | None => 1,
+    };
+
+    ExitCode::from(code)
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    let config_dir = nbd::utils::get_config_dir();
+    let config_path = nbd::utils::build_config_path(&config_dir);
+
+    if let Commands::Config(command) = &cli.command {
+        let format = cli.output.unwrap_or(OutputFormat::Table);
+        return actions::manage_config(command, &config_path, format);
+    }
 
-        let db_path = nbd::utils::build_database_path(&config_dir);
+    let config = nbd::utils::Config::load(&config_path)?;
+    let output = cli
+        .output
+        .or_else(|| OutputFormat::from_str(config.output()?, true).ok())
+        .unwrap_or(OutputFormat::Table);
 
+    if let Commands::Books(
+        command @ (BooksCommand::Ls | BooksCommand::Rename(_) | BooksCommand::Rm(_)),
+    ) = &cli.command
+    {
+        return actions::manage_books(command, &config_dir, &config_path, output).await;
+    }
+
+    let db_path =
+        nbd::utils::resolve_database_path(cli.db.as_deref(), cli.book.as_deref(), &config)?;
+
+    if matches!(cli.command, Commands::Init) {
         if nbd::utils::is_already_initialized(&db_path) {
             print!("A contact book has already been initialized");
             return Ok(());
         }
 
-        nbd::db::setup::initialize(&config_dir).await?;
+        nbd::db::setup::initialize_at(&db_path).await?;
 
         return Ok(());
     }
 
-    let pool = SqlitePool::connect(&env::var("DATABASE_URL")?).await?;
-    let data_repo = Repo::new(pool);
-    let actions = Actions::new(data_repo);
+    match &cli.command {
+        Commands::Db(command) => {
+            return actions::manage_database(command, &db_path, output, config.date_format()).await
+        }
+        Commands::Backup(command) => return actions::backup_book(command, &db_path).await,
+        Commands::Restore(command) => return actions::restore_book(command, &db_path).await,
+        _ => {}
+    }
+
+    let book = nbd::utils::resolve_book_name(cli.db.as_deref(), cli.book.as_deref(), &config);
+    let pool = nbd::db::setup::open(&db_path).await?;
+    let region = nbd::utils::default_region(config.region());
+    let data_repo = Repo::new(pool).with_region(&region);
+    let actions = Actions::new(data_repo, config);
 
     match &cli.command {
-        Commands::Init => {} // handled above (branch early)
         Commands::Create(value) => actions.create_contact(value).await?,
         Commands::Edit(value) => actions.edit_contact(value).await?,
-        Commands::Show => actions.show_all_contacts().await?,
-        Commands::Get(value) => actions.get_contact(value).await?,
+        Commands::Show(value) => actions.show_all_contacts(value, output).await?,
+        Commands::Get(value) => actions.get_contact(value, output).await?,
+        Commands::Search(value) => actions.search_contacts(value, output).await?,
         Commands::Delete(value) => actions.delete_contact(value).await?,
+        Commands::Dedupe => actions.show_duplicates(output).await?,
+        Commands::Merge(value) => actions.merge_contacts(value).await?,
         Commands::Import(value) => actions.import_contacts(value).await?,
+        Commands::Export(value) => actions.export_contacts(value).await?,
+        Commands::Tag(value) => actions.manage_tags(value, output).await?,
+        Commands::Address(value) => actions.manage_addresses(value, output).await?,
+        Commands::Labels(value) => actions.print_labels(value, output).await?,
+        Commands::Note(value) => actions.manage_notes(value, output).await?,
+        Commands::Log(value) => actions.log_interaction(value).await?,
+        Commands::History(value) => actions.show_history(value, output).await?,
+        Commands::Due(value) => actions.show_due_contacts(value, output).await?,
+        Commands::Birthdays(value) => actions.show_birthdays(value, output).await?,
+        Commands::ExportCalendar(value) => actions.export_calendar(value, &book).await?,
+        Commands::Books(BooksCommand::Copy(value)) => {
+            let target = actions::open_target_book(&value.to, &db_path, &region).await?;
+            actions.copy_contacts(value, &target, false).await?;
+        }
+        Commands::Books(BooksCommand::Move(value)) => {
+            let target = actions::open_target_book(&value.to, &db_path, &region).await?;
+            actions.copy_contacts(value, &target, true).await?;
+        }
+        Commands::Init
+        | Commands::Config(_)
+        | Commands::Books(_)
+        | Commands::Db(_)
+        | Commands::Backup(_)
+        | Commands::Restore(_) => {} // handled above (branch early)
     }
 
     Ok(())
@@ -0,0 +1,611 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, Utc};
+
+use nbd::{
+    models::{
+        Contact, ContactEmail, ContactField, ContactPhone, IndexedAddress, IndexedContact,
+        Interaction, Note, NoteRevision, PostalAddress, SearchHit,
+    },
+    utils,
+};
+use serde::Serialize;
+use tabled::{
+    settings::{location::ByColumnName, Remove},
+    Table, Tabled,
+};
+
+use crate::commander::OutputFormat;
+
+/// Prints contacts, with dates in tables written in `date_format`.
+pub fn print_contacts(
+    contacts: &[IndexedContact],
+    format: OutputFormat,
+    date_format: &str,
+) -> anyhow::Result<()> {
+    print_contact_columns(contacts, &utils::SHOW_COLUMNS, format, date_format)
+}
+
+/// Like [`print_contacts`], leaving table columns out unless `columns`
+/// names them. The other formats always hold every field.
+pub fn print_contact_columns(
+    contacts: &[IndexedContact],
+    columns: &[ContactField],
+    format: OutputFormat,
+    date_format: &str,
+) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    match format {
+        OutputFormat::Table => {
+            let rows = contacts
+                .iter()
+                .map(|contact| ContactRow::new(contact, date_format));
+            let mut table = Table::new(rows);
+            for column in utils::SHOW_COLUMNS {
+                if !columns.contains(&column) {
+                    table.with(Remove::column(ByColumnName::new(column.as_str())));
+                }
+            }
+            writeln!(stdout, "{table}")?;
+        }
+        OutputFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(contacts)?)?,
+        OutputFormat::Jsonl => {
+            for contact in contacts {
+                writeln!(stdout, "{}", serde_json::to_string(contact)?)?;
+            }
+        }
+        OutputFormat::Csv => utils::write_contacts_csv(contacts, &[], stdout)?,
+    }
+
+    Ok(())
+}
+
+/// A row of the contacts table, holding the columns of [`utils::SHOW_COLUMNS`]
+#[derive(Tabled)]
+struct ContactRow {
+    id: i64,
+    first_name: String,
+    last_name: String,
+    display_name: String,
+    email: String,
+    phone_number: String,
+    birthday: String,
+}
+
+impl ContactRow {
+    fn new(indexed: &IndexedContact, date_format: &str) -> Self {
+        let contact = &indexed.contact;
+
+        Self {
+            id: indexed.id,
+            first_name: contact.first_name.clone(),
+            last_name: contact.last_name.clone(),
+            display_name: contact.display_name.clone(),
+            email: contact.email.clone(),
+            phone_number: contact.phone_number.clone(),
+            birthday: contact
+                .birthday
+                .map(|birthday| utils::format_birthday(birthday, date_format))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Prints a contact with all of its email addresses, phone numbers and
+/// postal addresses: as tables below the contact, or as arrays in JSON.
+pub fn print_contact_details(
+    details: &ContactDetails,
+    format: OutputFormat,
+    date_format: &str,
+) -> anyhow::Result<()> {
+    let contact = std::slice::from_ref(&details.contact);
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(details)?),
+        OutputFormat::Jsonl => println!("{}", serde_json::to_string(details)?),
+        OutputFormat::Csv => print_contacts(contact, format, date_format)?,
+        OutputFormat::Table => {
+            print_contacts(contact, format, date_format)?;
+            if !details.emails.is_empty() {
+                println!("\nEmails\n{}", Table::new(&details.emails));
+            }
+            if !details.phones.is_empty() {
+                println!("\nPhones\n{}", Table::new(&details.phones));
+            }
+            if !details.addresses.is_empty() {
+                println!("\nAddresses\n{}", Table::new(&details.addresses));
+            }
+            if !details.notes.is_empty() {
+                println!("\nNotes");
+                print_notes(&details.notes, format, date_format)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A contact with everything stored alongside it
+#[derive(Serialize)]
+pub struct ContactDetails {
+    #[serde(flatten)]
+    pub contact: IndexedContact,
+    pub emails: Vec<ContactEmail>,
+    pub phones: Vec<ContactPhone>,
+    pub addresses: Vec<IndexedAddress>,
+    pub notes: Vec<Note>,
+}
+
+/// Prints notes as their Markdown text under a heading line with their ID
+/// and date in `date_format`, or as records in the other formats.
+pub fn print_notes(notes: &[Note], format: OutputFormat, date_format: &str) -> anyhow::Result<()> {
+    if format != OutputFormat::Table {
+        return print_records(notes, format);
+    }
+
+    let blocks: Vec<String> = notes
+        .iter()
+        .map(|note| {
+            let edited = if note.is_edited() {
+                format!(
+                    " (edited {})",
+                    utils::format_timestamp(&note.updated_at, date_format)
+                )
+            } else {
+                String::new()
+            };
+            format!(
+                "#{} {}{edited}\n{}",
+                note.id,
+                utils::format_timestamp(&note.created_at, date_format),
+                note.body
+            )
+        })
+        .collect();
+    if !blocks.is_empty() {
+        println!("{}", blocks.join("\n\n"));
+    }
+
+    Ok(())
+}
+
+/// Prints the earlier texts of a note, with dates in tables written in
+/// `date_format`.
+pub fn print_revisions(
+    revisions: &[NoteRevision],
+    format: OutputFormat,
+    date_format: &str,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Table {
+        return print_records(revisions, format);
+    }
+
+    let rows: Vec<RevisionRow> = revisions
+        .iter()
+        .map(|revision| RevisionRow {
+            written: utils::format_timestamp(&revision.written_at, date_format),
+            body: revision.body.clone(),
+        })
+        .collect();
+
+    print_records(&rows, format)
+}
+
+/// A row of `note history`: when a text was written, and the text
+#[derive(Tabled, Serialize)]
+struct RevisionRow {
+    written: String,
+    body: String,
+}
+
+/// Prints interactions, with dates in tables written in `date_format`.
+pub fn print_interactions(
+    interactions: &[Interaction],
+    format: OutputFormat,
+    date_format: &str,
+) -> anyhow::Result<()> {
+    if format != OutputFormat::Table {
+        return print_records(interactions, format);
+    }
+
+    let rows: Vec<InteractionRow> = interactions
+        .iter()
+        .map(|interaction| InteractionRow {
+            id: interaction.id,
+            when: utils::format_timestamp(&interaction.occurred_at, date_format),
+            kind: interaction.kind.clone(),
+            note: interaction.note.clone(),
+        })
+        .collect();
+
+    print_records(&rows, format)
+}
+
+/// A row of `history`: what happened with a contact, and when
+#[derive(Tabled, Serialize)]
+struct InteractionRow {
+    id: i64,
+    when: String,
+    kind: String,
+    note: String,
+}
+
+/// A row of `config list`: a setting and its value, empty when unset
+#[derive(Tabled, Serialize)]
+pub struct SettingRow {
+    pub setting: &'static str,
+    pub value: String,
+}
+
+/// A row of `books ls`; `default` marks the book opened without `--book`
+#[derive(Tabled, Serialize)]
+pub struct BookRow {
+    pub name: String,
+    #[tabled(display("display_default"))]
+    pub default: bool,
+    pub contacts: usize,
+    pub path: String,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_default(default: &bool) -> String {
+    if *default { "*" } else { "" }.to_owned()
+}
+
+/// A row of `db status`: a migration and when it was applied
+#[derive(Tabled, Serialize)]
+pub struct MigrationRow {
+    pub version: i64,
+    pub description: String,
+    #[tabled(display("display_applied", &self.date_format))]
+    pub applied_at: Option<DateTime<Utc>>,
+    #[tabled(skip)]
+    #[serde(skip)]
+    date_format: String,
+}
+
+impl MigrationRow {
+    /// Writes when the migration was applied in `date_format`.
+    pub fn new(status: &nbd::db::maintenance::MigrationStatus, date_format: &str) -> Self {
+        Self {
+            version: status.version,
+            description: status.description.clone(),
+            applied_at: status.installed_on,
+            date_format: date_format.to_owned(),
+        }
+    }
+}
+
+#[allow(clippy::ref_option)] // signature required by tabled
+fn display_applied(applied_at: &Option<DateTime<Utc>>, date_format: &str) -> String {
+    applied_at.as_ref().map_or_else(
+        || "pending".to_owned(),
+        |applied_at| utils::format_timestamp(applied_at, date_format),
+    )
+}
+
+/// A line of a note that `note grep` matched
+#[derive(Tabled, Serialize)]
+pub struct NoteMatch {
+    pub note: i64,
+    pub contact: String,
+    pub line: String,
+}
+
+/// Prints mailing labels: as blocks of text separated by blank lines, or as
+/// one record per label for a mail merge.
+pub fn print_mailing_labels(labels: &[MailingLabel], format: OutputFormat) -> anyhow::Result<()> {
+    if format != OutputFormat::Table {
+        return print_records(labels, format);
+    }
+
+    let blocks: Vec<String> = labels
+        .iter()
+        .map(|label| {
+            std::iter::once(label.name.clone())
+                .chain(label.address.lines())
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect();
+    if !blocks.is_empty() {
+        println!("{}", blocks.join("\n\n"));
+    }
+
+    Ok(())
+}
+
+/// Who a letter goes to and where
+#[derive(Tabled, Serialize)]
+pub struct MailingLabel {
+    name: String,
+    #[tabled(skip)]
+    #[serde(skip)]
+    address: PostalAddress,
+    street: String,
+    locality: String,
+    region: String,
+    postal_code: String,
+    country: String,
+}
+
+impl MailingLabel {
+    /// Addresses the label by full name, or by display name when the contact
+    /// has no first or last name.
+    pub fn new(contact: &Contact, address: &PostalAddress) -> Self {
+        let full_name = format!("{} {}", contact.first_name.trim(), contact.last_name.trim());
+        let name = if full_name.trim().is_empty() {
+            contact.display_name.trim().to_owned()
+        } else {
+            full_name.trim().to_owned()
+        };
+
+        Self {
+            name,
+            address: address.clone(),
+            street: address.street.clone(),
+            locality: address.locality.clone(),
+            region: address.region.clone(),
+            postal_code: address.postal_code.clone(),
+            country: address.country.clone(),
+        }
+    }
+}
+
+/// Prints search results best match first. Tables and CSV show a summary
+/// row per hit; JSON keeps the whole contact alongside its snippet and rank.
+pub fn print_search_hits(hits: &[SearchHit], format: OutputFormat) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Table | OutputFormat::Csv => {
+            let rows: Vec<SearchResult> = hits.iter().map(SearchResult::from).collect();
+            print_records(&rows, format)
+        }
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(hits)?);
+            Ok(())
+        }
+        OutputFormat::Jsonl => {
+            for hit in hits {
+                println!("{}", serde_json::to_string(hit)?);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Prints rows other than contacts, such as tags or interactions.
+pub fn print_records<T: Tabled + Serialize>(
+    records: &[T],
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let mut stdout = io::stdout().lock();
+
+    match format {
+        OutputFormat::Table => writeln!(stdout, "{}", Table::new(records))?,
+        OutputFormat::Json => writeln!(stdout, "{}", serde_json::to_string_pretty(records)?)?,
+        OutputFormat::Jsonl => {
+            for record in records {
+                writeln!(stdout, "{}", serde_json::to_string(record)?)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(stdout);
+            for record in records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the tags of a single contact, one per line unless JSON is asked for.
+pub fn print_tag_names(tags: &[String], format: OutputFormat) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(tags)?),
+        OutputFormat::Jsonl => println!("{}", serde_json::to_string(tags)?),
+        OutputFormat::Table | OutputFormat::Csv => {
+            for tag in tags {
+                println!("{tag}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A row of `search`: who matched, and the text that matched.
+#[derive(Tabled, Serialize)]
+pub struct SearchResult {
+    id: i64,
+    #[tabled(rename = "name")]
+    display_name: String,
+    email: String,
+    #[tabled(rename = "phone")]
+    phone_number: String,
+    #[tabled(rename = "match")]
+    snippet: String,
+}
+
+impl From<&SearchHit> for SearchResult {
+    fn from(hit: &SearchHit) -> Self {
+        let contact = &hit.contact.contact;
+
+        Self {
+            id: hit.contact.id,
+            display_name: contact.display_name.clone(),
+            email: contact.email.clone(),
+            phone_number: contact.phone_number.clone(),
+            snippet: hit.snippet.clone(),
+        }
+    }
+}
+
+/// A row of `dedupe`: two contacts that look like the same person, and why.
+#[derive(Tabled, Serialize)]
+pub struct DuplicateRow {
+    id: i64,
+    #[tabled(rename = "name")]
+    display_name: String,
+    #[tabled(rename = "other id")]
+    other_id: i64,
+    #[tabled(rename = "other name")]
+    other_display_name: String,
+    score: usize,
+    /// Why the two look alike, joined so CSV can hold it in one column
+    #[tabled(rename = "why")]
+    reasons: String,
+}
+
+impl From<&utils::DuplicatePair<'_>> for DuplicateRow {
+    fn from(pair: &utils::DuplicatePair<'_>) -> Self {
+        Self {
+            id: pair.first.id,
+            display_name: pair.first.contact.display_name.clone(),
+            other_id: pair.second.id,
+            other_display_name: pair.second.contact.display_name.clone(),
+            score: pair.score,
+            reasons: pair
+                .reasons
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+        }
+    }
+}
+
+/// A row of `due`: who to catch up with, and how late that catch-up is.
+#[derive(Tabled, Serialize)]
+pub struct DueContact {
+    id: i64,
+    #[tabled(rename = "name")]
+    display_name: String,
+    #[tabled(display("display_frequency"))]
+    frequency: Option<nbd::models::Cadence>,
+    #[tabled(
+        rename = "last seen",
+        display("display_last_seen", &self.date_format)
+    )]
+    last_seen_at: Option<DateTime<Utc>>,
+    #[tabled(rename = "due", display("display_date", &self.date_format))]
+    next_reminder_at: DateTime<Utc>,
+    #[tabled(rename = "overdue", display("display_days"))]
+    days_overdue: i64,
+    #[tabled(skip)]
+    #[serde(skip)]
+    date_format: String,
+}
+
+impl DueContact {
+    /// Returns `None` for contacts without a next reminder. Dates are
+    /// written in `date_format`.
+    pub fn new(indexed: &IndexedContact, now: DateTime<Utc>, date_format: &str) -> Option<Self> {
+        let contact = &indexed.contact;
+        let next_reminder_at = contact.next_reminder_at?;
+
+        Some(Self {
+            id: indexed.id,
+            display_name: contact.display_name.clone(),
+            frequency: contact.frequency,
+            last_seen_at: contact.last_seen_at,
+            next_reminder_at,
+            days_overdue: now.signed_duration_since(next_reminder_at).num_days(),
+            date_format: date_format.to_owned(),
+        })
+    }
+}
+
+#[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_frequency(frequency: &Option<nbd::models::Cadence>) -> String {
+    frequency
+        .map(|frequency| frequency.to_string())
+        .unwrap_or_default()
+}
+
+fn display_date(timestamp: &DateTime<Utc>, date_format: &str) -> String {
+    utils::format_date(timestamp.with_timezone(&Local).date_naive(), date_format)
+}
+
+#[allow(clippy::ref_option)] // signature required by tabled
+fn display_last_seen(timestamp: &Option<DateTime<Utc>>, date_format: &str) -> String {
+    timestamp.as_ref().map_or_else(
+        || "never".to_string(),
+        |timestamp| display_date(timestamp, date_format),
+    )
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_days(days: &i64) -> String {
+    let plural = |days: u64| if days == 1 { "day" } else { "days" };
+
+    match *days {
+        0 => "today".to_string(),
+        days if days > 0 => format!("{days} {}", plural(days.unsigned_abs())),
+        days => format!("in {} {}", days.unsigned_abs(), plural(days.unsigned_abs())),
+    }
+}
+
+/// A row of `birthdays`: whose birthday is next, and the age they turn.
+#[derive(Tabled, Serialize)]
+pub struct UpcomingBirthday {
+    pub id: i64,
+    #[tabled(rename = "name")]
+    display_name: String,
+    #[tabled(display("display_birthday", &self.date_format))]
+    birthday: nbd::models::Birthday,
+    #[tabled(display("display_day", &self.date_format))]
+    pub date: NaiveDate,
+    #[tabled(rename = "in", display("display_days_until"))]
+    days_until: i64,
+    #[tabled(display("display_turning"))]
+    turning: Option<i32>,
+    #[tabled(skip)]
+    #[serde(skip)]
+    date_format: String,
+}
+
+impl UpcomingBirthday {
+    /// Returns `None` for contacts without a birthday. Dates are written in
+    /// `date_format`.
+    pub fn new(indexed: &IndexedContact, today: NaiveDate, date_format: &str) -> Option<Self> {
+        let birthday = indexed.contact.birthday?;
+        let date = birthday.next_from(today)?;
+
+        Some(Self {
+            id: indexed.id,
+            display_name: indexed.contact.display_name.clone(),
+            birthday,
+            date,
+            days_until: date.signed_duration_since(today).num_days(),
+            turning: birthday.age_in(date.year()),
+            date_format: date_format.to_owned(),
+        })
+    }
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_birthday(birthday: &nbd::models::Birthday, date_format: &str) -> String {
+    utils::format_birthday(*birthday, date_format)
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_day(date: &NaiveDate, date_format: &str) -> String {
+    utils::format_date(*date, date_format)
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_days_until(days: &i64) -> String {
+    match *days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        days => format!("{days} days"),
+    }
+}
+
+#[allow(clippy::ref_option, clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_turning(turning: &Option<i32>) -> String {
+    turning.map(|age| age.to_string()).unwrap_or_default()
+}
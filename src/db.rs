@@ -1,6 +1,7 @@
 mod connection;
 mod contact_repo;
 
+pub mod maintenance;
 pub mod setup;
 
 pub use connection::Repo;
@@ -1,24 +1,37 @@
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
+use crate::utils;
+
 pub struct Repo<D> {
     pub database: Arc<D>,
+    /// Region of phone numbers saved without a country code
+    pub region: String,
 }
 
 impl<D> Repo<D> {
+    /// Opens a repo reading phone numbers in [`utils::DEFAULT_REGION`].
     #[must_use]
     pub fn new(database: D) -> Self {
         Self {
             database: Arc::new(database),
+            region: utils::DEFAULT_REGION.to_owned(),
         }
     }
+
+    /// Reads phone numbers without a country code as belonging to `region`.
+    #[must_use]
+    pub fn with_region(mut self, region: &str) -> Self {
+        region.clone_into(&mut self.region);
+        self
+    }
 }
 
 impl Repo<SqlitePool> {
     /// # Errors
     ///
     /// Will error if the database is not connected.
-    pub async fn check_connection(&self) -> anyhow::Result<()> {
+    pub async fn check_connection(&self) -> crate::Result<()> {
         // Execute a simple query to check if the connection works
         sqlx::query("SELECT 1").execute(&*self.database).await?;
         Ok(())
@@ -44,7 +57,7 @@ mod tests {
             .first_name("Lewis")
             .last_name("Carroll")
             .email("lewis@wonderland.com")
-            .phone_number("777-777-7777")
+            .phone_number("212-555-0177")
             .birthday("1832-1-27")
             .build()
             .expect("Expect Louis Carroll");
@@ -82,7 +95,7 @@ mod tests {
             .first_name("Lewis")
             .last_name("Carroll")
             .email("lewis@wonderland.com")
-            .phone_number("777-777-7777")
+            .phone_number("212-555-0177")
             .birthday("1832-1-27")
             .build()
             .expect("Louis Carroll");
@@ -1,53 +1,127 @@
-use crate::{
-    models,
-    utils::{self, default_date},
-};
+use crate::{models, utils, Error};
 use async_trait::async_trait;
-use sqlx::SqlitePool;
+use chrono::{DateTime, Utc};
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
 
 use super::connection::Repo;
 
 #[cfg_attr(test, mockall::automock)]
 #[async_trait]
 pub trait ContactRepo {
-    async fn save_contact(&self, contact: models::Contact) -> anyhow::Result<i64>;
-    async fn save_optional_contact(&self, contact: models::OptionalContact) -> anyhow::Result<i64>;
-    async fn import_contacts_by_csv(&self, filename: &str) -> anyhow::Result<i64>;
-    async fn get_all_contacts(&self) -> anyhow::Result<Vec<models::IndexedContact>>;
-    async fn update_contact(&self, update: models::ContactBuilder) -> anyhow::Result<()>;
-    async fn get_contact_by_id(&self, id: i64) -> anyhow::Result<models::IndexedContact>;
-    async fn delete_contact_by_id(&self, id: i64) -> anyhow::Result<i64>;
+    async fn save_contact(&self, contact: models::Contact) -> crate::Result<i64>;
+    async fn save_optional_contact(&self, contact: models::OptionalContact) -> crate::Result<i64>;
+    async fn import_contacts_by_csv(&self, filename: &str) -> crate::Result<i64>;
+    async fn import_contacts(
+        &self,
+        filename: &str,
+        options: &utils::ImportOptions,
+    ) -> crate::Result<utils::ImportReport>;
+    async fn get_all_contacts(&self) -> crate::Result<Vec<models::IndexedContact>>;
+    async fn get_contacts_by_tags(
+        &self,
+        tags: &[String],
+        excluded_tags: &[String],
+    ) -> crate::Result<Vec<models::IndexedContact>>;
+    async fn get_due_contacts(
+        &self,
+        due_by: DateTime<Utc>,
+    ) -> crate::Result<Vec<models::IndexedContact>>;
+    async fn update_contact(&self, update: models::ContactBuilder) -> crate::Result<()>;
+    async fn get_contact_by_id(&self, id: i64) -> crate::Result<models::IndexedContact>;
+    async fn get_contact_card(&self, id: i64) -> crate::Result<models::ContactCard>;
+    async fn delete_contact_by_id(&self, id: i64) -> crate::Result<i64>;
+    async fn add_tag(&self, contact_id: i64, tag: &str) -> crate::Result<()>;
+    async fn remove_tag(&self, contact_id: i64, tag: &str) -> crate::Result<bool>;
+    async fn get_tags(&self, contact_id: i64) -> crate::Result<Vec<String>>;
+    async fn get_all_tags(&self) -> crate::Result<Vec<models::Tag>>;
+    async fn get_emails(&self, contact_id: i64) -> crate::Result<Vec<models::ContactEmail>>;
+    async fn get_phones(&self, contact_id: i64) -> crate::Result<Vec<models::ContactPhone>>;
+    async fn add_email(
+        &self,
+        contact_id: i64,
+        email: &models::Labeled,
+        preferred: bool,
+    ) -> crate::Result<()>;
+    async fn add_phone(
+        &self,
+        contact_id: i64,
+        phone: &models::Labeled,
+        preferred: bool,
+    ) -> crate::Result<()>;
+    async fn remove_email(&self, contact_id: i64, email: &str) -> crate::Result<bool>;
+    async fn remove_phone(&self, contact_id: i64, phone_number: &str) -> crate::Result<bool>;
+    async fn get_addresses(&self, contact_id: i64) -> crate::Result<Vec<models::IndexedAddress>>;
+    async fn get_all_addresses(&self) -> crate::Result<Vec<models::IndexedAddress>>;
+    async fn get_address(&self, id: i64) -> crate::Result<models::IndexedAddress>;
+    async fn add_address(
+        &self,
+        contact_id: i64,
+        address: &models::PostalAddress,
+    ) -> crate::Result<i64>;
+    async fn update_address(&self, id: i64, address: &models::PostalAddress) -> crate::Result<()>;
+    async fn remove_address(&self, id: i64) -> crate::Result<bool>;
+    async fn add_note(&self, contact_id: i64, body: &str) -> crate::Result<i64>;
+    async fn get_note(&self, id: i64) -> crate::Result<models::Note>;
+    async fn get_notes(&self, contact_id: i64) -> crate::Result<Vec<models::Note>>;
+    async fn get_all_notes(&self) -> crate::Result<Vec<models::Note>>;
+    async fn update_note(&self, id: i64, body: &str) -> crate::Result<()>;
+    async fn remove_note(&self, id: i64) -> crate::Result<bool>;
+    async fn get_note_revisions(&self, id: i64) -> crate::Result<Vec<models::NoteRevision>>;
+    async fn log_interaction(
+        &self,
+        contact_id: i64,
+        kind: &str,
+        note: &str,
+        occurred_at: DateTime<Utc>,
+    ) -> crate::Result<i64>;
+    async fn get_interactions(&self, contact_id: i64) -> crate::Result<Vec<models::Interaction>>;
+    async fn search(&self, query: &str) -> crate::Result<Vec<models::SearchHit>>;
+    async fn merge_contacts(
+        &self,
+        keep_id: i64,
+        drop_id: i64,
+        take: &[models::ContactField],
+    ) -> crate::Result<models::IndexedContact>;
+    async fn get_merged_ids(&self, contact_id: i64) -> crate::Result<Vec<i64>>;
 }
 
 #[async_trait]
 impl ContactRepo for Repo<SqlitePool> {
-    async fn save_contact(&self, contact: models::Contact) -> anyhow::Result<i64> {
+    async fn save_contact(&self, contact: models::Contact) -> crate::Result<i64> {
         let query = "INSERT INTO contacts
-        (first_name, last_name, display_name, email, phone_number, birthday, starred, is_archived, created_at, updated_at, last_seen_at, frequency, last_reminder_at)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+        (first_name, last_name, display_name, email, phone_number, phone_e164, birthday, starred, is_archived, created_at, updated_at, last_seen_at, next_reminder_at, frequency, last_reminder_at, external_id)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+        let mut transaction = self.database.begin().await?;
         let result = sqlx::query(query)
             .bind(&contact.first_name)
             .bind(&contact.last_name)
             .bind(&contact.display_name)
             .bind(&contact.email)
             .bind(&contact.phone_number)
+            .bind(&contact.phone_e164)
             .bind(contact.birthday)
             .bind(contact.starred)
             .bind(contact.is_archived)
             .bind(contact.created_at)
             .bind(contact.updated_at)
             .bind(contact.last_seen_at)
-            .bind(&contact.frequency)
+            .bind(contact.due_at())
+            .bind(contact.frequency)
             .bind(contact.last_reminder_at)
-            .execute(&*self.database)
+            .bind(&contact.external_id)
+            .execute(&mut *transaction)
             .await?;
 
         let contact_id = result.last_insert_rowid();
 
+        sync_contact_methods(&mut transaction, contact_id, &self.region).await?;
+
+        transaction.commit().await?;
+
         Ok(contact_id)
     }
 
-    async fn get_all_contacts(&self) -> anyhow::Result<Vec<models::IndexedContact>> {
+    async fn get_all_contacts(&self) -> crate::Result<Vec<models::IndexedContact>> {
         let get_contacts_query = "SELECT *
              FROM contacts
              ORDER BY id";
@@ -60,10 +134,63 @@ impl ContactRepo for Repo<SqlitePool> {
         Ok(contacts_with_id)
     }
 
-    async fn update_contact(&self, contact: models::ContactBuilder) -> anyhow::Result<()> {
-        use chrono::Utc;
+    async fn get_contacts_by_tags(
+        &self,
+        tags: &[String],
+        excluded_tags: &[String],
+    ) -> crate::Result<Vec<models::IndexedContact>> {
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM contacts c WHERE 1 = 1");
+
+        for tag in tags {
+            query.push(
+                " AND EXISTS (SELECT 1 FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id
+                  WHERE ct.contact_id = c.id AND t.name = ",
+            );
+            query.push_bind(tag.trim());
+            query.push(")");
+        }
+
+        for tag in excluded_tags {
+            query.push(
+                " AND NOT EXISTS (SELECT 1 FROM contact_tags ct JOIN tags t ON t.id = ct.tag_id
+                  WHERE ct.contact_id = c.id AND t.name = ",
+            );
+            query.push_bind(tag.trim());
+            query.push(")");
+        }
+
+        query.push(" ORDER BY c.id");
+
+        let contacts = query
+            .build_query_as::<models::IndexedContact>()
+            .fetch_all(&*self.database)
+            .await?;
+
+        Ok(contacts)
+    }
+
+    /// Unarchived contacts whose next reminder falls on or before `due_by`,
+    /// the most overdue first.
+    async fn get_due_contacts(
+        &self,
+        due_by: DateTime<Utc>,
+    ) -> crate::Result<Vec<models::IndexedContact>> {
+        let contacts = sqlx::query_as::<_, models::IndexedContact>(
+            "SELECT * FROM contacts
+             WHERE next_reminder_at IS NOT NULL AND next_reminder_at <= $1 AND NOT is_archived
+             ORDER BY next_reminder_at, id",
+        )
+        .bind(due_by)
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(contacts)
+    }
+
+    async fn update_contact(&self, contact: models::ContactBuilder) -> crate::Result<()> {
         let now = Utc::now();
-        sqlx::query!(
+        let mut transaction = self.database.begin().await?;
+        let result = sqlx::query!(
             r#"
             UPDATE contacts
             SET
@@ -95,25 +222,52 @@ impl ContactRepo for Repo<SqlitePool> {
             contact.optional_contact.last_reminder_at,
             contact.id
         )
-        .execute(&*self.database)
+        .execute(&mut *transaction)
         .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound(contact.id));
+        }
+
+        for field in &contact.optional_contact.cleared {
+            clear_field(&mut transaction, contact.id, *field).await?;
+        }
+
+        if contact.optional_contact.phone_number.is_some() {
+            refresh_phone_e164(&mut transaction, contact.id, &self.region).await?;
+        }
+        refresh_next_reminder(&mut transaction, contact.id).await?;
+        sync_contact_methods(&mut transaction, contact.id, &self.region).await?;
+
+        transaction.commit().await?;
+
         Ok(())
     }
 
-    async fn get_contact_by_id(&self, id: i64) -> anyhow::Result<models::IndexedContact> {
+    async fn get_contact_by_id(&self, id: i64) -> crate::Result<models::IndexedContact> {
         let query_get_by_id = "SELECT * FROM contacts WHERE id=$1";
 
-        let contact: models::IndexedContact =
+        let contact: Option<models::IndexedContact> =
             sqlx::query_as::<_, models::IndexedContact>(query_get_by_id)
                 .bind(id)
-                .fetch_one(&*self.database)
+                .fetch_optional(&*self.database)
                 .await?;
 
-        Ok(contact)
+        contact.ok_or(Error::NotFound(id))
+    }
+
+    async fn get_contact_card(&self, id: i64) -> crate::Result<models::ContactCard> {
+        Ok(models::ContactCard {
+            contact: self.get_contact_by_id(id).await?,
+            tags: self.get_tags(id).await?,
+            emails: self.get_emails(id).await?,
+            phones: self.get_phones(id).await?,
+            addresses: self.get_addresses(id).await?,
+            notes: self.get_notes(id).await?,
+        })
     }
 
-    async fn delete_contact_by_id(&self, id: i64) -> anyhow::Result<i64> {
+    async fn delete_contact_by_id(&self, id: i64) -> crate::Result<i64> {
         let query_delete_by_id = "DELETE FROM contacts WHERE id=$1";
 
         let result = sqlx::query(query_delete_by_id)
@@ -122,60 +276,25 @@ impl ContactRepo for Repo<SqlitePool> {
             .await?;
 
         if result.rows_affected() == 0 {
-            anyhow::bail!("That Contact ID does not exist")
+            return Err(Error::NotFound(id));
         }
 
         Ok(id)
     }
 
-    async fn save_optional_contact(&self, contact: models::OptionalContact) -> anyhow::Result<i64> {
-        use chrono::Utc;
-
-        let mut display_name = contact.display_name.clone();
-
-        if display_name.is_none() {
-            let firstname = contact.first_name.clone();
-            let lastname = contact.last_name.clone();
-            display_name = Some(format!(
-                "{} {}",
-                firstname.unwrap_or_default(),
-                lastname.unwrap_or_default()
-            ));
-        }
-
-        let query =
-            "INSERT INTO contacts (first_name, last_name, display_name, phone_number, email, birthday, starred, is_archived, created_at, updated_at, last_seen_at, frequency, last_reminder_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+    async fn save_optional_contact(&self, contact: models::OptionalContact) -> crate::Result<i64> {
+        let mut transaction = self.database.begin().await?;
 
-        let default_date = default_date();
-        let birthday = contact.birthday.unwrap_or(default_date);
+        let contact_id = insert_optional_contact(&mut transaction, &contact, &self.region).await?;
 
-        let now = Utc::now();
-
-        let result = sqlx::query(query)
-            .bind(&contact.first_name)
-            .bind(&contact.last_name)
-            .bind(display_name)
-            .bind(&contact.phone_number)
-            .bind(&contact.email)
-            .bind(birthday)
-            .bind(contact.starred.unwrap_or(false))
-            .bind(contact.is_archived.unwrap_or(false))
-            .bind(now)
-            .bind(now)
-            .bind(contact.last_seen_at)
-            .bind(&contact.frequency)
-            .bind(contact.last_reminder_at)
-            .execute(&*self.database)
-            .await?;
-
-        let contact_id = result.last_insert_rowid();
+        transaction.commit().await?;
 
         Ok(contact_id)
     }
 
     #[allow(clippy::arithmetic_side_effects)]
-    async fn import_contacts_by_csv(&self, filename: &str) -> anyhow::Result<i64> {
-        let contacts = utils::process_csv_to_contacts(filename)?;
+    async fn import_contacts_by_csv(&self, filename: &str) -> crate::Result<i64> {
+        let contacts = utils::process_csv_to_contacts(filename, &self.region)?;
 
         let mut number_of_contacts_added = 0;
         for contact in &contacts {
@@ -185,370 +304,2754 @@ impl ContactRepo for Repo<SqlitePool> {
 
         Ok(number_of_contacts_added)
     }
-}
 
-#[cfg(test)]
-#[allow(clippy::expect_used)]
-#[allow(clippy::panic_in_result_fn)]
-#[allow(clippy::unwrap_used)]
-mod tests {
-    use super::*;
-    use mockall::predicate::*;
-    use test_utils::setup_in_memory_db;
+    /// Imports every valid row of `filename` in one transaction, matching
+    /// rows to existing contacts with `utils::find_existing`.
+    ///
+    /// Invalid rows abort the import unless `options.skip_invalid` is set, in
+    /// which case they are left out and listed in the report. A dry run rolls
+    /// the transaction back once the report is ready.
+    async fn import_contacts(
+        &self,
+        filename: &str,
+        options: &utils::ImportOptions,
+    ) -> crate::Result<utils::ImportReport> {
+        let file = utils::read_import_rows(filename, options, &self.region)?;
+
+        let mut rows = Vec::new();
+        let mut rejected = Vec::new();
+        for row in file.rows {
+            match row {
+                Ok(row) => rows.push(row.contact),
+                Err(error) => rejected.push(error),
+            }
+        }
 
-    #[tokio::test]
-    async fn test_save_contact() {
-        let mut mock_contact_repo = MockContactRepo::new();
+        if !rejected.is_empty() && !options.skip_invalid {
+            return Err(Error::InvalidRows(rejected));
+        }
 
-        let test_contact = models::Contact::builder()
-            .first_name("John")
-            .last_name("Smith")
-            .email("johndoe@example.com")
-            .phone_number("123-456-7890")
-            .birthday("1970-1-1")
-            .build()
-            .expect("Test contact");
+        let mut report = utils::ImportReport {
+            rejected,
+            unmapped_columns: file.unmapped_columns,
+            ..utils::ImportReport::default()
+        };
 
-        mock_contact_repo
-            .expect_save_contact()
-            .times(1)
-            .with(eq(test_contact.clone()))
-            .returning(|_| Ok(1));
+        let mut transaction = self.database.begin().await?;
+        let mut contacts: Vec<models::IndexedContact> =
+            sqlx::query_as("SELECT * FROM contacts ORDER BY id")
+                .fetch_all(&mut *transaction)
+                .await?;
 
-        let result = mock_contact_repo
-            .save_contact(test_contact)
-            .await
-            .expect("Saved contact");
+        for row in rows {
+            let existing = utils::find_existing(&row, &contacts).map(|found| found.id);
+            // Two contacts cannot share an external ID, so even `Create`
+            // updates the contact that already has it
+            let update =
+                options.on_duplicate == utils::OnDuplicate::Update || row.external_id.is_some();
+
+            let contact_id = match existing {
+                Some(_) if options.on_duplicate == utils::OnDuplicate::Skip => {
+                    report.skipped = report.skipped.saturating_add(1);
+                    continue;
+                }
+                Some(id) if update => {
+                    update_from_row(&mut transaction, id, &row, &self.region).await?;
+                    report.updated = report.updated.saturating_add(1);
+                    id
+                }
+                _ => {
+                    let id = insert_optional_contact(&mut transaction, &row, &self.region).await?;
+                    report.created = report.created.saturating_add(1);
+                    id
+                }
+            };
+
+            let contact = models::IndexedContact {
+                id: contact_id,
+                contact: fetch_contact(&mut transaction, contact_id).await?,
+            };
+            match contacts.iter_mut().find(|indexed| indexed.id == contact_id) {
+                Some(indexed) => *indexed = contact,
+                None => contacts.push(contact),
+            }
+        }
 
-        assert_eq!(result, 1);
+        if options.dry_run {
+            transaction.rollback().await?;
+        } else {
+            transaction.commit().await?;
+        }
+
+        Ok(report)
     }
 
-    #[tokio::test]
-    async fn test_get_all_contacts() {
-        let mut mock_contact_repo = MockContactRepo::new();
+    async fn add_tag(&self, contact_id: i64, tag: &str) -> crate::Result<()> {
+        let tag = tag.trim();
 
-        let contacts = vec![models::IndexedContact {
-            id: 1,
-            contact: models::Contact::builder()
-                .first_name("John")
-                .last_name("Doe")
-                .email("johndoe@example.com")
-                .phone_number("1234567890")
-                .birthday("1970-01-01")
-                .build()
-                .expect("Test contact"),
-        }];
+        let contact_exists = sqlx::query("SELECT 1 FROM contacts WHERE id = $1")
+            .bind(contact_id)
+            .fetch_optional(&*self.database)
+            .await?;
 
-        mock_contact_repo
-            .expect_get_all_contacts()
-            .times(1)
-            .return_once(move || Ok(contacts));
+        if contact_exists.is_none() {
+            return Err(Error::NotFound(contact_id));
+        }
 
-        let result = mock_contact_repo.get_all_contacts().await;
+        let mut transaction = self.database.begin().await?;
 
-        assert!(result.is_ok());
-    }
+        attach_tag(&mut transaction, contact_id, tag).await?;
 
-    #[tokio::test]
-    async fn test_update_contact() {
-        let mut mock_contact_repo = MockContactRepo::new();
+        transaction.commit().await?;
 
-        mock_contact_repo
-            .expect_update_contact()
-            .times(1)
-            .return_once(|_| Ok(()));
+        Ok(())
+    }
 
-        let edits = models::ContactBuilder::new(
-            1,
-            None,
-            None,
-            Some("some@email.com".to_string()),
-            None,
-            None,
-            None,
+    async fn remove_tag(&self, contact_id: i64, tag: &str) -> crate::Result<bool> {
+        let mut transaction = self.database.begin().await?;
+
+        let result = sqlx::query(
+            "DELETE FROM contact_tags
+             WHERE contact_id = $1 AND tag_id = (SELECT id FROM tags WHERE name = $2)",
         )
-        .expect("Contact with only an email");
+        .bind(contact_id)
+        .bind(tag.trim())
+        .execute(&mut *transaction)
+        .await?;
 
-        let result = mock_contact_repo.update_contact(edits).await;
+        sqlx::query("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM contact_tags)")
+            .execute(&mut *transaction)
+            .await?;
 
-        assert!(result.is_ok());
-    }
+        transaction.commit().await?;
 
-    #[tokio::test]
-    async fn test_get_contact_by_id() {
-        let mut mock_contact_repo = MockContactRepo::new();
+        Ok(result.rows_affected() > 0)
+    }
 
-        let contact = models::IndexedContact {
-            id: 1,
-            contact: models::Contact::builder()
-                .first_name("John")
-                .last_name("Doe")
-                .email("johndoe@example.com")
-                .phone_number("1234567890")
-                .birthday("1970-01-01")
-                .build()
-                .expect("Default contact"),
-        };
+    async fn get_tags(&self, contact_id: i64) -> crate::Result<Vec<String>> {
+        let tags = sqlx::query_scalar(
+            "SELECT t.name FROM tags t
+             JOIN contact_tags ct ON ct.tag_id = t.id
+             WHERE ct.contact_id = $1
+             ORDER BY t.name",
+        )
+        .bind(contact_id)
+        .fetch_all(&*self.database)
+        .await?;
 
-        mock_contact_repo
-            .expect_get_contact_by_id()
-            .times(1)
-            .with(eq(contact.id))
-            .return_once(|_| Ok(contact));
+        Ok(tags)
+    }
 
-        let result = mock_contact_repo.get_contact_by_id(1).await;
+    /// A contact's email addresses, the preferred one first.
+    async fn get_emails(&self, contact_id: i64) -> crate::Result<Vec<models::ContactEmail>> {
+        let emails = sqlx::query_as::<_, models::ContactEmail>(
+            "SELECT contact_id, label, email, is_preferred FROM contact_emails
+             WHERE contact_id = $1
+             ORDER BY is_preferred DESC, id",
+        )
+        .bind(contact_id)
+        .fetch_all(&*self.database)
+        .await?;
 
-        assert!(result.is_ok());
+        Ok(emails)
+    }
 
-        let actual_contact = result.expect("Actual Contact");
+    /// A contact's phone numbers, the preferred one first.
+    async fn get_phones(&self, contact_id: i64) -> crate::Result<Vec<models::ContactPhone>> {
+        let phones = sqlx::query_as::<_, models::ContactPhone>(
+            "SELECT contact_id, label, phone_number, phone_e164, is_preferred FROM contact_phones
+             WHERE contact_id = $1
+             ORDER BY is_preferred DESC, id",
+        )
+        .bind(contact_id)
+        .fetch_all(&*self.database)
+        .await?;
 
-        assert_eq!(actual_contact.id, 1);
+        Ok(phones)
     }
 
-    #[tokio::test]
-    async fn should_save_option_contact_in_database() -> anyhow::Result<()> {
-        let pool = setup_in_memory_db().await;
+    /// Adds an email address, or relabels it when the contact already has
+    /// it. A preferred address also becomes the contact's `email`.
+    async fn add_email(
+        &self,
+        contact_id: i64,
+        email: &models::Labeled,
+        preferred: bool,
+    ) -> crate::Result<()> {
+        if utils::is_not_valid_email(&email.value) {
+            return Err(Error::InvalidEmail(email.value.clone()));
+        }
 
-        let data_repo = Repo::new(pool);
+        let mut transaction = self.database.begin().await?;
 
-        let test_contact = models::OptionalContact {
-            first_name: Some("Jason".to_string()),
-            ..models::OptionalContact::default()
-        };
+        fetch_contact(&mut transaction, contact_id).await?;
+        attach_email(&mut transaction, contact_id, email).await?;
 
-        let result = data_repo.save_optional_contact(test_contact).await?;
+        if preferred {
+            sqlx::query("UPDATE contacts SET email = $1, updated_at = $2 WHERE id = $3")
+                .bind(&email.value)
+                .bind(Utc::now())
+                .bind(contact_id)
+                .execute(&mut *transaction)
+                .await?;
+        }
 
-        assert_eq!(result, 1);
+        sync_contact_methods(&mut transaction, contact_id, &self.region).await?;
+
+        transaction.commit().await?;
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn should_be_able_to_retrieve_full_contact_when_saved_full_contact() -> anyhow::Result<()>
-    {
-        let pool = setup_in_memory_db().await;
-
-        let data_repo = Repo::new(pool);
-
-        let test_contact = models::OptionalContact {
-            first_name: Some("Ada".to_string()),
-            last_name: Some("Lovelace".to_string()),
-            display_name: Some("Addy".to_string()),
-            email: Some("ada@lovelace.rs".to_string()),
-            phone_number: Some("1233211233".to_string()),
-            birthday: Some(chrono::NaiveDate::default()),
-            starred: None,
-            is_archived: None,
-            last_seen_at: None,
-            frequency: None,
-            last_reminder_at: None,
-        };
-
-        let contact_id = data_repo
-            .save_optional_contact(test_contact.clone())
-            .await?;
+    /// Adds a phone number, or relabels it when the contact already has it.
+    /// A preferred number also becomes the contact's `phone_number`.
+    async fn add_phone(
+        &self,
+        contact_id: i64,
+        phone: &models::Labeled,
+        preferred: bool,
+    ) -> crate::Result<()> {
+        if utils::is_not_valid_phone_number(&phone.value, &self.region) {
+            return Err(Error::InvalidPhone(phone.value.clone()));
+        }
 
-        let saved_contact = data_repo.get_contact_by_id(contact_id).await?;
+        let mut transaction = self.database.begin().await?;
 
-        let saved_contact = saved_contact.contact;
+        fetch_contact(&mut transaction, contact_id).await?;
+        attach_phone(&mut transaction, contact_id, phone, &self.region).await?;
 
-        assert_eq!(
-            saved_contact.first_name,
-            test_contact.first_name.expect("Contact first name")
-        );
-        assert_eq!(
-            saved_contact.last_name,
-            test_contact.last_name.expect("Contact last name")
-        );
-        assert_eq!(
-            saved_contact.phone_number,
-            test_contact.phone_number.expect("Contact phone number")
-        );
+        if preferred {
+            sqlx::query("UPDATE contacts SET phone_number = $1, updated_at = $2 WHERE id = $3")
+                .bind(&phone.value)
+                .bind(Utc::now())
+                .bind(contact_id)
+                .execute(&mut *transaction)
+                .await?;
+            refresh_phone_e164(&mut transaction, contact_id, &self.region).await?;
+        }
 
-        assert_eq!(
-            saved_contact.email,
-            test_contact.email.expect("Contact emails")
-        );
+        sync_contact_methods(&mut transaction, contact_id, &self.region).await?;
 
-        assert_eq!(
-            saved_contact.display_name,
-            test_contact.display_name.expect("Display name")
-        );
+        transaction.commit().await?;
 
         Ok(())
     }
 
-    #[tokio::test]
-    async fn should_default_to_first_and_last_name_for_display_name() -> anyhow::Result<()> {
-        let pool = setup_in_memory_db().await;
-
-        let data_repo = Repo::new(pool);
+    /// Removes an email address. When it was the preferred one, the next
+    /// address the contact has takes its place.
+    async fn remove_email(&self, contact_id: i64, email: &str) -> crate::Result<bool> {
+        let mut transaction = self.database.begin().await?;
 
-        let test_contact = models::OptionalContact {
-            first_name: Some("Ada".to_string()),
-            last_name: Some("Lovelace".to_string()),
-            birthday: Some(chrono::NaiveDate::default()),
-            ..models::OptionalContact::default()
-        };
+        let result = sqlx::query("DELETE FROM contact_emails WHERE contact_id = $1 AND email = $2")
+            .bind(contact_id)
+            .bind(email.trim())
+            .execute(&mut *transaction)
+            .await?;
 
-        let contact_id = data_repo
-            .save_optional_contact(test_contact.clone())
+        sqlx::query("UPDATE contacts SET email = '' WHERE id = $1 AND email = $2 COLLATE NOCASE")
+            .bind(contact_id)
+            .bind(email.trim())
+            .execute(&mut *transaction)
             .await?;
 
-        let saved_contact = data_repo.get_contact_by_id(contact_id).await?;
+        sync_contact_methods(&mut transaction, contact_id, &self.region).await?;
 
-        let saved_contact = saved_contact.contact;
+        transaction.commit().await?;
 
-        assert_eq!(saved_contact.display_name, "Ada Lovelace".to_string());
+        Ok(result.rows_affected() > 0)
+    }
 
-        let test_contact = models::OptionalContact {
-            first_name: Some("Jason".to_string()),
-            last_name: Some("Ribble".to_string()),
-            ..models::OptionalContact::default()
-        };
+    /// Removes a phone number. When it was the preferred one, the next
+    /// number the contact has takes its place.
+    async fn remove_phone(&self, contact_id: i64, phone_number: &str) -> crate::Result<bool> {
+        let mut transaction = self.database.begin().await?;
 
-        let contact_id = data_repo
-            .save_optional_contact(test_contact.clone())
-            .await?;
+        let result =
+            sqlx::query("DELETE FROM contact_phones WHERE contact_id = $1 AND phone_number = $2")
+                .bind(contact_id)
+                .bind(phone_number.trim())
+                .execute(&mut *transaction)
+                .await?;
 
-        let saved_contact = data_repo.get_contact_by_id(contact_id).await?;
+        sqlx::query(
+            "UPDATE contacts SET phone_number = '', phone_e164 = NULL
+             WHERE id = $1 AND phone_number = $2",
+        )
+        .bind(contact_id)
+        .bind(phone_number.trim())
+        .execute(&mut *transaction)
+        .await?;
 
-        let saved_contact = saved_contact.contact;
+        sync_contact_methods(&mut transaction, contact_id, &self.region).await?;
 
-        assert_eq!(saved_contact.display_name, "Jason Ribble".to_string());
+        transaction.commit().await?;
 
-        Ok(())
+        Ok(result.rows_affected() > 0)
     }
 
-    #[tokio::test]
-    async fn should_save_two_option_contact_in_database() -> anyhow::Result<()> {
-        let pool = setup_in_memory_db().await;
+    async fn get_addresses(&self, contact_id: i64) -> crate::Result<Vec<models::IndexedAddress>> {
+        let addresses = sqlx::query_as::<_, models::IndexedAddress>(
+            "SELECT * FROM contact_addresses WHERE contact_id = $1 ORDER BY id",
+        )
+        .bind(contact_id)
+        .fetch_all(&*self.database)
+        .await?;
 
+        Ok(addresses)
+    }
+
+    async fn get_all_addresses(&self) -> crate::Result<Vec<models::IndexedAddress>> {
+        let addresses = sqlx::query_as::<_, models::IndexedAddress>(
+            "SELECT * FROM contact_addresses ORDER BY contact_id, id",
+        )
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(addresses)
+    }
+
+    async fn get_address(&self, id: i64) -> crate::Result<models::IndexedAddress> {
+        sqlx::query_as::<_, models::IndexedAddress>("SELECT * FROM contact_addresses WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&*self.database)
+            .await?
+            .ok_or(Error::AddressNotFound(id))
+    }
+
+    async fn add_address(
+        &self,
+        contact_id: i64,
+        address: &models::PostalAddress,
+    ) -> crate::Result<i64> {
+        if address.is_empty() {
+            return Err(Error::EmptyUpdate);
+        }
+        if !address.label.fits_address() {
+            return Err(Error::InvalidAddressLabel(address.label.to_string()));
+        }
+
+        let mut transaction = self.database.begin().await?;
+
+        fetch_contact(&mut transaction, contact_id).await?;
+        let id = insert_address(&mut transaction, contact_id, address).await?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    async fn update_address(&self, id: i64, address: &models::PostalAddress) -> crate::Result<()> {
+        if address.is_empty() {
+            return Err(Error::EmptyUpdate);
+        }
+        if !address.label.fits_address() {
+            return Err(Error::InvalidAddressLabel(address.label.to_string()));
+        }
+
+        let result = sqlx::query(
+            "UPDATE contact_addresses
+             SET label = $1, street = $2, locality = $3, region = $4, postal_code = $5,
+                 country = $6
+             WHERE id = $7",
+        )
+        .bind(address.label)
+        .bind(address.street.trim())
+        .bind(address.locality.trim())
+        .bind(address.region.trim())
+        .bind(address.postal_code.trim())
+        .bind(address.country.trim())
+        .bind(id)
+        .execute(&*self.database)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::AddressNotFound(id));
+        }
+
+        Ok(())
+    }
+
+    async fn remove_address(&self, id: i64) -> crate::Result<bool> {
+        let result = sqlx::query("DELETE FROM contact_addresses WHERE id = $1")
+            .bind(id)
+            .execute(&*self.database)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn add_note(&self, contact_id: i64, body: &str) -> crate::Result<i64> {
+        let mut transaction = self.database.begin().await?;
+
+        fetch_contact(&mut transaction, contact_id).await?;
+        let id = insert_note(&mut transaction, contact_id, body).await?;
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    async fn get_note(&self, id: i64) -> crate::Result<models::Note> {
+        sqlx::query_as::<_, models::Note>("SELECT * FROM notes WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&*self.database)
+            .await?
+            .ok_or(Error::NoteNotFound(id))
+    }
+
+    /// A contact's notes, oldest first.
+    async fn get_notes(&self, contact_id: i64) -> crate::Result<Vec<models::Note>> {
+        let notes = sqlx::query_as::<_, models::Note>(
+            "SELECT * FROM notes WHERE contact_id = $1 ORDER BY created_at, id",
+        )
+        .bind(contact_id)
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(notes)
+    }
+
+    async fn get_all_notes(&self) -> crate::Result<Vec<models::Note>> {
+        let notes = sqlx::query_as::<_, models::Note>(
+            "SELECT * FROM notes ORDER BY contact_id, created_at, id",
+        )
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(notes)
+    }
+
+    /// Replaces the text of a note, keeping the text it had as a revision.
+    /// Saving the same text again changes nothing.
+    async fn update_note(&self, id: i64, body: &str) -> crate::Result<()> {
+        let body = body.trim();
+        if body.is_empty() {
+            return Err(Error::EmptyNote);
+        }
+
+        let mut transaction = self.database.begin().await?;
+
+        let note = sqlx::query_as::<_, models::Note>("SELECT * FROM notes WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&mut *transaction)
+            .await?
+            .ok_or(Error::NoteNotFound(id))?;
+
+        if note.body == body {
+            return Ok(());
+        }
+
+        sqlx::query("INSERT INTO note_revisions (note_id, body, written_at) VALUES ($1, $2, $3)")
+            .bind(id)
+            .bind(&note.body)
+            .bind(note.updated_at)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query("UPDATE notes SET body = $1, updated_at = $2 WHERE id = $3")
+            .bind(body)
+            .bind(Utc::now())
+            .bind(id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn remove_note(&self, id: i64) -> crate::Result<bool> {
+        let result = sqlx::query("DELETE FROM notes WHERE id = $1")
+            .bind(id)
+            .execute(&*self.database)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The earlier texts of a note, oldest first.
+    async fn get_note_revisions(&self, id: i64) -> crate::Result<Vec<models::NoteRevision>> {
+        let revisions = sqlx::query_as::<_, models::NoteRevision>(
+            "SELECT note_id, written_at, body FROM note_revisions
+             WHERE note_id = $1
+             ORDER BY written_at, id",
+        )
+        .bind(id)
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(revisions)
+    }
+
+    async fn get_all_tags(&self) -> crate::Result<Vec<models::Tag>> {
+        let tags = sqlx::query_as::<_, models::Tag>(
+            "SELECT t.name, COUNT(ct.contact_id) AS contacts FROM tags t
+             LEFT JOIN contact_tags ct ON ct.tag_id = t.id
+             GROUP BY t.id
+             ORDER BY t.name",
+        )
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(tags)
+    }
+
+    /// Records an interaction and moves `last_seen_at` forward to it, in one
+    /// transaction. Logging an older interaction leaves `last_seen_at` alone.
+    async fn log_interaction(
+        &self,
+        contact_id: i64,
+        kind: &str,
+        note: &str,
+        occurred_at: DateTime<Utc>,
+    ) -> crate::Result<i64> {
+        let mut transaction = self.database.begin().await?;
+
+        let last_seen_at: Option<Option<DateTime<Utc>>> =
+            sqlx::query_scalar("SELECT last_seen_at FROM contacts WHERE id = $1")
+                .bind(contact_id)
+                .fetch_optional(&mut *transaction)
+                .await?;
+
+        let Some(last_seen_at) = last_seen_at else {
+            return Err(Error::NotFound(contact_id));
+        };
+
+        let result = sqlx::query(
+            "INSERT INTO interactions (contact_id, occurred_at, kind, note) VALUES ($1, $2, $3, $4)",
+        )
+        .bind(contact_id)
+        .bind(occurred_at)
+        .bind(kind.trim().to_lowercase())
+        .bind(note.trim())
+        .execute(&mut *transaction)
+        .await?;
+
+        if last_seen_at.is_none_or(|last_seen_at| last_seen_at < occurred_at) {
+            sqlx::query("UPDATE contacts SET last_seen_at = $1 WHERE id = $2")
+                .bind(occurred_at)
+                .bind(contact_id)
+                .execute(&mut *transaction)
+                .await?;
+
+            refresh_next_reminder(&mut transaction, contact_id).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    async fn get_interactions(&self, contact_id: i64) -> crate::Result<Vec<models::Interaction>> {
+        let interactions = sqlx::query_as::<_, models::Interaction>(
+            "SELECT id, contact_id, occurred_at, kind, note FROM interactions
+             WHERE contact_id = $1
+             ORDER BY occurred_at DESC, id DESC",
+        )
+        .bind(contact_id)
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(interactions)
+    }
+
+    /// Ranks contacts whose names, email, phone digits or interaction notes
+    /// start with every term of `query`.
+    async fn search(&self, query: &str) -> crate::Result<Vec<models::SearchHit>> {
+        let Some(match_query) = to_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let hits = sqlx::query_as::<_, models::SearchHit>(
+            "SELECT c.*,
+                snippet(contacts_fts, -1, '[', ']', '…', 10) AS snippet,
+                bm25(contacts_fts, 10.0, 10.0, 8.0, 5.0, 5.0, 1.0) AS rank
+             FROM contacts_fts
+             JOIN contacts c ON c.id = contacts_fts.rowid
+             WHERE contacts_fts MATCH $1
+             ORDER BY rank, c.id",
+        )
+        .bind(match_query)
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(hits)
+    }
+
+    /// Folds `drop_id` into `keep_id` in one transaction, following
+    /// `Contact::merged_with`. Tags and interactions move to the kept contact
+    /// and the dropped ID is recorded in `contact_merges`.
+    async fn merge_contacts(
+        &self,
+        keep_id: i64,
+        drop_id: i64,
+        take: &[models::ContactField],
+    ) -> crate::Result<models::IndexedContact> {
+        if keep_id == drop_id {
+            return Err(Error::SelfMerge(keep_id));
+        }
+
+        let mut transaction = self.database.begin().await?;
+
+        let keep = fetch_contact(&mut transaction, keep_id).await?;
+        let drop = fetch_contact(&mut transaction, drop_id).await?;
+
+        let merged = keep.merged_with(&drop, take);
+
+        sqlx::query(
+            "INSERT OR IGNORE INTO contact_tags (contact_id, tag_id)
+             SELECT $1, tag_id FROM contact_tags WHERE contact_id = $2",
+        )
+        .bind(keep_id)
+        .bind(drop_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query(
+            "UPDATE OR IGNORE contact_emails SET contact_id = $1, is_preferred = FALSE
+             WHERE contact_id = $2",
+        )
+        .bind(keep_id)
+        .bind(drop_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query(
+            "UPDATE OR IGNORE contact_phones SET contact_id = $1, is_preferred = FALSE
+             WHERE contact_id = $2",
+        )
+        .bind(keep_id)
+        .bind(drop_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query("UPDATE notes SET contact_id = $1 WHERE contact_id = $2")
+            .bind(keep_id)
+            .bind(drop_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query("UPDATE contact_addresses SET contact_id = $1 WHERE contact_id = $2")
+            .bind(keep_id)
+            .bind(drop_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query("UPDATE interactions SET contact_id = $1 WHERE contact_id = $2")
+            .bind(keep_id)
+            .bind(drop_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query("UPDATE contact_merges SET kept_id = $1 WHERE kept_id = $2")
+            .bind(keep_id)
+            .bind(drop_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO contact_merges (kept_id, dropped_id, dropped_name, merged_at)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(keep_id)
+        .bind(drop_id)
+        .bind(&drop.display_name)
+        .bind(Utc::now())
+        .execute(&mut *transaction)
+        .await?;
+
+        // Delete first, so the kept contact can take over its external ID
+        sqlx::query("DELETE FROM contacts WHERE id = $1")
+            .bind(drop_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query(
+            "UPDATE contacts
+             SET first_name = $1, last_name = $2, display_name = $3, email = $4,
+                 phone_number = $5, birthday = $6, starred = $7, is_archived = $8,
+                 created_at = $9, updated_at = $10, last_seen_at = $11,
+                 next_reminder_at = $12, frequency = $13, last_reminder_at = $14,
+                 external_id = $15, phone_e164 = $16
+             WHERE id = $17",
+        )
+        .bind(&merged.first_name)
+        .bind(&merged.last_name)
+        .bind(&merged.display_name)
+        .bind(&merged.email)
+        .bind(&merged.phone_number)
+        .bind(merged.birthday)
+        .bind(merged.starred)
+        .bind(merged.is_archived)
+        .bind(merged.created_at)
+        .bind(Utc::now())
+        .bind(merged.last_seen_at)
+        .bind(merged.next_reminder_at)
+        .bind(merged.frequency)
+        .bind(merged.last_reminder_at)
+        .bind(&merged.external_id)
+        .bind(&merged.phone_e164)
+        .bind(keep_id)
+        .execute(&mut *transaction)
+        .await?;
+
+        sync_contact_methods(&mut transaction, keep_id, &self.region).await?;
+
+        transaction.commit().await?;
+
+        self.get_contact_by_id(keep_id).await
+    }
+
+    /// IDs of the contacts merged into `contact_id`, oldest merge first.
+    async fn get_merged_ids(&self, contact_id: i64) -> crate::Result<Vec<i64>> {
+        let ids = sqlx::query_scalar(
+            "SELECT dropped_id FROM contact_merges WHERE kept_id = $1 ORDER BY merged_at, id",
+        )
+        .bind(contact_id)
+        .fetch_all(&*self.database)
+        .await?;
+
+        Ok(ids)
+    }
+}
+
+/// Turns free-form search terms into an FTS5 query that prefix-matches every
+/// term. Terms that look like phone numbers keep their digits only, to match
+/// the indexed `phone_digits` column.
+fn to_fts_query(query: &str) -> Option<String> {
+    let is_phone_character =
+        |character: char| character.is_ascii_digit() || " -().+/".contains(character);
+
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| {
+            if term.chars().all(is_phone_character) {
+                term.chars().filter(char::is_ascii_digit).collect()
+            } else {
+                term.replace('"', "")
+            }
+        })
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| format!("\"{term}\"*"))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+async fn insert_optional_contact(
+    connection: &mut SqliteConnection,
+    contact: &models::OptionalContact,
+    region: &str,
+) -> crate::Result<i64> {
+    let display_name = contact.display_name.clone().unwrap_or_else(|| {
+        format!(
+            "{} {}",
+            contact.first_name.clone().unwrap_or_default(),
+            contact.last_name.clone().unwrap_or_default()
+        )
+    });
+
+    let query =
+        "INSERT INTO contacts (first_name, last_name, display_name, phone_number, phone_e164, email, birthday, starred, is_archived, created_at, updated_at, last_seen_at, next_reminder_at, frequency, last_reminder_at, external_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)";
+
+    let now = Utc::now();
+    let created_at = contact.created_at.unwrap_or(now);
+
+    let result = sqlx::query(query)
+        .bind(&contact.first_name)
+        .bind(&contact.last_name)
+        .bind(display_name)
+        .bind(&contact.phone_number)
+        .bind(
+            contact
+                .phone_number
+                .as_deref()
+                .and_then(|phone| utils::normalize_phone_number(phone, region)),
+        )
+        .bind(&contact.email)
+        .bind(contact.birthday)
+        .bind(contact.starred.unwrap_or(false))
+        .bind(contact.is_archived.unwrap_or(false))
+        .bind(created_at)
+        .bind(now)
+        .bind(contact.last_seen_at)
+        .bind(models::next_reminder_at(
+            contact.frequency,
+            created_at,
+            contact.last_seen_at,
+        ))
+        .bind(contact.frequency)
+        .bind(contact.last_reminder_at)
+        .bind(&contact.external_id)
+        .execute(&mut *connection)
+        .await?;
+
+    let contact_id = result.last_insert_rowid();
+
+    for tag in &contact.tags {
+        attach_tag(connection, contact_id, tag).await?;
+    }
+    attach_contact_methods(connection, contact_id, contact, region).await?;
+    for address in &contact.addresses {
+        insert_address(connection, contact_id, address).await?;
+    }
+    for note in &contact.notes {
+        insert_note(connection, contact_id, note).await?;
+    }
+
+    Ok(contact_id)
+}
+
+/// Fills the contact in with every value the imported row holds and adds the
+/// row's tags, emails, phone numbers, addresses and notes. Addresses and
+/// notes the contact already has are not added twice. `last_seen_at` never moves backwards.
+async fn update_from_row(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    contact: &models::OptionalContact,
+    region: &str,
+) -> crate::Result<()> {
+    sqlx::query(
+        "UPDATE contacts
+         SET
+             first_name = COALESCE($1, first_name),
+             last_name = COALESCE($2, last_name),
+             display_name = COALESCE($3, display_name),
+             email = COALESCE($4, email),
+             phone_number = COALESCE($5, phone_number),
+             birthday = COALESCE($6, birthday),
+             starred = COALESCE($7, starred),
+             is_archived = COALESCE($8, is_archived),
+             last_seen_at = MAX(COALESCE($9, last_seen_at), COALESCE(last_seen_at, $9)),
+             frequency = COALESCE($10, frequency),
+             last_reminder_at = COALESCE($11, last_reminder_at),
+             external_id = COALESCE($12, external_id),
+             created_at = MIN(created_at, COALESCE($13, created_at)),
+             updated_at = $14
+         WHERE id = $15",
+    )
+    .bind(&contact.first_name)
+    .bind(&contact.last_name)
+    .bind(&contact.display_name)
+    .bind(&contact.email)
+    .bind(&contact.phone_number)
+    .bind(contact.birthday)
+    .bind(contact.starred)
+    .bind(contact.is_archived)
+    .bind(contact.last_seen_at)
+    .bind(contact.frequency)
+    .bind(contact.last_reminder_at)
+    .bind(&contact.external_id)
+    .bind(contact.created_at)
+    .bind(Utc::now())
+    .bind(contact_id)
+    .execute(&mut *connection)
+    .await?;
+
+    for tag in &contact.tags {
+        attach_tag(connection, contact_id, tag).await?;
+    }
+
+    if contact.phone_number.is_some() {
+        refresh_phone_e164(connection, contact_id, region).await?;
+    }
+    attach_contact_methods(connection, contact_id, contact, region).await?;
+    for address in &contact.addresses {
+        attach_address(connection, contact_id, address).await?;
+    }
+    for note in &contact.notes {
+        attach_note(connection, contact_id, note).await?;
+    }
+    refresh_next_reminder(connection, contact_id).await
+}
+
+async fn attach_tag(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    tag: &str,
+) -> crate::Result<()> {
+    let tag = tag.trim();
+
+    sqlx::query("INSERT INTO tags (name) VALUES ($1) ON CONFLICT (name) DO NOTHING")
+        .bind(tag)
+        .execute(&mut *connection)
+        .await?;
+
+    sqlx::query(
+        "INSERT OR IGNORE INTO contact_tags (contact_id, tag_id)
+         SELECT $1, id FROM tags WHERE name = $2",
+    )
+    .bind(contact_id)
+    .bind(tag)
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Saves an email address for the contact, keeping the label it already has
+/// when the new one is `other`.
+async fn attach_email(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    email: &models::Labeled,
+) -> crate::Result<()> {
+    sqlx::query(
+        "INSERT INTO contact_emails (contact_id, label, email) VALUES ($1, $2, $3)
+         ON CONFLICT (contact_id, email) DO UPDATE
+         SET label = CASE WHEN excluded.label = 'other' THEN label ELSE excluded.label END",
+    )
+    .bind(contact_id)
+    .bind(email.label)
+    .bind(&email.value)
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Saves a phone number for the contact, keeping the label it already has
+/// when the new one is `other`.
+async fn attach_phone(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    phone: &models::Labeled,
+    region: &str,
+) -> crate::Result<()> {
+    sqlx::query(
+        "INSERT INTO contact_phones (contact_id, label, phone_number, phone_e164)
+         VALUES ($1, $2, $3, $4)
+         ON CONFLICT (contact_id, phone_number) DO UPDATE
+         SET label = CASE WHEN excluded.label = 'other' THEN label ELSE excluded.label END",
+    )
+    .bind(contact_id)
+    .bind(phone.label)
+    .bind(&phone.value)
+    .bind(utils::normalize_phone_number(&phone.value, region))
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+/// Saves the labelled emails and phone numbers of a new or imported
+/// contact and brings the preferred ones in line.
+async fn attach_contact_methods(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    contact: &models::OptionalContact,
+    region: &str,
+) -> crate::Result<()> {
+    for email in &contact.emails {
+        attach_email(connection, contact_id, email).await?;
+    }
+    for phone in &contact.phones {
+        attach_phone(connection, contact_id, phone, region).await?;
+    }
+
+    sync_contact_methods(connection, contact_id, region).await
+}
+
+async fn insert_address(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    address: &models::PostalAddress,
+) -> crate::Result<i64> {
+    let result = sqlx::query(
+        "INSERT INTO contact_addresses
+         (contact_id, label, street, locality, region, postal_code, country)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)",
+    )
+    .bind(contact_id)
+    .bind(address.label)
+    .bind(address.street.trim())
+    .bind(address.locality.trim())
+    .bind(address.region.trim())
+    .bind(address.postal_code.trim())
+    .bind(address.country.trim())
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Adds an address unless the contact already has the same one.
+async fn attach_address(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    address: &models::PostalAddress,
+) -> crate::Result<()> {
+    let existing = sqlx::query(
+        "SELECT 1 FROM contact_addresses
+         WHERE contact_id = $1 AND street = $2 AND locality = $3 AND region = $4
+             AND postal_code = $5 AND country = $6",
+    )
+    .bind(contact_id)
+    .bind(address.street.trim())
+    .bind(address.locality.trim())
+    .bind(address.region.trim())
+    .bind(address.postal_code.trim())
+    .bind(address.country.trim())
+    .fetch_optional(&mut *connection)
+    .await?;
+
+    if existing.is_none() {
+        insert_address(connection, contact_id, address).await?;
+    }
+
+    Ok(())
+}
+
+async fn insert_note(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    body: &str,
+) -> crate::Result<i64> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(Error::EmptyNote);
+    }
+
+    let now = Utc::now();
+    let result = sqlx::query(
+        "INSERT INTO notes (contact_id, body, created_at, updated_at) VALUES ($1, $2, $3, $3)",
+    )
+    .bind(contact_id)
+    .bind(body)
+    .bind(now)
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(result.last_insert_rowid())
+}
+
+/// Adds a note unless the contact already has one with the same text.
+async fn attach_note(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    body: &str,
+) -> crate::Result<()> {
+    let existing = sqlx::query("SELECT 1 FROM notes WHERE contact_id = $1 AND body = $2")
+        .bind(contact_id)
+        .bind(body.trim())
+        .fetch_optional(&mut *connection)
+        .await?;
+
+    if existing.is_none() {
+        insert_note(connection, contact_id, body).await?;
+    }
+
+    Ok(())
+}
+
+/// Keeps the contact's `email` and `phone_number` columns and the preferred
+/// rows of `contact_emails` and `contact_phones` pointing at the same value,
+/// inside the caller's transaction. A value set on the contact is added to
+/// its list; a contact without one takes the first entry of its list.
+async fn sync_contact_methods(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    region: &str,
+) -> crate::Result<()> {
+    let (email, phone_number): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT email, phone_number FROM contacts WHERE id = $1")
+            .bind(contact_id)
+            .fetch_one(&mut *connection)
+            .await?;
+
+    let email = email.unwrap_or_default();
+    if email.trim().is_empty() {
+        sqlx::query(
+            "UPDATE contacts SET email = (
+                 SELECT email FROM contact_emails WHERE contact_id = $1
+                 ORDER BY is_preferred DESC, id LIMIT 1
+             )
+             WHERE id = $1 AND EXISTS (SELECT 1 FROM contact_emails WHERE contact_id = $1)",
+        )
+        .bind(contact_id)
+        .execute(&mut *connection)
+        .await?;
+    } else {
+        attach_email(
+            connection,
+            contact_id,
+            &models::Labeled::new(models::Label::Other, &email),
+        )
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE contact_emails
+         SET is_preferred = (email = (SELECT trim(email) FROM contacts WHERE id = $1))
+         WHERE contact_id = $1",
+    )
+    .bind(contact_id)
+    .execute(&mut *connection)
+    .await?;
+
+    let phone_number = phone_number.unwrap_or_default();
+    if phone_number.trim().is_empty() {
+        sqlx::query(
+            "UPDATE contacts SET (phone_number, phone_e164) = (
+                 SELECT phone_number, phone_e164 FROM contact_phones WHERE contact_id = $1
+                 ORDER BY is_preferred DESC, id LIMIT 1
+             )
+             WHERE id = $1 AND EXISTS (SELECT 1 FROM contact_phones WHERE contact_id = $1)",
+        )
+        .bind(contact_id)
+        .execute(&mut *connection)
+        .await?;
+    } else {
+        attach_phone(
+            connection,
+            contact_id,
+            &models::Labeled::new(models::Label::Other, &phone_number),
+            region,
+        )
+        .await?;
+    }
+
+    sqlx::query(
+        "UPDATE contact_phones
+         SET is_preferred = (phone_number = (SELECT trim(phone_number) FROM contacts WHERE id = $1))
+         WHERE contact_id = $1",
+    )
+    .bind(contact_id)
+    .execute(&mut *connection)
+    .await?;
+
+    Ok(())
+}
+
+async fn fetch_contact(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+) -> crate::Result<models::Contact> {
+    sqlx::query_as::<_, models::IndexedContact>("SELECT * FROM contacts WHERE id = $1")
+        .bind(contact_id)
+        .fetch_optional(&mut *connection)
+        .await?
+        .map(|indexed| indexed.contact)
+        .ok_or(Error::NotFound(contact_id))
+}
+
+/// Recomputes `phone_e164` after the phone number of a contact changed,
+/// inside the caller's transaction.
+async fn refresh_phone_e164(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    region: &str,
+) -> crate::Result<()> {
+    let phone_number: Option<String> =
+        sqlx::query_scalar("SELECT phone_number FROM contacts WHERE id = $1")
+            .bind(contact_id)
+            .fetch_one(&mut *connection)
+            .await?;
+
+    sqlx::query("UPDATE contacts SET phone_e164 = $1 WHERE id = $2")
+        .bind(
+            phone_number
+                .as_deref()
+                .and_then(|phone| utils::normalize_phone_number(phone, region)),
+        )
+        .bind(contact_id)
+        .execute(&mut *connection)
+        .await?;
+
+    Ok(())
+}
+
+/// Sets a field of a contact back to unknown, inside the caller's
+/// transaction. Clearing the email or phone number removes it from the
+/// contact's list too; fields that cannot be unknown are left alone.
+async fn clear_field(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+    field: models::ContactField,
+) -> crate::Result<()> {
+    let queries: &[&str] = match field {
+        models::ContactField::Email => &[
+            "DELETE FROM contact_emails WHERE contact_id = $1
+             AND email = (SELECT trim(email) FROM contacts WHERE id = $1)",
+            "UPDATE contacts SET email = '' WHERE id = $1",
+        ],
+        models::ContactField::PhoneNumber => &[
+            "DELETE FROM contact_phones WHERE contact_id = $1
+             AND phone_number = (SELECT trim(phone_number) FROM contacts WHERE id = $1)",
+            "UPDATE contacts SET phone_number = '', phone_e164 = NULL WHERE id = $1",
+        ],
+        models::ContactField::Birthday => &["UPDATE contacts SET birthday = NULL WHERE id = $1"],
+        models::ContactField::LastSeenAt => {
+            &["UPDATE contacts SET last_seen_at = NULL WHERE id = $1"]
+        }
+        models::ContactField::Frequency => &["UPDATE contacts SET frequency = NULL WHERE id = $1"],
+        models::ContactField::LastReminderAt => {
+            &["UPDATE contacts SET last_reminder_at = NULL WHERE id = $1"]
+        }
+        _ => &[],
+    };
+
+    for query in queries {
+        sqlx::query(query)
+            .bind(contact_id)
+            .execute(&mut *connection)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Sets `next_reminder_at` of contacts that have a cadence but no reminder,
+/// such as those whose cadence was set before reminders were kept.
+pub(super) async fn fill_missing_reminders(pool: &SqlitePool) -> crate::Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    let ids: Vec<i64> = sqlx::query_scalar(
+        "SELECT id FROM contacts WHERE frequency IS NOT NULL AND next_reminder_at IS NULL",
+    )
+    .fetch_all(&mut *transaction)
+    .await?;
+
+    for id in ids {
+        refresh_next_reminder(&mut transaction, id).await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+/// Recomputes `next_reminder_at` after the cadence or `last_seen_at` of a
+/// contact changed, inside the caller's transaction.
+async fn refresh_next_reminder(
+    connection: &mut SqliteConnection,
+    contact_id: i64,
+) -> crate::Result<()> {
+    let (frequency, created_at, last_seen_at): (
+        Option<models::Cadence>,
+        DateTime<Utc>,
+        Option<DateTime<Utc>>,
+    ) = sqlx::query_as("SELECT frequency, created_at, last_seen_at FROM contacts WHERE id = $1")
+        .bind(contact_id)
+        .fetch_one(&mut *connection)
+        .await?;
+
+    sqlx::query("UPDATE contacts SET next_reminder_at = $1 WHERE id = $2")
+        .bind(models::next_reminder_at(
+            frequency,
+            created_at,
+            last_seen_at,
+        ))
+        .bind(contact_id)
+        .execute(&mut *connection)
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+#[allow(clippy::panic_in_result_fn)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use mockall::predicate::*;
+    use test_utils::setup_in_memory_db;
+
+    #[tokio::test]
+    async fn test_save_contact() {
+        let mut mock_contact_repo = MockContactRepo::new();
+
+        let test_contact = models::Contact::builder()
+            .first_name("John")
+            .last_name("Smith")
+            .email("johndoe@example.com")
+            .phone_number("201-555-0123")
+            .birthday("1970-1-1")
+            .build()
+            .expect("Test contact");
+
+        mock_contact_repo
+            .expect_save_contact()
+            .times(1)
+            .with(eq(test_contact.clone()))
+            .returning(|_| Ok(1));
+
+        let result = mock_contact_repo
+            .save_contact(test_contact)
+            .await
+            .expect("Saved contact");
+
+        assert_eq!(result, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_all_contacts() {
+        let mut mock_contact_repo = MockContactRepo::new();
+
+        let contacts = vec![models::IndexedContact {
+            id: 1,
+            contact: models::Contact::builder()
+                .first_name("John")
+                .last_name("Doe")
+                .email("johndoe@example.com")
+                .phone_number("2015550123")
+                .birthday("1970-01-01")
+                .build()
+                .expect("Test contact"),
+        }];
+
+        mock_contact_repo
+            .expect_get_all_contacts()
+            .times(1)
+            .return_once(move || Ok(contacts));
+
+        let result = mock_contact_repo.get_all_contacts().await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_update_contact() {
+        let mut mock_contact_repo = MockContactRepo::new();
+
+        mock_contact_repo
+            .expect_update_contact()
+            .times(1)
+            .return_once(|_| Ok(()));
+
+        let edits = models::ContactBuilder::new(
+            1,
+            None,
+            None,
+            Some("some@email.com".to_string()),
+            None,
+            None,
+            None,
+        )
+        .expect("Contact with only an email");
+
+        let result = mock_contact_repo.update_contact(edits).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_get_contact_by_id() {
+        let mut mock_contact_repo = MockContactRepo::new();
+
+        let contact = models::IndexedContact {
+            id: 1,
+            contact: models::Contact::builder()
+                .first_name("John")
+                .last_name("Doe")
+                .email("johndoe@example.com")
+                .phone_number("2015550123")
+                .birthday("1970-01-01")
+                .build()
+                .expect("Default contact"),
+        };
+
+        mock_contact_repo
+            .expect_get_contact_by_id()
+            .times(1)
+            .with(eq(contact.id))
+            .return_once(|_| Ok(contact));
+
+        let result = mock_contact_repo.get_contact_by_id(1).await;
+
+        assert!(result.is_ok());
+
+        let actual_contact = result.expect("Actual Contact");
+
+        assert_eq!(actual_contact.id, 1);
+    }
+
+    #[tokio::test]
+    async fn should_save_option_contact_in_database() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let test_contact = models::OptionalContact {
+            first_name: Some("Jason".to_string()),
+            ..models::OptionalContact::default()
+        };
+
+        let result = data_repo.save_optional_contact(test_contact).await?;
+
+        assert_eq!(result, 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_be_able_to_retrieve_full_contact_when_saved_full_contact() -> anyhow::Result<()>
+    {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let test_contact = models::OptionalContact {
+            first_name: Some("Ada".to_string()),
+            last_name: Some("Lovelace".to_string()),
+            display_name: Some("Addy".to_string()),
+            email: Some("ada@lovelace.rs".to_string()),
+            phone_number: Some("2015550133".to_string()),
+            birthday: models::Birthday::new(Some(1970), 1, 1),
+            starred: None,
+            is_archived: None,
+            created_at: None,
+            last_seen_at: None,
+            frequency: None,
+            last_reminder_at: None,
+            external_id: None,
+            tags: Vec::new(),
+            emails: Vec::new(),
+            phones: Vec::new(),
+            addresses: Vec::new(),
+            notes: Vec::new(),
+            cleared: Vec::new(),
+        };
+
+        let contact_id = data_repo
+            .save_optional_contact(test_contact.clone())
+            .await?;
+
+        let saved_contact = data_repo.get_contact_by_id(contact_id).await?;
+
+        let saved_contact = saved_contact.contact;
+
+        assert_eq!(
+            saved_contact.first_name,
+            test_contact.first_name.expect("Contact first name")
+        );
+        assert_eq!(
+            saved_contact.last_name,
+            test_contact.last_name.expect("Contact last name")
+        );
+        assert_eq!(
+            saved_contact.phone_number,
+            test_contact.phone_number.expect("Contact phone number")
+        );
+
+        assert_eq!(
+            saved_contact.email,
+            test_contact.email.expect("Contact emails")
+        );
+
+        assert_eq!(
+            saved_contact.display_name,
+            test_contact.display_name.expect("Display name")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_default_to_first_and_last_name_for_display_name() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let test_contact = models::OptionalContact {
+            first_name: Some("Ada".to_string()),
+            last_name: Some("Lovelace".to_string()),
+            birthday: models::Birthday::new(Some(1970), 1, 1),
+            ..models::OptionalContact::default()
+        };
+
+        let contact_id = data_repo
+            .save_optional_contact(test_contact.clone())
+            .await?;
+
+        let saved_contact = data_repo.get_contact_by_id(contact_id).await?;
+
+        let saved_contact = saved_contact.contact;
+
+        assert_eq!(saved_contact.display_name, "Ada Lovelace".to_string());
+
+        let test_contact = models::OptionalContact {
+            first_name: Some("Jason".to_string()),
+            last_name: Some("Ribble".to_string()),
+            ..models::OptionalContact::default()
+        };
+
+        let contact_id = data_repo
+            .save_optional_contact(test_contact.clone())
+            .await?;
+
+        let saved_contact = data_repo.get_contact_by_id(contact_id).await?;
+
+        let saved_contact = saved_contact.contact;
+
+        assert_eq!(saved_contact.display_name, "Jason Ribble".to_string());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_save_two_option_contact_in_database() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let test_contact = models::OptionalContact {
+            first_name: Some("Jason".to_string()),
+            ..models::OptionalContact::default()
+        };
+
+        let contact_id = data_repo.save_optional_contact(test_contact).await?;
+
+        assert_eq!(contact_id, 1);
+
+        let another_contact = models::OptionalContact {
+            first_name: Some("Alice".to_string()),
+            ..models::OptionalContact::default()
+        };
+
+        let contact_id = data_repo.save_optional_contact(another_contact).await?;
+
+        assert_eq!(contact_id, 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_store_one_contact_when_given_alice_csv() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let example_csv = "tests/fixtures/alice.csv";
+
+        let number_of_imported_contacts = Ok(data_repo.import_contacts_by_csv(example_csv).await?);
+
+        let number_of_contacts = i64::try_from(data_repo.get_all_contacts().await?.len());
+
+        assert_eq!(number_of_contacts, number_of_imported_contacts);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_store_three_contacts_when_given_example() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let example_csv = "tests/fixtures/example.csv";
+
+        let number_of_imported_contacts = Ok(data_repo.import_contacts_by_csv(example_csv).await?);
+
+        let number_of_contacts = i64::try_from(data_repo.get_all_contacts().await?.len());
+
+        assert_eq!(number_of_contacts, number_of_imported_contacts);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_create_metadata_when_importing_csv() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let example_csv = "tests/fixtures/example.csv";
+
+        let number_of_imported_contacts = data_repo.import_contacts_by_csv(example_csv).await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+        let last_contact = contacts.last().expect("Contact has a last item");
+        assert_eq!(number_of_imported_contacts, last_contact.id);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_save_csv_with_one_row_and_birthday() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let example_csv = "tests/fixtures/birthday.csv";
+
+        data_repo.import_contacts_by_csv(example_csv).await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+
+        let aldous_huxley_birthday = contacts.first().unwrap().contact.birthday;
+
+        assert_eq!(
+            aldous_huxley_birthday,
+            models::Birthday::new(Some(1894), 7, 26)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_save_csv_with_threes_row_and_birthday() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let example_csv = "tests/fixtures/three_birthdays.csv";
+
+        data_repo.import_contacts_by_csv(example_csv).await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+
+        let aldous_huxley_birthday = contacts.first().unwrap().contact.birthday;
+
+        assert_eq!(
+            aldous_huxley_birthday,
+            models::Birthday::new(Some(1894), 7, 26)
+        );
+
+        let cs_lewis_birthday = contacts.get(2).unwrap().contact.birthday;
+
+        assert_eq!(cs_lewis_birthday, models::Birthday::new(Some(1898), 11, 29));
+
+        Ok(())
+    }
+
+    fn write_csv(contents: &str) -> anyhow::Result<tempfile::NamedTempFile> {
+        use std::io::Write;
+
+        let mut file = tempfile::NamedTempFile::with_suffix(".csv")?;
+        write!(file, "{contents}")?;
+
+        Ok(file)
+    }
+
+    #[tokio::test]
+    async fn should_import_nothing_when_a_row_is_invalid() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        let csv = write_csv(
+            "first_name,email,phone_number\nAlice,alice@example.com,\nBob,bob@,\nCy,,12\n",
+        )?;
+        let filename = csv.path().to_str().unwrap();
+
+        let error = data_repo
+            .import_contacts(filename, &utils::ImportOptions::default())
+            .await
+            .unwrap_err();
+
+        let Error::InvalidRows(rows) = error else {
+            panic!("Expected invalid rows, got {error:?}");
+        };
+        let rows: Vec<(u64, &str)> = rows
+            .iter()
+            .map(|row| (row.line, row.column.as_str()))
+            .collect();
+        assert_eq!(rows, [(3, "email"), (4, "phone_number")]);
+        assert!(data_repo.get_all_contacts().await?.is_empty());
+
+        let options = utils::ImportOptions {
+            skip_invalid: true,
+            ..utils::ImportOptions::default()
+        };
+        let report = data_repo.import_contacts(filename, &options).await?;
+
+        assert_eq!(report.created, 1);
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(data_repo.get_all_contacts().await?.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_roll_back_a_dry_run() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        let csv = write_csv("first_name,tags\nAlice,work\nBob,\nAlice,climbing\n")?;
+
+        let options = utils::ImportOptions {
+            dry_run: true,
+            ..utils::ImportOptions::default()
+        };
+        let report = data_repo
+            .import_contacts(csv.path().to_str().unwrap(), &options)
+            .await?;
+
+        assert_eq!((report.created, report.updated), (2, 1));
+        assert!(data_repo.get_all_contacts().await?.is_empty());
+        assert!(data_repo.get_all_tags().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_follow_the_on_duplicate_mode() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        let first = write_csv("first_name,email\nAlice,alice@example.com\n")?;
+        let second = write_csv("first_name,last_name,email\nAlice,Liddell,ALICE@example.com\n")?;
+        let import = |file: &tempfile::NamedTempFile, on_duplicate| {
+            let options = utils::ImportOptions {
+                on_duplicate,
+                ..utils::ImportOptions::default()
+            };
+            let filename = file.path().to_str().unwrap().to_owned();
+            let data_repo = &data_repo;
+            async move { data_repo.import_contacts(&filename, &options).await }
+        };
+
+        import(&first, utils::OnDuplicate::Update).await?;
+
+        let report = import(&second, utils::OnDuplicate::Skip).await?;
+        assert_eq!(report.skipped, 1);
+        assert_eq!(data_repo.get_contact_by_id(1).await?.contact.last_name, "");
+
+        let report = import(&second, utils::OnDuplicate::Update).await?;
+        assert_eq!(report.updated, 1);
+        assert_eq!(
+            data_repo.get_contact_by_id(1).await?.contact.last_name,
+            "Liddell"
+        );
+
+        let report = import(&second, utils::OnDuplicate::Create).await?;
+        assert_eq!(report.created, 1);
+        assert_eq!(data_repo.get_all_contacts().await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_update_contacts_by_external_id_on_reimport() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        let first = write_csv("external_id,first_name,email\nc1,Alice,alice@example.com\n")?;
+        let second = write_csv("external_id,first_name,email\nc1,Alice,alice@wonderland.com\n")?;
+        let options = utils::ImportOptions {
+            on_duplicate: utils::OnDuplicate::Create,
+            ..utils::ImportOptions::default()
+        };
+
+        data_repo
+            .import_contacts(first.path().to_str().unwrap(), &options)
+            .await?;
+        let report = data_repo
+            .import_contacts(second.path().to_str().unwrap(), &options)
+            .await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+        assert_eq!(report.updated, 1);
+        assert_eq!(contacts.len(), 1);
+        assert_eq!(
+            contacts.first().unwrap().contact.email,
+            "alice@wonderland.com"
+        );
+        assert_eq!(
+            contacts.first().unwrap().contact.external_id.as_deref(),
+            Some("c1")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_keep_the_creation_date_of_imported_contacts() -> anyhow::Result<()> {
+        let data_repo = Repo::new(setup_in_memory_db().await);
+        let created_at: DateTime<Utc> = "2020-01-02T03:04:05Z".parse()?;
+        let file = write_csv(
+            "id,first_name,email,frequency,created_at,updated_at\n\
+             7,Ada,ada@example.com,monthly,2020-01-02T03:04:05Z,2021-01-01T00:00:00Z\n",
+        )?;
+
+        data_repo
+            .import_contacts(
+                file.path().to_str().unwrap(),
+                &utils::ImportOptions::default(),
+            )
+            .await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+        let ada = contacts.first().unwrap();
+        assert_eq!(ada.id, 1);
+        assert_eq!(ada.contact.created_at, created_at);
+        assert!(ada.contact.updated_at > created_at);
+        assert_eq!(
+            ada.contact.next_reminder_at,
+            "monthly".parse::<models::Cadence>()?.after(created_at)
+        );
+
+        let mut exported = Vec::new();
+        utils::write_contacts_csv(&contacts, &[], &mut exported)?;
+        let export = write_csv(&String::from_utf8(exported)?)?;
+        let other_repo = Repo::new(setup_in_memory_db().await);
+        other_repo
+            .import_contacts(
+                export.path().to_str().unwrap(),
+                &utils::ImportOptions::default(),
+            )
+            .await?;
+
+        let other = other_repo.get_all_contacts().await?;
+        assert_eq!(other.first().unwrap().contact.created_at, created_at);
+
+        let later =
+            write_csv("first_name,email,created_at\nAda,ada@example.com,2024-01-01T00:00:00Z\n")?;
+        data_repo
+            .import_contacts(
+                later.path().to_str().unwrap(),
+                &utils::ImportOptions::default(),
+            )
+            .await?;
+
+        let ada = data_repo.get_contact_by_id(1).await?;
+        assert_eq!(ada.contact.created_at, created_at);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_store_two_contacts_when_given_example_vcard() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+
+        let data_repo = Repo::new(pool);
+
+        let example_vcf = "tests/fixtures/example.vcf";
+
+        let report = data_repo
+            .import_contacts(example_vcf, &utils::ImportOptions::default())
+            .await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+
+        assert_eq!(report.imported(), 2);
+        assert_eq!(contacts.len(), 2);
+
+        let ada = &contacts.first().unwrap().contact;
+        assert_eq!(ada.first_name, "Ada");
+        assert_eq!(ada.email, "ada@lovelace.com");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_round_trip_contacts_through_a_vcard_export() -> anyhow::Result<()> {
+        use std::io::Write;
+
+        let data_repo = Repo::new(setup_in_memory_db().await);
+        let mut source = tempfile::NamedTempFile::with_suffix(".vcf")?;
+        write!(
+            source,
+            "BEGIN:VCARD\r\nVERSION:4.0\r\nFN:Ada Lovelace\r\nN:Lovelace;Ada;;;\r\n\
+             EMAIL;TYPE=home;PREF=1:ada@lovelace.com\r\nEMAIL;TYPE=work:ada@engine.org\r\n\
+             TEL;TYPE=cell:201-555-0133\r\nTEL;TYPE=work:201-555-0188\r\n\
+             ADR;TYPE=home:;;12 St James's Square;London;;SW1Y 4JH;United Kingdom\r\n\
+             CATEGORIES:math,poetry\r\nNOTE:Wrote the first program\\; loves poetry\r\n\
+             END:VCARD\r\n"
+        )?;
+        let options = utils::ImportOptions {
+            on_duplicate: utils::OnDuplicate::Create,
+            ..utils::ImportOptions::default()
+        };
+
+        data_repo
+            .import_contacts(source.path().to_str().unwrap(), &options)
+            .await?;
+        let id = data_repo.get_all_contacts().await?.first().unwrap().id;
+        let card = data_repo.get_contact_card(id).await?;
+
+        let mut export = tempfile::NamedTempFile::with_suffix(".vcf")?;
+        write!(
+            export,
+            "{}",
+            utils::contacts_to_vcards(std::slice::from_ref(&card), utils::VcardVersion::V4)
+        )?;
+        let export = export.path().to_str().unwrap();
+
+        let report = data_repo.import_contacts(export, &options).await?;
+
+        assert_eq!(report.created, 0);
+        assert_eq!(report.updated, 1);
+        assert_eq!(data_repo.get_all_contacts().await?.len(), 1);
+        let reimported = data_repo.get_contact_card(id).await?;
+        assert_eq!(reimported.contact.uid(), card.contact.uid());
+        assert_eq!(
+            models::ContactCard {
+                contact: card.contact.clone(),
+                ..reimported
+            },
+            card
+        );
+
+        let other_repo = Repo::new(setup_in_memory_db().await);
+        other_repo.import_contacts(export, &options).await?;
+        let other_id = other_repo.get_all_contacts().await?.first().unwrap().id;
+        let other = other_repo.get_contact_card(other_id).await?;
+
+        let emails = |card: &models::ContactCard| {
+            card.emails
+                .iter()
+                .map(|email| (email.label, email.email.clone(), email.is_preferred))
+                .collect::<Vec<_>>()
+        };
+        let phones = |card: &models::ContactCard| {
+            card.phones
+                .iter()
+                .map(|phone| (phone.label, phone.phone_number.clone(), phone.is_preferred))
+                .collect::<Vec<_>>()
+        };
+        let addresses = |card: &models::ContactCard| {
+            card.addresses
+                .iter()
+                .map(|indexed| indexed.address.clone())
+                .collect::<Vec<_>>()
+        };
+        let notes = |card: &models::ContactCard| {
+            card.notes
+                .iter()
+                .map(|note| note.body.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(card.emails.len(), 2);
+        assert_eq!(card.phones.len(), 2);
+        assert_eq!(emails(&other), emails(&card));
+        assert_eq!(phones(&other), phones(&card));
+        assert_eq!(addresses(&other), addresses(&card));
+        assert_eq!(other.tags, card.tags);
+        assert_eq!(notes(&other), notes(&card));
+        assert_eq!(other.contact.contact.email, card.contact.contact.email);
+        assert_eq!(
+            other.contact.contact.phone_number,
+            card.contact.contact.phone_number
+        );
+
+        Ok(())
+    }
+
+    async fn save_named_contact(data_repo: &Repo<SqlitePool>, first_name: &str) -> i64 {
+        let contact = models::OptionalContact {
+            first_name: Some(first_name.to_string()),
+            ..models::OptionalContact::default()
+        };
+
+        data_repo
+            .save_optional_contact(contact)
+            .await
+            .expect("Saved contact")
+    }
+
+    #[tokio::test]
+    async fn should_add_and_list_tags_of_a_contact() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let contact_id = save_named_contact(&data_repo, "Ada").await;
+
+        data_repo.add_tag(contact_id, "work").await?;
+        data_repo.add_tag(contact_id, "climbing").await?;
+        data_repo.add_tag(contact_id, "Work").await?;
+
+        let tags = data_repo.get_tags(contact_id).await?;
+
+        assert_eq!(tags, vec!["climbing".to_string(), "work".to_string()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_remove_tag_and_forget_unused_tags() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let contact_id = save_named_contact(&data_repo, "Ada").await;
+        data_repo.add_tag(contact_id, "work").await?;
+
+        assert!(data_repo.remove_tag(contact_id, "work").await?);
+        assert!(!data_repo.remove_tag(contact_id, "work").await?);
+        assert!(data_repo.get_all_tags().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_count_contacts_per_tag() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let ada = save_named_contact(&data_repo, "Ada").await;
+        let alice = save_named_contact(&data_repo, "Alice").await;
+        data_repo.add_tag(ada, "work").await?;
+        data_repo.add_tag(alice, "work").await?;
+        data_repo.add_tag(alice, "family").await?;
+
+        let tags = data_repo.get_all_tags().await?;
+
+        assert_eq!(
+            tags,
+            vec![
+                models::Tag {
+                    name: "family".to_string(),
+                    contacts: 1
+                },
+                models::Tag {
+                    name: "work".to_string(),
+                    contacts: 2
+                },
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_filter_contacts_by_tags() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let ada = save_named_contact(&data_repo, "Ada").await;
+        let alice = save_named_contact(&data_repo, "Alice").await;
+        save_named_contact(&data_repo, "Bob").await;
+        data_repo.add_tag(ada, "work").await?;
+        data_repo.add_tag(alice, "work").await?;
+        data_repo.add_tag(alice, "family").await?;
+
+        let work = data_repo
+            .get_contacts_by_tags(&["work".to_string()], &[])
+            .await?;
+        let work_not_family = data_repo
+            .get_contacts_by_tags(&["work".to_string()], &["family".to_string()])
+            .await?;
+        let not_work = data_repo
+            .get_contacts_by_tags(&[], &["work".to_string()])
+            .await?;
+
+        assert_eq!(work.len(), 2);
+        assert_eq!(work_not_family.first().unwrap().id, ada);
+        assert_eq!(work_not_family.len(), 1);
+        assert_eq!(not_work.first().unwrap().contact.first_name, "Bob");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_tagging_nonexistent_id() {
+        let pool = setup_in_memory_db().await;
         let data_repo = Repo::new(pool);
 
-        let test_contact = models::OptionalContact {
-            first_name: Some("Jason".to_string()),
-            ..models::OptionalContact::default()
-        };
+        let result = data_repo.add_tag(999, "work").await;
 
-        let contact_id = data_repo.save_optional_contact(test_contact).await?;
+        assert!(matches!(result, Err(Error::NotFound(999))));
+    }
 
-        assert_eq!(contact_id, 1);
+    #[tokio::test]
+    async fn should_save_tags_of_imported_contacts() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
 
-        let another_contact = models::OptionalContact {
-            first_name: Some("Alice".to_string()),
-            ..models::OptionalContact::default()
-        };
+        data_repo
+            .import_contacts("tests/fixtures/tags.csv", &utils::ImportOptions::default())
+            .await?;
 
-        let contact_id = data_repo.save_optional_contact(another_contact).await?;
+        let tags = data_repo.get_tags(1).await?;
 
-        assert_eq!(contact_id, 2);
+        assert_eq!(tags, vec!["climbing".to_string(), "work".to_string()]);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_store_one_contact_when_given_alice_csv() -> anyhow::Result<()> {
+    async fn should_update_last_seen_at_when_logging_interaction() -> anyhow::Result<()> {
         let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let contact_id = save_named_contact(&data_repo, "Ada").await;
+        let at = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+
+        data_repo
+            .log_interaction(contact_id, "Call", "Talked about engines", at)
+            .await?;
+
+        let contact = data_repo.get_contact_by_id(contact_id).await?;
+        let interactions = data_repo.get_interactions(contact_id).await?;
+
+        assert_eq!(contact.contact.last_seen_at, Some(at));
+        assert_eq!(interactions.len(), 1);
+        assert_eq!(interactions.first().unwrap().kind, "call");
+        assert_eq!(interactions.first().unwrap().occurred_at, at);
+
+        Ok(())
+    }
 
+    #[tokio::test]
+    async fn should_keep_latest_last_seen_at_when_logging_older_interaction() -> anyhow::Result<()>
+    {
+        let pool = setup_in_memory_db().await;
         let data_repo = Repo::new(pool);
 
-        let example_csv = "tests/fixtures/alice.csv";
+        let contact_id = save_named_contact(&data_repo, "Ada").await;
+        let latest = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        let older = Utc.with_ymd_and_hms(2026, 9, 1, 12, 0, 0).unwrap();
 
-        let number_of_imported_contacts = Ok(data_repo.import_contacts_by_csv(example_csv).await?);
+        data_repo
+            .log_interaction(contact_id, "coffee", "", latest)
+            .await?;
+        data_repo
+            .log_interaction(contact_id, "email", "", older)
+            .await?;
 
-        let number_of_contacts = i64::try_from(data_repo.get_all_contacts().await?.len());
+        let contact = data_repo.get_contact_by_id(contact_id).await?;
+        let kinds: Vec<String> = data_repo
+            .get_interactions(contact_id)
+            .await?
+            .into_iter()
+            .map(|interaction| interaction.kind)
+            .collect();
 
-        assert_eq!(number_of_contacts, number_of_imported_contacts);
+        assert_eq!(contact.contact.last_seen_at, Some(latest));
+        assert_eq!(kinds, vec!["coffee".to_string(), "email".to_string()]);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_store_three_contacts_when_given_example() -> anyhow::Result<()> {
+    async fn should_recompute_next_reminder_when_last_seen_at_changes() -> anyhow::Result<()> {
         let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let contact_id = save_named_contact(&data_repo, "Ada").await;
+        let update = models::ContactBuilder::builder()
+            .id(contact_id)
+            .frequency("every 2 weeks".parse()?)
+            .build()?;
+        data_repo.update_contact(update).await?;
+
+        let contact = data_repo.get_contact_by_id(contact_id).await?.contact;
+        assert_eq!(contact.next_reminder_at, contact.due_at());
+        assert!(contact.next_reminder_at.is_some());
+
+        let seen = Utc.with_ymd_and_hms(2026, 10, 1, 12, 0, 0).unwrap();
+        data_repo
+            .log_interaction(contact_id, "coffee", "", seen)
+            .await?;
+
+        let contact = data_repo.get_contact_by_id(contact_id).await?.contact;
+        assert_eq!(
+            contact.next_reminder_at,
+            Some(Utc.with_ymd_and_hms(2026, 10, 15, 12, 0, 0).unwrap())
+        );
+
+        Ok(())
+    }
 
+    #[tokio::test]
+    async fn should_clear_fields_back_to_unknown() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
         let data_repo = Repo::new(pool);
 
-        let example_csv = "tests/fixtures/example.csv";
+        let contact_id = save_named_contact(&data_repo, "Ada").await;
+        data_repo
+            .add_email(contact_id, &"work:ada@work.com".parse()?, true)
+            .await?;
+        data_repo
+            .add_email(contact_id, &"home:ada@home.com".parse()?, false)
+            .await?;
+        let update = models::ContactBuilder::builder()
+            .id(contact_id)
+            .birthday("1815-12-10".parse()?)
+            .frequency("monthly".parse()?)
+            .build()?;
+        data_repo.update_contact(update).await?;
+
+        let update = models::ContactBuilder::builder()
+            .id(contact_id)
+            .clear(models::ContactField::Birthday)
+            .clear(models::ContactField::Frequency)
+            .clear(models::ContactField::Email)
+            .build()?;
+        data_repo.update_contact(update).await?;
+
+        let contact = data_repo.get_contact_by_id(contact_id).await?.contact;
+        assert_eq!(contact.birthday, None);
+        assert_eq!(contact.frequency, None);
+        assert_eq!(contact.next_reminder_at, None);
+        assert_eq!(contact.email, "ada@home.com");
+        assert_eq!(data_repo.get_emails(contact_id).await?.len(), 1);
 
-        let number_of_imported_contacts = Ok(data_repo.import_contacts_by_csv(example_csv).await?);
+        Ok(())
+    }
 
-        let number_of_contacts = i64::try_from(data_repo.get_all_contacts().await?.len());
+    #[tokio::test]
+    async fn should_list_due_contacts_most_overdue_first() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
 
-        assert_eq!(number_of_contacts, number_of_imported_contacts);
+        let ada = save_named_contact(&data_repo, "Ada").await;
+        let alice = save_named_contact(&data_repo, "Alice").await;
+        let bob = save_named_contact(&data_repo, "Bob").await;
+        let archived = save_named_contact(&data_repo, "Archie").await;
+
+        for (id, seen_month) in [(ada, 8), (alice, 6), (bob, 10), (archived, 1)] {
+            let update = models::ContactBuilder::builder()
+                .id(id)
+                .frequency("monthly".parse()?)
+                .build()?;
+            data_repo.update_contact(update).await?;
+            let seen = Utc.with_ymd_and_hms(2026, seen_month, 1, 0, 0, 0).unwrap();
+            data_repo.log_interaction(id, "call", "", seen).await?;
+        }
+        let archive = models::ContactBuilder::builder()
+            .id(archived)
+            .archived(true)
+            .build()?;
+        data_repo.update_contact(archive).await?;
+
+        let due_by = Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap();
+        let ids: Vec<i64> = data_repo
+            .get_due_contacts(due_by)
+            .await?
+            .into_iter()
+            .map(|indexed| indexed.id)
+            .collect();
+
+        assert_eq!(ids, vec![alice, ada]);
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_create_metadata_when_importing_csv() -> anyhow::Result<()> {
+    async fn should_remind_of_contacts_whose_cadence_predates_reminders() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        sqlx::query(
+            "INSERT INTO contacts (first_name, last_name, display_name, phone_number, email, created_at, updated_at, frequency)
+             VALUES ('Ada', '', 'Ada', '', '', '2026-01-05T00:00:00Z', '2026-01-05T00:00:00Z', 'monthly')",
+        )
+        .execute(&pool)
+        .await?;
+        let data_repo = Repo::new(pool);
+        let due_by = Utc.with_ymd_and_hms(2026, 10, 18, 0, 0, 0).unwrap();
+        assert!(data_repo.get_due_contacts(due_by).await?.is_empty());
+
+        fill_missing_reminders(&data_repo.database).await?;
+
+        let due = data_repo.get_due_contacts(due_by).await?;
+        assert_eq!(due.len(), 1);
+        assert_eq!(
+            due[0].contact.next_reminder_at,
+            Some(Utc.with_ymd_and_hms(2026, 2, 5, 0, 0, 0).unwrap())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_build_prefix_queries_from_terms() {
+        assert_eq!(
+            to_fts_query("Dan conf"),
+            Some("\"Dan\"* \"conf\"*".to_string())
+        );
+        assert_eq!(
+            to_fts_query("(555) 123-4"),
+            Some("\"555\"* \"1234\"*".to_string())
+        );
+        assert_eq!(
+            to_fts_query("say \"hi\""),
+            Some("\"say\"* \"hi\"*".to_string())
+        );
+        assert_eq!(to_fts_query("  - "), None);
+    }
+
+    async fn save_searchable_contacts(data_repo: &Repo<SqlitePool>) -> anyhow::Result<()> {
+        let dan = models::OptionalContact {
+            first_name: Some("Dan".to_string()),
+            last_name: Some("Brown".to_string()),
+            email: Some("dan@example.com".to_string()),
+            phone_number: Some("(303) 555-0167".to_string()),
+            ..models::OptionalContact::default()
+        };
+        let danielle = models::OptionalContact {
+            first_name: Some("Danielle".to_string()),
+            last_name: Some("Smith".to_string()),
+            ..models::OptionalContact::default()
+        };
+        let ada = models::OptionalContact {
+            first_name: Some("Ada".to_string()),
+            email: Some("ada@dan.example".to_string()),
+            ..models::OptionalContact::default()
+        };
+
+        data_repo.save_optional_contact(dan).await?;
+        data_repo.save_optional_contact(danielle).await?;
+        data_repo.save_optional_contact(ada).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_rank_name_matches_before_email_matches() -> anyhow::Result<()> {
         let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        save_searchable_contacts(&data_repo).await?;
+
+        let names: Vec<String> = data_repo
+            .search("dan")
+            .await?
+            .into_iter()
+            .map(|hit| hit.contact.contact.first_name)
+            .collect();
+
+        assert_eq!(names.len(), 3);
+        assert_eq!(names.last(), Some(&"Ada".to_string()));
 
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_store_phone_numbers_in_e164_form() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
         let data_repo = Repo::new(pool);
 
-        let example_csv = "tests/fixtures/example.csv";
+        let id = data_repo
+            .save_optional_contact(models::OptionalContact {
+                first_name: Some("Ada".to_string()),
+                phone_number: Some("(201) 555-0123".to_string()),
+                ..models::OptionalContact::default()
+            })
+            .await?;
 
-        let number_of_imported_contacts = data_repo.import_contacts_by_csv(example_csv).await?;
+        let ada = data_repo.get_contact_by_id(id).await?.contact;
+        assert_eq!(ada.phone_number, "(201) 555-0123");
+        assert_eq!(ada.phone_e164.as_deref(), Some("+12015550123"));
+
+        data_repo
+            .update_contact(
+                models::ContactBuilder::builder()
+                    .id(id)
+                    .phone_number("+44 20 7946 0958".to_string())
+                    .build()?,
+            )
+            .await?;
 
-        let contacts = data_repo.get_all_contacts().await?;
-        let last_contact = contacts.last().expect("Contact has a last item");
-        assert_eq!(number_of_imported_contacts, last_contact.id);
+        let ada = data_repo.get_contact_by_id(id).await?.contact;
+        assert_eq!(ada.phone_number, "+44 20 7946 0958");
+        assert_eq!(ada.phone_e164.as_deref(), Some("+442079460958"));
 
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_save_csv_with_one_row_and_birthday() -> anyhow::Result<()> {
+    async fn should_read_phone_numbers_in_the_region_of_the_repo() -> anyhow::Result<()> {
         let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool).with_region("GB");
+
+        let id = data_repo
+            .save_optional_contact(models::OptionalContact {
+                first_name: Some("Ada".to_string()),
+                phone_number: Some("020 7946 0958".to_string()),
+                ..models::OptionalContact::default()
+            })
+            .await?;
+
+        let ada = data_repo.get_contact_by_id(id).await?;
+        assert_eq!(ada.contact.phone_e164.as_deref(), Some("+442079460958"));
+
+        data_repo
+            .add_phone(id, &"work:020 7946 0000".parse()?, false)
+            .await?;
+
+        let in_us = Repo::new(data_repo.database.as_ref().clone());
+        let rejected = in_us
+            .add_phone(id, &"home:020 7946 0001".parse()?, false)
+            .await;
+        assert!(matches!(rejected, Err(Error::InvalidPhone(_))));
+
+        Ok(())
+    }
 
+    #[tokio::test]
+    async fn should_keep_labelled_emails_and_phones_in_line_with_the_contact() -> anyhow::Result<()>
+    {
+        let pool = setup_in_memory_db().await;
         let data_repo = Repo::new(pool);
 
-        let example_csv = "tests/fixtures/birthday.csv";
+        let id = data_repo
+            .save_optional_contact(models::OptionalContact {
+                first_name: Some("Ada".to_string()),
+                email: Some("ada@home.com".to_string()),
+                phones: vec![models::Labeled::new(models::Label::Mobile, "201-555-0123")],
+                ..models::OptionalContact::default()
+            })
+            .await?;
 
-        data_repo.import_contacts_by_csv(example_csv).await?;
+        let ada = data_repo.get_contact_by_id(id).await?.contact;
+        assert_eq!(ada.phone_number, "201-555-0123");
+        assert_eq!(ada.phone_e164.as_deref(), Some("+12015550123"));
 
-        let contacts = data_repo.get_all_contacts().await?;
+        data_repo
+            .add_email(id, &"work:ada@work.com".parse()?, true)
+            .await?;
+        data_repo
+            .add_email(id, &"home:ADA@home.com".parse()?, false)
+            .await?;
 
-        let aldous_huxley_birthday = contacts.first().unwrap().contact.birthday;
+        let emails = data_repo.get_emails(id).await?;
+        let emails: Vec<(models::Label, &str, bool)> = emails
+            .iter()
+            .map(|email| (email.label, email.email.as_str(), email.is_preferred))
+            .collect();
+        assert_eq!(
+            emails,
+            vec![
+                (models::Label::Work, "ada@work.com", true),
+                (models::Label::Home, "ada@home.com", false),
+            ]
+        );
+        assert_eq!(
+            data_repo.get_contact_by_id(id).await?.contact.email,
+            "ada@work.com"
+        );
 
+        assert!(data_repo.remove_email(id, "ada@work.com").await?);
+        assert!(!data_repo.remove_email(id, "ada@work.com").await?);
         assert_eq!(
-            aldous_huxley_birthday,
-            chrono::NaiveDate::from_ymd_opt(1894, 7, 26).expect("Aldous's Birthday")
+            data_repo.get_contact_by_id(id).await?.contact.email,
+            "ada@home.com"
         );
 
+        assert!(data_repo.remove_phone(id, "201-555-0123").await?);
+        let ada = data_repo.get_contact_by_id(id).await?.contact;
+        assert_eq!(ada.phone_number, "");
+        assert_eq!(ada.phone_e164, None);
+        assert!(data_repo.get_phones(id).await?.is_empty());
+
+        let error = data_repo
+            .add_phone(id, &"work:555-0123".parse()?, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, Error::InvalidPhone(_)));
+
         Ok(())
     }
 
     #[tokio::test]
-    async fn should_save_csv_with_threes_row_and_birthday() -> anyhow::Result<()> {
+    async fn should_move_emails_and_phones_when_merging() -> anyhow::Result<()> {
         let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let keep = data_repo
+            .save_optional_contact(models::OptionalContact {
+                first_name: Some("Ada".to_string()),
+                email: Some("ada@example.com".to_string()),
+                ..models::OptionalContact::default()
+            })
+            .await?;
+        let drop = data_repo
+            .save_optional_contact(models::OptionalContact {
+                first_name: Some("Ada".to_string()),
+                email: Some("ada@work.com".to_string()),
+                phone_number: Some("201-555-0123".to_string()),
+                ..models::OptionalContact::default()
+            })
+            .await?;
+
+        data_repo.merge_contacts(keep, drop, &[]).await?;
+
+        let emails = data_repo.get_emails(keep).await?;
+        let phones = data_repo.get_phones(keep).await?;
+        assert_eq!(
+            emails
+                .iter()
+                .map(|email| (email.email.as_str(), email.is_preferred))
+                .collect::<Vec<_>>(),
+            vec![("ada@example.com", true), ("ada@work.com", false)]
+        );
+        assert_eq!(phones.len(), 1);
+        assert!(phones.first().unwrap().is_preferred);
 
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_add_edit_and_remove_addresses() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
         let data_repo = Repo::new(pool);
 
-        let example_csv = "tests/fixtures/three_birthdays.csv";
+        let home = models::PostalAddress {
+            label: models::Label::Home,
+            street: "12 St James's Square".to_string(),
+            locality: "London".to_string(),
+            ..models::PostalAddress::default()
+        };
+        let contact = models::OptionalContact {
+            first_name: Some("Ada".to_string()),
+            addresses: vec![home.clone()],
+            ..models::OptionalContact::default()
+        };
+        let id = data_repo.save_optional_contact(contact.clone()).await?;
+
+        let mut transaction = data_repo.database.begin().await?;
+        update_from_row(&mut transaction, id, &contact, &data_repo.region).await?;
+        transaction.commit().await?;
+
+        let addresses = data_repo.get_addresses(id).await?;
+        assert_eq!(addresses.len(), 1);
+        assert_eq!(addresses.first().unwrap().address, home);
+
+        let work_id = data_repo
+            .add_address(
+                id,
+                &models::PostalAddress {
+                    street: "1 Analytical Way".to_string(),
+                    ..models::PostalAddress::default()
+                },
+            )
+            .await?;
+        let mut work = data_repo.get_address(work_id).await?.address;
+        work.label = models::Label::Work;
+        data_repo.update_address(work_id, &work).await?;
+
+        assert_eq!(data_repo.get_address(work_id).await?.address, work);
+        assert_eq!(data_repo.get_all_addresses().await?.len(), 2);
+
+        assert!(data_repo.remove_address(work_id).await?);
+        assert!(!data_repo.remove_address(work_id).await?);
+        assert!(matches!(
+            data_repo.get_address(work_id).await,
+            Err(Error::AddressNotFound(_))
+        ));
+        assert!(matches!(
+            data_repo
+                .add_address(id, &models::PostalAddress::default())
+                .await,
+            Err(Error::EmptyUpdate)
+        ));
+
+        let mobile = models::PostalAddress {
+            label: models::Label::Mobile,
+            ..home.clone()
+        };
+        assert!(matches!(
+            data_repo.add_address(id, &mobile).await,
+            Err(Error::InvalidAddressLabel(label)) if label == "mobile"
+        ));
+        assert!(matches!(
+            data_repo.update_address(work_id, &mobile).await,
+            Err(Error::InvalidAddressLabel(_))
+        ));
+        assert!(
+            sqlx::query("UPDATE contact_addresses SET label = 'mobile' WHERE contact_id = $1")
+                .bind(id)
+                .execute(&*data_repo.database)
+                .await
+                .is_err()
+        );
 
-        data_repo.import_contacts_by_csv(example_csv).await?;
+        Ok(())
+    }
 
-        let contacts = data_repo.get_all_contacts().await?;
+    #[tokio::test]
+    async fn should_keep_the_history_of_notes_and_search_them() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
 
-        let aldous_huxley_birthday = contacts.first().unwrap().contact.birthday;
+        let id = data_repo
+            .save_optional_contact(models::OptionalContact {
+                first_name: Some("Ada".to_string()),
+                ..models::OptionalContact::default()
+            })
+            .await?;
+
+        let note_id = data_repo.add_note(id, "  Allergic to peanuts\n").await?;
+        data_repo
+            .update_note(note_id, "Allergic to **peanuts** and shellfish")
+            .await?;
+        data_repo
+            .update_note(note_id, "Allergic to **peanuts** and shellfish")
+            .await?;
 
+        let notes = data_repo.get_notes(id).await?;
+        assert_eq!(notes.len(), 1);
         assert_eq!(
-            aldous_huxley_birthday,
-            chrono::NaiveDate::from_ymd_opt(1894, 7, 26).expect("Valid date")
+            notes.first().unwrap().body,
+            "Allergic to **peanuts** and shellfish"
         );
+        assert!(notes.first().unwrap().is_edited());
 
-        let cs_lewis_birthday = contacts.get(2).unwrap().contact.birthday;
+        let revisions = data_repo.get_note_revisions(note_id).await?;
+        assert_eq!(
+            revisions
+                .iter()
+                .map(|revision| revision.body.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Allergic to peanuts"]
+        );
+
+        let hits = data_repo.search("shellfish").await?;
+        assert_eq!(hits.len(), 1);
+
+        assert!(matches!(
+            data_repo.add_note(id, " \n").await,
+            Err(Error::EmptyNote)
+        ));
+        assert!(matches!(
+            data_repo.add_note(id.saturating_add(1), "Hello").await,
+            Err(Error::NotFound(_))
+        ));
+
+        assert!(data_repo.remove_note(note_id).await?);
+        assert!(data_repo.search("shellfish").await?.is_empty());
+        assert!(matches!(
+            data_repo.update_note(note_id, "Gone").await,
+            Err(Error::NoteNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_match_phone_numbers_on_digits() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        save_searchable_contacts(&data_repo).await?;
+
+        let hits = data_repo.search("303-5550").await?;
+
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits.first().unwrap().contact.contact.first_name, "Dan");
+        assert_eq!(hits.first().unwrap().snippet, "[3035550167]");
 
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_search_updated_fields_and_interaction_notes() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        save_searchable_contacts(&data_repo).await?;
+
+        let update = models::ContactBuilder::builder()
+            .id(2)
+            .email("danielle@acme.example".to_string())
+            .build()?;
+        data_repo.update_contact(update).await?;
+        data_repo
+            .log_interaction(3, "coffee", "Met at the conference", Utc::now())
+            .await?;
+
+        assert_eq!(data_repo.search("acme").await?.len(), 1);
+
+        let hits = data_repo.search("dan conference").await?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits.first().unwrap().contact.id, 3);
+        assert!(hits.first().unwrap().snippet.contains('['));
+
+        let hits = data_repo.search("conference").await?;
+        assert_eq!(hits.first().unwrap().snippet, "Met at the [conference]");
+
+        data_repo.delete_contact_by_id(3).await?;
+        assert!(data_repo.search("conference").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_search_secondary_emails_and_phone_numbers() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        let id = save_named_contact(&data_repo, "Ada").await;
+
+        data_repo
+            .add_email(id, &"home:ada@home.example".parse()?, true)
+            .await?;
+        data_repo
+            .add_email(id, &"work:lovelace@engine.example".parse()?, false)
+            .await?;
+        data_repo
+            .add_phone(id, &"work:(201) 555-0188".parse()?, false)
+            .await?;
+
+        let found = |hits: Vec<models::SearchHit>| -> Vec<i64> {
+            hits.iter().map(|hit| hit.contact.id).collect()
+        };
+        assert_eq!(found(data_repo.search("engine").await?), [id]);
+        assert_eq!(found(data_repo.search("201-555-0188").await?), [id]);
+        assert_eq!(found(data_repo.search("ada@home").await?), [id]);
+
+        data_repo
+            .remove_email(id, "lovelace@engine.example")
+            .await?;
+        assert!(data_repo.search("engine").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_merge_tags_interactions_and_record_the_dropped_id() -> anyhow::Result<()> {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        let keep = save_named_contact(&data_repo, "Lewis").await;
+        let drop = save_named_contact(&data_repo, "Lewsi").await;
+
+        data_repo.add_tag(keep, "writer").await?;
+        data_repo.add_tag(drop, "writer").await?;
+        data_repo.add_tag(drop, "oxford").await?;
+        data_repo
+            .log_interaction(drop, "letter", "About the walrus", Utc::now())
+            .await?;
+
+        let merged = data_repo
+            .merge_contacts(keep, drop, &[models::ContactField::FirstName])
+            .await?;
+
+        assert_eq!(merged.id, keep);
+        assert_eq!(merged.contact.first_name, "Lewsi");
+        assert_eq!(data_repo.get_tags(keep).await?, ["oxford", "writer"]);
+        assert_eq!(data_repo.get_interactions(keep).await?.len(), 1);
+        assert_eq!(data_repo.get_merged_ids(keep).await?, [drop]);
         assert_eq!(
-            cs_lewis_birthday,
-            chrono::NaiveDate::from_ymd_opt(1898, 11, 29).expect("Valid date")
+            data_repo
+                .search("walrus")
+                .await?
+                .first()
+                .map(|hit| hit.contact.id),
+            Some(keep)
         );
+        assert!(matches!(
+            data_repo.get_contact_by_id(drop).await,
+            Err(Error::NotFound(id)) if id == drop
+        ));
 
         Ok(())
     }
 
+    #[tokio::test]
+    async fn should_refuse_to_merge_a_contact_into_itself() {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+        let id = save_named_contact(&data_repo, "Lewis").await;
+
+        assert!(matches!(
+            data_repo.merge_contacts(id, id, &[]).await,
+            Err(Error::SelfMerge(self_id)) if self_id == id
+        ));
+        assert!(matches!(
+            data_repo.merge_contacts(id, 999, &[]).await,
+            Err(Error::NotFound(999))
+        ));
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_logging_for_nonexistent_id() {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let result = data_repo.log_interaction(999, "call", "", Utc::now()).await;
+
+        assert!(matches!(result, Err(Error::NotFound(999))));
+    }
+
     #[tokio::test]
     async fn should_return_error_when_deleting_nonexistent_id() {
         let pool = setup_in_memory_db().await;
@@ -557,6 +3060,30 @@ mod tests {
         let nonexistent_id = 999;
         let result = data_repo.delete_contact_by_id(nonexistent_id).await;
 
-        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::NotFound(999))));
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_getting_nonexistent_id() {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let result = data_repo.get_contact_by_id(999).await;
+
+        assert!(matches!(result, Err(Error::NotFound(999))));
+    }
+
+    #[tokio::test]
+    async fn should_return_not_found_when_updating_nonexistent_id() {
+        let pool = setup_in_memory_db().await;
+        let data_repo = Repo::new(pool);
+
+        let edits =
+            models::ContactBuilder::new(999, Some("Ada".to_string()), None, None, None, None, None)
+                .expect("Contact with only a first name");
+
+        let result = data_repo.update_contact(edits).await;
+
+        assert!(matches!(result, Err(Error::NotFound(999))));
     }
 }
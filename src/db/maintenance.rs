@@ -0,0 +1,710 @@
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Datelike, NaiveDateTime, Utc};
+use sqlx::{migrate::Migrator, sqlite::SqliteConnectOptions, SqlitePool};
+
+use super::setup;
+use crate::Error;
+
+/// The migrations this version of `nbd` knows, embedded at compile time
+pub(crate) static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Folder next to a contact book that its backups are kept in
+pub const BACKUPS_DIR: &str = "backups";
+
+/// Kind of the backups `nbd-cli backup` takes, the only ones pruned
+pub const SCHEDULED_BACKUP: &str = "backup";
+
+/// Days of which `prune_backups` keeps the newest scheduled backup
+pub const KEEP_DAILY: usize = 7;
+
+/// ISO weeks of which `prune_backups` keeps the newest scheduled backup
+pub const KEEP_WEEKLY: usize = 4;
+
+/// Format of the time a backup was taken in its file name
+const BACKUP_TIMESTAMP: &str = "%Y%m%d-%H%M%S";
+
+/// A migration and whether the contact book has it yet
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MigrationStatus {
+    pub version: i64,
+    pub description: String,
+    /// When it was applied, or `None` while it is pending
+    pub installed_on: Option<DateTime<Utc>>,
+}
+
+impl MigrationStatus {
+    #[must_use]
+    pub const fn is_pending(&self) -> bool {
+        self.installed_on.is_none()
+    }
+}
+
+/// What `migrate` did: the versions it applied, and the backup it took
+/// first when there were any
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Migrated {
+    pub applied: Vec<i64>,
+    pub backup: Option<PathBuf>,
+}
+
+/// A backup of a contact book and when it was taken
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub taken_at: DateTime<Utc>,
+}
+
+/// What `restore` did: the schema version of the restored backup, the
+/// backup of the replaced book, and the migrations the restored book
+/// needed to catch up
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Restored {
+    pub version: i64,
+    pub replaced: Option<PathBuf>,
+    pub migrated: Migrated,
+}
+
+/// Something `check` found wrong with a contact book
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Problem {
+    /// `PRAGMA integrity_check` reported damage to the file
+    Damaged(String),
+    /// A row refers to a row of `parent` that no longer exists
+    MissingParent {
+        table: String,
+        rowid: Option<i64>,
+        parent: String,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Damaged(message) => write!(formatter, "The database file is damaged: {message}"),
+            Self::MissingParent {
+                table,
+                rowid: Some(rowid),
+                parent,
+            } => write!(
+                formatter,
+                "Row {rowid} of {table} belongs to a row of {parent} that no longer exists"
+            ),
+            Self::MissingParent {
+                table,
+                rowid: None,
+                parent,
+            } => write!(
+                formatter,
+                "A row of {table} belongs to a row of {parent} that no longer exists"
+            ),
+        }
+    }
+}
+
+/// Every migration this version knows, oldest first, with when it was
+/// applied. Migrations the book has but this version does not know are
+/// listed too, so a book from a newer version is easy to spot.
+///
+/// # Errors
+///
+/// Will return sqlite errors
+pub async fn migration_status(pool: &SqlitePool) -> crate::Result<Vec<MigrationStatus>> {
+    let applied = applied_migrations(pool).await?;
+
+    let mut statuses: Vec<MigrationStatus> = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            installed_on: applied
+                .iter()
+                .find(|status| status.version == migration.version)
+                .and_then(|status| status.installed_on),
+        })
+        .collect();
+
+    for status in applied {
+        if !statuses.iter().any(|known| known.version == status.version) {
+            statuses.push(status);
+        }
+    }
+    statuses.sort_by_key(|status| status.version);
+
+    Ok(statuses)
+}
+
+/// Applies the migrations the book at `db_path` is missing, after copying
+/// it into [`BACKUPS_DIR`] beside it. A book without pending migrations is
+/// left alone and not backed up.
+///
+/// # Errors
+///
+/// Will return an error if the backup cannot be written, or sqlite errors
+/// when a migration fails
+pub async fn migrate(pool: &SqlitePool, db_path: &Path) -> crate::Result<Migrated> {
+    let applied: Vec<i64> = migration_status(pool)
+        .await?
+        .into_iter()
+        .filter(MigrationStatus::is_pending)
+        .map(|status| status.version)
+        .collect();
+
+    if applied.is_empty() {
+        return Ok(Migrated::default());
+    }
+
+    let backup = backup(pool, db_path, "pre-migrate").await?;
+    MIGRATOR.run(pool).await?;
+
+    Ok(Migrated {
+        applied,
+        backup: Some(backup),
+    })
+}
+
+/// Copies the book at `db_path` into [`BACKUPS_DIR`] beside it, as
+/// `{name}-{kind}-{timestamp}.db`, and returns the path of the copy.
+///
+/// # Errors
+///
+/// Will return an error if the folder cannot be created, or sqlite errors
+/// when the copy cannot be written
+pub async fn backup(pool: &SqlitePool, db_path: &Path, kind: &str) -> crate::Result<PathBuf> {
+    let dir = backups_dir(db_path);
+    std::fs::create_dir_all(&dir)?;
+
+    let stem = db_path
+        .file_stem()
+        .map_or_else(|| "contacts".into(), |stem| stem.to_string_lossy());
+    let timestamp = Utc::now().format(BACKUP_TIMESTAMP);
+
+    let mut path = dir.join(format!("{stem}-{kind}-{timestamp}.db"));
+    let mut copy = 1_u32;
+    while path.exists() {
+        path = dir.join(format!("{stem}-{kind}-{timestamp}-{copy}.db"));
+        copy = copy.saturating_add(1);
+    }
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy().into_owned())
+        .execute(pool)
+        .await?;
+
+    Ok(path)
+}
+
+/// The backups of `kind` of the book at `db_path`, newest first. Files
+/// whose names do not read as such a backup are left out.
+///
+/// # Errors
+///
+/// Will return an error if the backups folder cannot be read
+pub fn list_backups(db_path: &Path, kind: &str) -> crate::Result<Vec<Backup>> {
+    let dir = backups_dir(db_path);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let stem = db_path.file_stem().unwrap_or_default().to_string_lossy();
+    let prefix = format!("{stem}-{kind}-");
+
+    let mut backups = Vec::new();
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        let taken_at = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .filter(|_| path.extension().is_some_and(|extension| extension == "db"))
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|rest| rest.get(..15))
+            .and_then(|timestamp| NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP).ok());
+
+        if let Some(taken_at) = taken_at {
+            backups.push(Backup {
+                path,
+                taken_at: taken_at.and_utc(),
+            });
+        }
+    }
+    backups.sort_by(|a, b| {
+        b.taken_at
+            .cmp(&a.taken_at)
+            .then_with(|| b.path.cmp(&a.path))
+    });
+
+    Ok(backups)
+}
+
+/// The backups of `backups`, newest first, that fall outside the retention
+/// rules.
+///
+/// The newest backup of each of the `keep_daily` latest days that have one
+/// is kept, and likewise for the `keep_weekly` latest ISO weeks.
+#[must_use]
+pub fn backups_to_prune(backups: &[Backup], keep_daily: usize, keep_weekly: usize) -> Vec<Backup> {
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    let mut pruned = Vec::new();
+
+    for backup in backups {
+        let day = backup.taken_at.date_naive();
+        let week = day.iso_week();
+
+        let kept_daily = days.len() < keep_daily && days.insert(day);
+        let kept_weekly = weeks.len() < keep_weekly && weeks.insert((week.year(), week.week()));
+
+        if !kept_daily && !kept_weekly {
+            pruned.push(backup.clone());
+        }
+    }
+
+    pruned
+}
+
+/// Deletes the scheduled backups of the book at `db_path` that
+/// `backups_to_prune` lets go, returning them.
+///
+/// # Errors
+///
+/// Will return an error if the backups cannot be listed or deleted
+pub fn prune_backups(
+    db_path: &Path,
+    keep_daily: usize,
+    keep_weekly: usize,
+) -> crate::Result<Vec<Backup>> {
+    let pruned = backups_to_prune(
+        &list_backups(db_path, SCHEDULED_BACKUP)?,
+        keep_daily,
+        keep_weekly,
+    );
+
+    for backup in &pruned {
+        std::fs::remove_file(&backup.path)?;
+    }
+
+    Ok(pruned)
+}
+
+/// Replaces the book at `db_path` with the copy at `backup_path`.
+///
+/// The copy must be a healthy contact book this version can read. The
+/// replaced book is backed up first, and the restored one is migrated when
+/// it comes from an older version.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - The backup is missing, damaged or no contact book, as
+///   `Error::InvalidFile`
+/// - Its schema is newer than this version knows, as `Error::InvalidFile`
+/// - A file cannot be copied, or sqlite errors while backing up or
+///   migrating
+pub async fn restore(db_path: &Path, backup_path: &Path) -> crate::Result<Restored> {
+    let invalid = |reason: &str| {
+        Error::InvalidFile(format!(
+            "Cannot restore {}: {reason}",
+            backup_path.display()
+        ))
+    };
+
+    if !backup_path.is_file() {
+        return Err(invalid("there is no such file"));
+    }
+
+    let backup = SqlitePool::connect_with(
+        SqliteConnectOptions::new()
+            .filename(backup_path)
+            .read_only(true),
+    )
+    .await?;
+    let version = schema_version(&backup)
+        .await
+        .ok()
+        .flatten()
+        .ok_or_else(|| invalid("it is not a contact book"))?;
+    let latest = MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or_default();
+    if version > latest {
+        return Err(invalid(&format!(
+            "its schema version {version} is newer than {latest}, the latest this version of nbd knows"
+        )));
+    }
+    if let Some(problem) = check(&backup).await?.first() {
+        return Err(invalid(&problem.to_string()));
+    }
+    backup.close().await;
+
+    let replaced = if db_path.exists() {
+        let current = setup::connect(db_path).await?;
+        let replaced = self::backup(&current, db_path, "pre-restore").await?;
+        current.close().await;
+        Some(replaced)
+    } else {
+        if let Some(parent) = db_path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        None
+    };
+
+    let mut staging = db_path.as_os_str().to_owned();
+    staging.push("-restoring");
+    let staging = PathBuf::from(staging);
+    std::fs::copy(backup_path, &staging)?;
+    for sidecar in setup::sidecars(db_path) {
+        if sidecar.exists() {
+            std::fs::remove_file(sidecar)?;
+        }
+    }
+    std::fs::rename(&staging, db_path)?;
+
+    let restored = setup::connect(db_path).await?;
+    let migrated = migrate(&restored, db_path).await?;
+    restored.close().await;
+
+    Ok(Restored {
+        version,
+        replaced,
+        migrated,
+    })
+}
+
+/// The version of the newest migration applied to a book, or `None` when it
+/// has none.
+async fn schema_version(pool: &SqlitePool) -> crate::Result<Option<i64>> {
+    Ok(applied_migrations(pool)
+        .await?
+        .iter()
+        .map(|status| status.version)
+        .max())
+}
+
+/// The folder backups of the book at `db_path` go in.
+#[must_use]
+pub fn backups_dir(db_path: &Path) -> PathBuf {
+    db_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(BACKUPS_DIR)
+}
+
+/// Runs `SQLite`'s integrity and foreign key checks, returning what they
+/// found. An empty list means the book is healthy.
+///
+/// # Errors
+///
+/// Will return sqlite errors
+pub async fn check(pool: &SqlitePool) -> crate::Result<Vec<Problem>> {
+    let mut problems: Vec<Problem> = sqlx::query_scalar::<_, String>("PRAGMA integrity_check")
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .filter(|message| message != "ok")
+        .map(Problem::Damaged)
+        .collect();
+
+    let orphans: Vec<(String, Option<i64>, String, i64)> =
+        sqlx::query_as("PRAGMA foreign_key_check")
+            .fetch_all(pool)
+            .await?;
+
+    problems.extend(
+        orphans
+            .into_iter()
+            .map(|(table, rowid, parent, _)| Problem::MissingParent {
+                table,
+                rowid,
+                parent,
+            }),
+    );
+
+    Ok(problems)
+}
+
+async fn applied_migrations(pool: &SqlitePool) -> crate::Result<Vec<MigrationStatus>> {
+    let has_table: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations')",
+    )
+    .fetch_one(pool)
+    .await?;
+
+    if !has_table {
+        return Ok(Vec::new());
+    }
+
+    let rows: Vec<(i64, String, NaiveDateTime)> = sqlx::query_as(
+        "SELECT version, description, installed_on FROM _sqlx_migrations WHERE success = 1",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(version, description, installed_on)| MigrationStatus {
+            version,
+            description,
+            installed_on: Some(installed_on.and_utc()),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::setup;
+
+    /// A book with only the first `count` migrations applied
+    async fn old_book(db_path: &Path, count: usize) -> anyhow::Result<SqlitePool> {
+        let migrations = tempfile::TempDir::new()?;
+        let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("migrations");
+        let mut files: Vec<PathBuf> = std::fs::read_dir(&source)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<_, _>>()?;
+        files.sort();
+        for file in files.iter().take(count) {
+            std::fs::copy(
+                file,
+                migrations.path().join(file.file_name().unwrap_or_default()),
+            )?;
+        }
+
+        let pool = SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(db_path)
+                .create_if_missing(true),
+        )
+        .await?;
+        Migrator::new(migrations.path()).await?.run(&pool).await?;
+
+        Ok(pool)
+    }
+
+    #[tokio::test]
+    async fn should_keep_only_legacy_frequencies_that_read_as_cadences() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        let before_normalizing = MIGRATOR
+            .iter()
+            .position(|migration| migration.description == "normalize frequency")
+            .unwrap_or_default();
+        let pool = old_book(&db_path, before_normalizing).await?;
+        for frequency in [
+            "'Every  2 Weeks'",
+            "30",
+            "'sometimes'",
+            "'2W'",
+            "''",
+            "'every 0 days'",
+        ] {
+            sqlx::query(&format!(
+                "INSERT INTO contacts (first_name, last_name, display_name, phone_number, email, created_at, updated_at, frequency) VALUES ('Ada', '', 'Ada', '', '', '2026-01-01T00:00:00Z', '2026-01-01T00:00:00Z', {frequency})"
+            ))
+            .execute(&pool)
+            .await?;
+        }
+        pool.close().await;
+
+        let repo = crate::db::Repo::new(setup::open(&db_path).await?);
+        let frequencies: Vec<Option<String>> = crate::db::ContactRepo::get_all_contacts(&repo)
+            .await?
+            .into_iter()
+            .map(|contact| contact.contact.frequency.map(|cadence| cadence.to_string()))
+            .collect();
+
+        assert_eq!(
+            frequencies,
+            vec![
+                Some("every 2 weeks".to_string()),
+                Some("every 30 days".to_string()),
+                None,
+                Some("every 2 weeks".to_string()),
+                None,
+                None,
+            ]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_back_up_before_applying_pending_migrations() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        let pool = old_book(&db_path, 6).await?;
+
+        let statuses = migration_status(&pool).await?;
+        let pending = statuses.iter().filter(|status| status.is_pending()).count();
+        assert_eq!(statuses.len(), MIGRATOR.iter().count());
+        assert_eq!(pending, statuses.len() - 6);
+
+        let migrated = migrate(&pool, &db_path).await?;
+
+        assert_eq!(migrated.applied.len(), pending);
+        let backup = migrated.backup.expect("a backup before migrating");
+        assert!(backup.starts_with(temp.path().join(BACKUPS_DIR)));
+        assert!(backup.to_string_lossy().contains("contacts-pre-migrate-"));
+
+        let backed_up = setup::connect(&backup).await?;
+        assert_eq!(
+            migration_status(&backed_up)
+                .await?
+                .iter()
+                .filter(|status| status.is_pending())
+                .count(),
+            pending
+        );
+
+        assert!(migration_status(&pool)
+            .await?
+            .iter()
+            .all(|status| !status.is_pending()));
+        assert_eq!(migrate(&pool, &db_path).await?, Migrated::default());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_report_rows_whose_parent_is_gone() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        setup::initialize_at(&db_path).await?;
+        let pool = setup::open(&db_path).await?;
+
+        assert_eq!(check(&pool).await?, Vec::new());
+
+        let mut connection = pool.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query(
+            "INSERT INTO notes (contact_id, body, created_at, updated_at) VALUES (7, 'Hi', '', '')",
+        )
+        .execute(&mut *connection)
+        .await?;
+        drop(connection);
+
+        let problems = check(&pool).await?;
+
+        assert_eq!(
+            problems,
+            vec![Problem::MissingParent {
+                table: "notes".to_string(),
+                rowid: Some(1),
+                parent: "contacts".to_string(),
+            }]
+        );
+        assert_eq!(
+            problems[0].to_string(),
+            "Row 1 of notes belongs to a row of contacts that no longer exists"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_the_newest_backup_of_recent_days_and_weeks() {
+        // Two backups a day, every day from 2026-08-01 to 2026-10-18
+        let start = NaiveDateTime::parse_from_str("20260801-090000", BACKUP_TIMESTAMP).unwrap();
+        let backups: Vec<Backup> = (0..79_i64)
+            .flat_map(|day| [day * 24 + 9, day * 24 + 21])
+            .rev()
+            .map(|hours| Backup {
+                path: PathBuf::from(format!("{hours}.db")),
+                taken_at: (start + chrono::Duration::hours(hours - 9)).and_utc(),
+            })
+            .collect();
+
+        let pruned = backups_to_prune(&backups, KEEP_DAILY, KEEP_WEEKLY);
+        let kept: Vec<String> = backups
+            .iter()
+            .filter(|backup| !pruned.contains(backup))
+            .map(|backup| backup.taken_at.format("%Y-%m-%d %H:%M").to_string())
+            .collect();
+
+        assert_eq!(
+            kept,
+            vec![
+                "2026-10-18 21:00",
+                "2026-10-17 21:00",
+                "2026-10-16 21:00",
+                "2026-10-15 21:00",
+                "2026-10-14 21:00",
+                "2026-10-13 21:00",
+                "2026-10-12 21:00",
+                "2026-10-11 21:00",
+                "2026-10-04 21:00",
+                "2026-09-27 21:00",
+            ]
+        );
+        assert_eq!(pruned.len(), backups.len() - kept.len());
+    }
+
+    #[tokio::test]
+    async fn should_restore_a_backup_after_saving_the_replaced_book() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        setup::initialize_at(&db_path).await?;
+        let pool = setup::open(&db_path).await?;
+        let saved = backup(&pool, &db_path, SCHEDULED_BACKUP).await?;
+        sqlx::query("INSERT INTO contacts (first_name, last_name, display_name, phone_number, email, created_at, updated_at) VALUES ('Ada', '', 'Ada', '', '', '', '')")
+            .execute(&pool)
+            .await?;
+        pool.close().await;
+
+        let restored = restore(&db_path, &saved).await?;
+
+        assert_eq!(
+            restored.version,
+            MIGRATOR.iter().map(|m| m.version).max().unwrap()
+        );
+        assert_eq!(restored.migrated, Migrated::default());
+        let replaced = setup::connect(&restored.replaced.unwrap()).await?;
+        let contacts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM contacts")
+            .fetch_one(&replaced)
+            .await?;
+        assert_eq!(contacts, 1);
+        let current = setup::connect(&db_path).await?;
+        let contacts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM contacts")
+            .fetch_one(&current)
+            .await?;
+        assert_eq!(contacts, 0);
+        assert_eq!(list_backups(&db_path, SCHEDULED_BACKUP)?.len(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_refuse_backups_it_cannot_read() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        let newer = temp.path().join("newer.db");
+        setup::initialize_at(&newer).await?;
+        let pool = setup::connect(&newer).await?;
+        sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (99990101000000, 'future', 1, x'00', 0)")
+            .execute(&pool)
+            .await?;
+        pool.close().await;
+        let text = temp.path().join("notes.txt");
+        std::fs::write(&text, "not a database")?;
+
+        for (backup, reason) in [
+            (newer, "is newer than"),
+            (text, "not a contact book"),
+            (temp.path().join("missing.db"), "no such file"),
+        ] {
+            assert!(matches!(
+                restore(&db_path, &backup).await,
+                Err(Error::InvalidFile(message)) if message.contains(reason)
+            ));
+        }
+        assert!(!db_path.exists());
+        Ok(())
+    }
+}
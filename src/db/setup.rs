@@ -1,44 +1,186 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::Sqlite;
 use sqlx::{migrate::MigrateDatabase, SqlitePool};
 
-use crate::utils::{build_database_path, build_database_url, ensure_config_dir};
+use super::contact_repo;
+use super::maintenance::{self, MIGRATOR};
+use crate::utils::{
+    build_book_path, build_database_url, ensure_config_dir, is_already_initialized, DEFAULT_BOOK,
+};
+use crate::Error;
 
 /// # Errors
 ///
 /// Will return sqlite errors
-pub async fn create_database(url: &str) -> anyhow::Result<()> {
+pub async fn create_database(url: &str) -> crate::Result<()> {
     Sqlite::create_database(url).await?;
 
     let pool = SqlitePool::connect(url).await?;
-    sqlx::migrate!("./migrations").run(&pool).await?;
+    MIGRATOR.run(&pool).await?;
 
     Ok(())
 }
 
+/// Files `SQLite` may keep next to a database, named by the suffix added to
+/// its path
+const SIDECAR_SUFFIXES: [&str; 3] = ["-wal", "-shm", "-journal"];
+
 /// Bootstraps a fresh contact book on disk.
 ///
-/// Ensures the config directory exists, then creates the database
-/// file inside it. This is the imperative shell that composes the pure
-/// path helpers with the database-creating I/O.
+/// Ensures the config directory exists, then creates the database of the
+/// book named `book` inside it. This is the imperative shell that composes
+/// the pure path helpers with the database-creating I/O.
 ///
 /// # Errors
 ///
-/// Returns an error if the config directory cannot be created or the
-/// database file cannot be created.
-pub async fn initialize(config_dir: &Path) -> anyhow::Result<()> {
-    ensure_config_dir(config_dir)?;
+/// Returns an error if the book name is invalid, or the config directory
+/// or the database file cannot be created.
+pub async fn initialize(config_dir: &Path, book: &str) -> crate::Result<()> {
+    initialize_at(&build_book_path(config_dir, book)?).await
+}
 
-    let db_path = build_database_path(config_dir);
+/// Bootstraps a fresh contact book at `db_path`, creating the directory it
+/// goes in first.
+///
+/// # Errors
+///
+/// Returns an error if the directory cannot be created or the database
+/// file cannot be created.
+pub async fn initialize_at(db_path: &Path) -> crate::Result<()> {
+    if let Some(parent) = db_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        ensure_config_dir(parent)?;
+    }
 
-    let db_url = build_database_url(&db_path);
+    let db_url = build_database_url(db_path);
 
     create_database(&db_url).await?;
 
     Ok(())
 }
 
+/// Opens the contact book at `db_path`, first applying any migrations it
+/// is missing so books made by older versions keep working.
+///
+/// The book is backed up before anything is applied; see
+/// [`maintenance::migrate`]. Contacts with a cadence but no reminder then
+/// get one.
+///
+/// # Errors
+///
+/// Returns `Error::NotInitialized` when there is no database at `db_path`,
+/// and sqlite errors when it cannot be opened, backed up or migrated.
+pub async fn open(db_path: &Path) -> crate::Result<SqlitePool> {
+    let pool = connect(db_path).await?;
+    maintenance::migrate(&pool, db_path).await?;
+    contact_repo::fill_missing_reminders(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Opens the contact book at `db_path` as it is, without migrating it.
+///
+/// # Errors
+///
+/// Returns `Error::NotInitialized` when there is no database at `db_path`,
+/// and sqlite errors when it cannot be opened.
+pub async fn connect(db_path: &Path) -> crate::Result<SqlitePool> {
+    if !is_already_initialized(db_path) {
+        return Err(Error::NotInitialized(db_path.to_path_buf()));
+    }
+
+    Ok(SqlitePool::connect_with(SqliteConnectOptions::new().filename(db_path)).await?)
+}
+
+/// Renames the book `from` of `config_dir` to `to`, moving its database
+/// along with the files `SQLite` keeps beside it.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - Either name is invalid, or one of them is [`DEFAULT_BOOK`]
+/// - There is no book named `from`, as `Error::BookNotFound`
+/// - A book named `to` already exists
+/// - A file cannot be moved
+pub fn rename_book(config_dir: &Path, from: &str, to: &str) -> crate::Result<()> {
+    let from_path = named_book_path(config_dir, from)?;
+    let to_path = named_book_path(config_dir, to)?;
+
+    if !from_path.exists() {
+        return Err(Error::BookNotFound(from.to_owned()));
+    }
+    if to_path.exists() {
+        return Err(Error::InvalidBook {
+            name: to.to_owned(),
+            reason: "a book with that name already exists".to_owned(),
+        });
+    }
+
+    std::fs::rename(&from_path, &to_path)?;
+    for (from_sidecar, to_sidecar) in sidecars(&from_path).into_iter().zip(sidecars(&to_path)) {
+        if from_sidecar.exists() {
+            std::fs::rename(from_sidecar, to_sidecar)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Deletes the book `name` of `config_dir` and the files `SQLite` keeps
+/// beside it.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - The name is invalid or is [`DEFAULT_BOOK`]
+/// - There is no book named `name`, as `Error::BookNotFound`
+/// - A file cannot be deleted
+pub fn remove_book(config_dir: &Path, name: &str) -> crate::Result<()> {
+    let path = named_book_path(config_dir, name)?;
+
+    if !path.exists() {
+        return Err(Error::BookNotFound(name.to_owned()));
+    }
+
+    std::fs::remove_file(&path)?;
+    for sidecar in sidecars(&path) {
+        if sidecar.exists() {
+            std::fs::remove_file(sidecar)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The path of a book `rename_book` and `remove_book` may act on: any but
+/// [`DEFAULT_BOOK`], whose database is the one `contacts.db` of the config
+/// directory.
+fn named_book_path(config_dir: &Path, name: &str) -> crate::Result<PathBuf> {
+    if name == DEFAULT_BOOK {
+        return Err(Error::InvalidBook {
+            name: name.to_owned(),
+            reason: "the default book cannot be renamed or removed".to_owned(),
+        });
+    }
+
+    build_book_path(config_dir, name)
+}
+
+pub(super) fn sidecars(db_path: &Path) -> Vec<PathBuf> {
+    SIDECAR_SUFFIXES
+        .iter()
+        .map(|suffix| {
+            let mut path = db_path.as_os_str().to_owned();
+            path.push(suffix);
+            PathBuf::from(path)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -63,9 +205,81 @@ mod test {
         let db_path = config_dir.join("contacts.db");
         assert!(!db_path.exists());
 
-        initialize(&config_dir).await?;
+        initialize(&config_dir, DEFAULT_BOOK).await?;
 
         assert!(db_path.exists(), "expected database at {db_path:?}");
         Ok(())
     }
+
+    #[tokio::test]
+    async fn initialize_creates_named_books_in_the_books_folder() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let config_dir = temp.path().join("nbd");
+
+        initialize(&config_dir, "volunteers").await?;
+
+        assert!(config_dir.join("books/volunteers.db").exists());
+        assert!(!config_dir.join("contacts.db").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn rename_and_remove_books_with_their_sidecar_files() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let config_dir = temp.path();
+        initialize(config_dir, "volunteers").await?;
+        initialize(config_dir, "family").await?;
+        std::fs::write(config_dir.join("books/volunteers.db-wal"), "")?;
+
+        rename_book(config_dir, "volunteers", "helpers")?;
+
+        assert!(config_dir.join("books/helpers.db").exists());
+        assert!(config_dir.join("books/helpers.db-wal").exists());
+        assert!(!config_dir.join("books/volunteers.db").exists());
+        assert!(matches!(
+            rename_book(config_dir, "helpers", "family"),
+            Err(Error::InvalidBook { name, .. }) if name == "family"
+        ));
+        assert!(matches!(
+            rename_book(config_dir, "volunteers", "others"),
+            Err(Error::BookNotFound(name)) if name == "volunteers"
+        ));
+        assert!(matches!(
+            remove_book(config_dir, DEFAULT_BOOK),
+            Err(Error::InvalidBook { .. })
+        ));
+
+        remove_book(config_dir, "helpers")?;
+
+        assert!(!config_dir.join("books/helpers.db").exists());
+        assert!(!config_dir.join("books/helpers.db-wal").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn open_migrates_an_existing_database() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        Sqlite::create_database(&build_database_url(&db_path)).await?;
+
+        let pool = open(&db_path).await?;
+
+        let contacts: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM contacts")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(contacts, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn open_refuses_a_missing_database() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+
+        let result = open(&db_path).await;
+
+        assert!(matches!(result, Err(Error::NotInitialized(path)) if path == db_path));
+        assert!(!db_path.exists());
+        Ok(())
+    }
 }
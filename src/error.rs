@@ -0,0 +1,246 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// Errors returned by the `nbd` library.
+///
+/// Each variant is stable enough to match on, so tools embedding the library
+/// do not have to inspect message text.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No contact has the given ID
+    NotFound(i64),
+    /// No address has the given ID
+    AddressNotFound(i64),
+    /// No note has the given ID
+    NoteNotFound(i64),
+    /// There is no contact book at the given path yet
+    NotInitialized(PathBuf),
+    /// No book of the config directory has the given name
+    BookNotFound(String),
+    /// A book name holds characters other than letters, digits, `-` and
+    /// `_`, or names a book that cannot be used that way
+    InvalidBook {
+        name: String,
+        reason: String,
+    },
+    /// No contact's name is close to the given query
+    NoMatch(String),
+    /// Several contacts' names fit the query equally well; `candidates` holds
+    /// their IDs and display names
+    AmbiguousName {
+        query: String,
+        candidates: Vec<(i64, String)>,
+    },
+    /// The email address failed validation
+    InvalidEmail(String),
+    /// The phone number failed validation
+    InvalidPhone(String),
+    /// The date could not be parsed
+    InvalidDate(String),
+    /// The keep-in-touch cadence could not be parsed
+    InvalidCadence(String),
+    /// A contact cannot be merged into itself
+    SelfMerge(i64),
+    /// An email or phone label is not one of home, work, mobile or other
+    InvalidLabel(String),
+    /// An address label is not one of home, work or other
+    InvalidAddressLabel(String),
+    /// An update carried no fields to change
+    EmptyUpdate,
+    /// A note has no text, so it was not saved
+    EmptyNote,
+    /// An update was built without a contact ID
+    MissingId,
+    /// A contact field name is not a column of the `contacts` table
+    UnknownField(String),
+    /// A key of `config.toml` is not a setting
+    UnknownSetting(String),
+    /// A setting of `config.toml` holds a value it does not accept
+    InvalidSetting {
+        key: String,
+        reason: String,
+    },
+    /// A row of an imported file holds an invalid value; `line` is 1-based
+    /// and counts the header
+    CsvRow {
+        line: u64,
+        field: String,
+    },
+    /// Rows of an imported file hold invalid values, so nothing was imported
+    InvalidRows(Vec<RowError>),
+    /// An imported file is empty, malformed or of an unsupported format
+    InvalidFile(String),
+    Database(sqlx::Error),
+    Io(io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Why a row of an imported file was rejected; `line` is 1-based and counts
+/// the header of a CSV file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowError {
+    pub line: u64,
+    pub column: String,
+    pub reason: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "line {}, {}: {}",
+            self.line, self.column, self.reason
+        )
+    }
+}
+
+impl Error {
+    /// Wraps an I/O error from opening `path` so the message names the file.
+    pub(crate) fn open_file(path: &Path, error: &io::Error) -> Self {
+        Self::Io(io::Error::new(
+            error.kind(),
+            format!("Failed to open file: {}", path.display()),
+        ))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound(id) => write!(formatter, "That Contact ID does not exist: {id}"),
+            Self::AddressNotFound(id) => write!(formatter, "That address ID does not exist: {id}"),
+            Self::NoteNotFound(id) => write!(formatter, "That note ID does not exist: {id}"),
+            Self::NotInitialized(path) => write!(
+                formatter,
+                "There is no contact book at {}; run `nbd-cli init` first",
+                path.display()
+            ),
+            Self::BookNotFound(name) => write!(formatter, "There is no book named {name}"),
+            Self::InvalidBook { name, reason } => {
+                write!(formatter, "Cannot use the book {name}: {reason}")
+            }
+            Self::NoMatch(query) => write!(formatter, "No contact matches {query}"),
+            Self::AmbiguousName { query, candidates } => {
+                write!(formatter, "{query} matches several contacts:")?;
+                for (id, display_name) in candidates {
+                    write!(formatter, "\n  {id}\t{display_name}")?;
+                }
+                formatter.write_str("\nUse one of these IDs instead")
+            }
+            Self::InvalidEmail(email) => write!(formatter, "{email} is invalid"),
+            Self::InvalidPhone(phone_number) => write!(formatter, "{phone_number} is invalid"),
+            Self::InvalidDate(date) => write!(formatter, "{date} is invalid"),
+            Self::InvalidCadence(cadence) => write!(
+                formatter,
+                "{cadence} is not a valid cadence; try weekly, monthly, quarterly or \"every 2 weeks\""
+            ),
+            Self::SelfMerge(id) => write!(formatter, "Cannot merge contact {id} into itself"),
+            Self::InvalidLabel(label) => write!(
+                formatter,
+                "{label} is not a valid label; use home, work, mobile or other"
+            ),
+            Self::InvalidAddressLabel(label) => write!(
+                formatter,
+                "{label} is not a valid address label; use home, work or other"
+            ),
+            Self::EmptyUpdate => formatter.write_str("No fields provided for update"),
+            Self::EmptyNote => formatter.write_str("The note is empty, so nothing was saved"),
+            Self::MissingId => formatter.write_str("ID is required"),
+            Self::UnknownField(name) => write!(formatter, "Unknown column: {name}"),
+            Self::UnknownSetting(key) => {
+                let keys = crate::utils::Setting::ALL.map(crate::utils::Setting::as_str);
+                write!(
+                    formatter,
+                    "Unknown setting: {key}; use one of {}",
+                    keys.join(", ")
+                )
+            }
+            Self::InvalidSetting { key, reason } => write!(formatter, "Invalid {key}: {reason}"),
+            Self::CsvRow { line, field } => write!(formatter, "Invalid {field} on line {line}"),
+            Self::InvalidRows(rows) => {
+                formatter.write_str("Nothing was imported because of invalid rows:")?;
+                for row in rows {
+                    write!(formatter, "\n  {row}")?;
+                }
+                Ok(())
+            }
+            Self::InvalidFile(reason) => formatter.write_str(reason),
+            Self::Database(error) => write!(formatter, "Database error: {error}"),
+            Self::Io(error) => write!(formatter, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Database(error) => Some(error),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for Error {
+    fn from(error: sqlx::Error) -> Self {
+        Self::Database(error)
+    }
+}
+
+impl From<sqlx::migrate::MigrateError> for Error {
+    fn from(error: sqlx::migrate::MigrateError) -> Self {
+        Self::Database(sqlx::Error::Migrate(Box::new(error)))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_keep_messages_readable() {
+        assert_eq!(
+            Error::NotFound(3).to_string(),
+            "That Contact ID does not exist: 3"
+        );
+        assert_eq!(
+            Error::InvalidEmail("test@.com".to_string()).to_string(),
+            "test@.com is invalid"
+        );
+        assert_eq!(
+            Error::CsvRow {
+                line: 2,
+                field: "phone_number".to_string()
+            }
+            .to_string(),
+            "Invalid phone_number on line 2"
+        );
+        assert_eq!(
+            Error::InvalidRows(vec![RowError {
+                line: 3,
+                column: "email".to_string(),
+                reason: "\"a@\" is not a valid email".to_string()
+            }])
+            .to_string(),
+            "Nothing was imported because of invalid rows:\n  line 3, email: \"a@\" is not a valid email"
+        );
+        assert_eq!(
+            Error::AmbiguousName {
+                query: "dan".to_string(),
+                candidates: vec![(1, "Dan Brown".to_string()), (4, "Dan Smith".to_string())]
+            }
+            .to_string(),
+            "dan matches several contacts:\n  1\tDan Brown\n  4\tDan Smith\nUse one of these IDs instead"
+        );
+    }
+}
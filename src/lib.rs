@@ -4,9 +4,12 @@
 #![cfg_attr(test, allow(clippy::panic_in_result_fn))]
 
 pub mod db;
+mod error;
 pub mod models;
 
 pub mod utils;
 
+pub use error::{Error, Result, RowError};
+
 #[cfg(test)]
 pub mod test_helpers;
@@ -1,6 +1,31 @@
+mod address;
+mod birthday;
+mod card;
 mod contact;
+mod interaction;
+mod labeled;
+mod note;
+mod search;
+mod tag;
 
+pub use address::Indexed as IndexedAddress;
+pub use address::PostalAddress;
+pub use birthday::Birthday;
+pub use card::Card as ContactCard;
+pub use contact::next_reminder_at;
+pub use contact::Cadence;
+pub use contact::CadenceUnit;
 pub use contact::Construct as ContactBuilder;
 pub use contact::Contact;
+pub use contact::Field as ContactField;
 pub use contact::Indexed as IndexedContact;
 pub use contact::Optional as OptionalContact;
+pub use interaction::Interaction;
+pub use labeled::Email as ContactEmail;
+pub use labeled::Label;
+pub use labeled::Labeled;
+pub use labeled::Phone as ContactPhone;
+pub use note::Note;
+pub use note::NoteRevision;
+pub use search::SearchHit;
+pub use tag::Tag;
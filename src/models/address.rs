@@ -0,0 +1,128 @@
+use tabled::Tabled;
+
+use super::Label;
+use crate::Error;
+
+/// A postal address, split into the parts mail is sorted by
+#[derive(Debug, Default, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct PostalAddress {
+    pub label: Label,
+    pub street: String,
+    /// City, town or village
+    pub locality: String,
+    /// State, province or county
+    pub region: String,
+    pub postal_code: String,
+    pub country: String,
+}
+
+impl PostalAddress {
+    /// Reads the label of an address, which is home, work or other.
+    ///
+    /// # Errors
+    ///
+    /// Will return [`Error::InvalidAddressLabel`] for any other label.
+    pub fn parse_label(value: &str) -> crate::Result<Label> {
+        value
+            .parse::<Label>()
+            .ok()
+            .filter(|label| label.fits_address())
+            .ok_or_else(|| Error::InvalidAddressLabel(value.to_owned()))
+    }
+
+    /// Whether every part of the address is blank; the label does not count.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        [
+            &self.street,
+            &self.locality,
+            &self.region,
+            &self.postal_code,
+            &self.country,
+        ]
+        .iter()
+        .all(|part| part.trim().is_empty())
+    }
+
+    /// The address as written on an envelope: the street, then
+    /// `locality, region postal_code`, then the country. Blank parts are
+    /// left out.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        let region_and_code = [self.region.trim(), self.postal_code.trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let town = [self.locality.trim(), region_and_code.as_str()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        self.street
+            .lines()
+            .map(str::trim)
+            .chain([town.as_str(), self.country.trim()])
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+/// A saved address of a contact
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct Indexed {
+    pub id: i64,
+    #[tabled(skip)]
+    #[serde(skip)]
+    pub contact_id: i64,
+    #[sqlx(flatten)]
+    #[tabled(inline)]
+    #[serde(flatten)]
+    pub address: PostalAddress,
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_write_addresses_as_envelope_lines() {
+        let address = PostalAddress {
+            label: Label::Home,
+            street: "12 St James's Square\nFlat 3".to_string(),
+            locality: "London".to_string(),
+            region: String::new(),
+            postal_code: "SW1Y 4JH".to_string(),
+            country: "United Kingdom".to_string(),
+        };
+
+        assert_eq!(
+            address.lines(),
+            vec![
+                "12 St James's Square",
+                "Flat 3",
+                "London, SW1Y 4JH",
+                "United Kingdom"
+            ]
+        );
+        assert!(!address.is_empty());
+        assert!(PostalAddress::default().is_empty());
+    }
+
+    #[test]
+    fn should_only_accept_home_work_and_other_address_labels() {
+        assert_eq!(PostalAddress::parse_label("Home").unwrap(), Label::Home);
+        assert_eq!(PostalAddress::parse_label("other").unwrap(), Label::Other);
+        assert!(matches!(
+            PostalAddress::parse_label("mobile"),
+            Err(Error::InvalidAddressLabel(label)) if label == "mobile"
+        ));
+        assert!(matches!(
+            PostalAddress::parse_label("wrok"),
+            Err(Error::InvalidAddressLabel(_))
+        ));
+    }
+}
@@ -0,0 +1,251 @@
+use std::{fmt, str::FromStr};
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::Error;
+
+/// A month and day of birth, with the year when it is known.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Birthday {
+    year: Option<i32>,
+    month: u32,
+    day: u32,
+}
+
+/// A leap year, so that year-less birthdays can fall on February 29.
+const LEAP_YEAR: i32 = 2000;
+
+impl Birthday {
+    /// Returns `None` when the day does not exist in that month (and year,
+    /// when given).
+    #[must_use]
+    pub fn new(year: Option<i32>, month: u32, day: u32) -> Option<Self> {
+        NaiveDate::from_ymd_opt(year.unwrap_or(LEAP_YEAR), month, day)?;
+
+        Some(Self { year, month, day })
+    }
+
+    #[must_use]
+    pub fn from_date(date: NaiveDate) -> Self {
+        Self {
+            year: Some(date.year()),
+            month: date.month(),
+            day: date.day(),
+        }
+    }
+
+    #[must_use]
+    pub const fn year(self) -> Option<i32> {
+        self.year
+    }
+
+    #[must_use]
+    pub const fn month(self) -> u32 {
+        self.month
+    }
+
+    #[must_use]
+    pub const fn day(self) -> u32 {
+        self.day
+    }
+
+    /// The full date of birth, when the year is known.
+    #[must_use]
+    pub fn date(self) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(self.year?, self.month, self.day)
+    }
+
+    /// The day the birthday is celebrated in `year`. February 29 birthdays
+    /// fall on February 28 outside leap years.
+    #[must_use]
+    pub fn in_year(self, year: i32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, self.month, self.day).or_else(|| {
+            (self.month == 2 && self.day == 29)
+                .then(|| NaiveDate::from_ymd_opt(year, 2, 28))
+                .flatten()
+        })
+    }
+
+    /// The next time the birthday is celebrated, `today` included.
+    #[must_use]
+    pub fn next_from(self, today: NaiveDate) -> Option<NaiveDate> {
+        self.in_year(today.year())
+            .filter(|date| *date >= today)
+            .or_else(|| self.in_year(today.year().checked_add(1)?))
+    }
+
+    /// The age turned on the birthday celebrated in `year`, when the year of
+    /// birth is known.
+    #[must_use]
+    pub fn age_in(self, year: i32) -> Option<i32> {
+        year.checked_sub(self.year?)
+    }
+}
+
+impl fmt::Display for Birthday {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.year {
+            Some(year) => write!(formatter, "{year:04}-{:02}-{:02}", self.month, self.day),
+            None => write!(formatter, "--{:02}-{:02}", self.month, self.day),
+        }
+    }
+}
+
+impl FromStr for Birthday {
+    type Err = Error;
+
+    /// Reads `1970-01-01`, `19700101` and date-times such as
+    /// `1970-01-01T00:00:00Z`, or the year-less `--04-15`, `--0415` and `04-15`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let trimmed = value.trim();
+        let invalid = || Error::InvalidDate(value.to_owned());
+
+        let month_day = |month_day: &str| {
+            let (month, day) = month_day
+                .split_once('-')
+                .or_else(|| month_day.is_char_boundary(2).then(|| month_day.split_at(2)))
+                .filter(|(month, day)| month.len() == 2 && day.len() == 2)?;
+
+            Self::new(None, month.parse().ok()?, day.parse().ok()?)
+        };
+
+        if let Some(year_less) = trimmed.strip_prefix("--") {
+            return month_day(year_less).ok_or_else(invalid);
+        }
+
+        if trimmed.len() == 5 {
+            return month_day(trimmed).ok_or_else(invalid);
+        }
+
+        let date = trimmed.split_once('T').map_or(trimmed, |(date, _)| date);
+
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .or_else(|_| NaiveDate::parse_from_str(date, "%Y%m%d"))
+            .map(Self::from_date)
+            .map_err(|_| invalid())
+    }
+}
+
+impl serde::Serialize for Birthday {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Birthday {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl sqlx::Type<sqlx::Sqlite> for Birthday {
+    fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
+        <String as sqlx::Type<sqlx::Sqlite>>::type_info()
+    }
+
+    fn compatible(type_info: &sqlx::sqlite::SqliteTypeInfo) -> bool {
+        <String as sqlx::Type<sqlx::Sqlite>>::compatible(type_info)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Sqlite> for Birthday {
+    fn encode_by_ref(
+        &self,
+        buffer: &mut Vec<sqlx::sqlite::SqliteArgumentValue<'_>>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <String as sqlx::Encode<sqlx::Sqlite>>::encode(self.to_string(), buffer)
+    }
+}
+
+impl sqlx::Decode<'_, sqlx::Sqlite> for Birthday {
+    fn decode(value: sqlx::sqlite::SqliteValueRef<'_>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as sqlx::Decode<sqlx::Sqlite>>::decode(value)?;
+        Ok(value.parse()?)
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn should_parse_birthdays_with_and_without_year() {
+        assert_eq!(
+            "1815-12-10".parse::<Birthday>().unwrap(),
+            Birthday::new(Some(1815), 12, 10).unwrap()
+        );
+        assert_eq!(
+            "1832-1-27".parse::<Birthday>().unwrap(),
+            Birthday::new(Some(1832), 1, 27).unwrap()
+        );
+        assert_eq!(
+            "1987-07-11T00:00:00Z".parse::<Birthday>().unwrap(),
+            Birthday::new(Some(1987), 7, 11).unwrap()
+        );
+        for year_less in ["--1210", "--12-10", "12-10"] {
+            assert_eq!(
+                year_less.parse::<Birthday>().unwrap(),
+                Birthday::new(None, 12, 10).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn should_reject_days_that_do_not_exist() {
+        for value in ["1970-13-32", "--02-30", "2023-02-29", "soon", ""] {
+            let err = value.parse::<Birthday>().unwrap_err();
+            assert!(matches!(err, Error::InvalidDate(ref invalid) if invalid == value));
+        }
+
+        assert!("--02-29".parse::<Birthday>().is_ok());
+    }
+
+    #[test]
+    fn should_display_as_stored() {
+        assert_eq!(
+            Birthday::new(Some(1815), 12, 10).unwrap().to_string(),
+            "1815-12-10"
+        );
+        assert_eq!(Birthday::new(None, 4, 5).unwrap().to_string(), "--04-05");
+    }
+
+    #[test]
+    fn should_find_next_birthday() {
+        let birthday = Birthday::new(Some(1815), 12, 10).unwrap();
+
+        assert_eq!(
+            birthday.next_from(date(2026, 10, 18)),
+            Some(date(2026, 12, 10))
+        );
+        assert_eq!(
+            birthday.next_from(date(2026, 12, 10)),
+            Some(date(2026, 12, 10))
+        );
+        assert_eq!(
+            birthday.next_from(date(2026, 12, 11)),
+            Some(date(2027, 12, 10))
+        );
+        assert_eq!(birthday.age_in(2026), Some(211));
+        assert_eq!(Birthday::new(None, 12, 10).unwrap().age_in(2026), None);
+    }
+
+    #[test]
+    fn should_celebrate_leap_day_on_february_28_in_common_years() {
+        let birthday = Birthday::new(Some(2000), 2, 29).unwrap();
+
+        assert_eq!(
+            birthday.next_from(date(2026, 1, 1)),
+            Some(date(2026, 2, 28))
+        );
+        assert_eq!(
+            birthday.next_from(date(2027, 3, 1)),
+            Some(date(2028, 2, 29))
+        );
+    }
+}
@@ -0,0 +1,25 @@
+use super::{ContactEmail, ContactPhone, IndexedAddress, IndexedContact, Note};
+
+/// A contact together with everything stored alongside it, as exports write it
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Card {
+    pub contact: IndexedContact,
+    pub tags: Vec<String>,
+    pub emails: Vec<ContactEmail>,
+    pub phones: Vec<ContactPhone>,
+    pub addresses: Vec<IndexedAddress>,
+    pub notes: Vec<Note>,
+}
+
+impl From<IndexedContact> for Card {
+    fn from(contact: IndexedContact) -> Self {
+        Self {
+            contact,
+            tags: Vec::new(),
+            emails: Vec::new(),
+            phones: Vec::new(),
+            addresses: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+}
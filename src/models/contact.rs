@@ -1,15 +1,21 @@
-use crate::utils::{self, default_date};
-use chrono::{DateTime, NaiveDate, Utc};
+use super::{Birthday, Labeled, PostalAddress};
+use crate::{utils, Error};
+use chrono::{DateTime, Days, Months, SecondsFormat, Utc};
+use std::{fmt, str::FromStr};
 use tabled::Tabled;
 
-#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled)]
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
 pub struct Contact {
     pub first_name: String,
     pub last_name: String,
     pub display_name: String,
     pub email: String,
     pub phone_number: String,
-    pub birthday: NaiveDate,
+    /// `phone_number` in E.164 form, or `None` when it is not a valid number
+    #[tabled(skip)]
+    pub phone_e164: Option<String>,
+    #[tabled(display("display_birthday"))]
+    pub birthday: Option<Birthday>,
     #[tabled(skip)]
     pub starred: bool,
     #[tabled(skip)]
@@ -21,9 +27,22 @@ pub struct Contact {
     #[tabled(skip)]
     pub last_seen_at: Option<DateTime<Utc>>,
     #[tabled(skip)]
-    pub frequency: Option<String>,
+    pub next_reminder_at: Option<DateTime<Utc>>,
+    #[tabled(skip)]
+    pub frequency: Option<Cadence>,
     #[tabled(skip)]
     pub last_reminder_at: Option<DateTime<Utc>>,
+    /// ID of the contact in the system it was imported from
+    #[tabled(skip)]
+    pub external_id: Option<String>,
+}
+
+/// Shows unknown birthdays as an empty cell
+#[allow(clippy::ref_option)] // signature required by tabled
+fn display_birthday(birthday: &Option<Birthday>) -> String {
+    birthday
+        .map(|birthday| utils::format_birthday(birthday, utils::DEFAULT_DATE_FORMAT))
+        .unwrap_or_default()
 }
 
 #[derive(Debug, Default)]
@@ -33,6 +52,8 @@ pub struct ContactBuilder {
     email: Option<String>,
     phone_number: Option<String>,
     birthday: Option<String>,
+    frequency: Option<String>,
+    region: Option<String>,
 }
 
 impl ContactBuilder {
@@ -65,20 +86,44 @@ impl ContactBuilder {
         self
     }
 
+    pub fn frequency(mut self, frequency: &str) -> Self {
+        self.frequency = Some(frequency.to_owned());
+        self
+    }
+
+    /// Region of a phone number without a country code, [`utils::DEFAULT_REGION`]
+    /// unless set
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_owned());
+        self
+    }
+
     /// # Errors
     ///
-    /// This errors if there is an invalid email, phone number, or birthday, or if required fields are missing
-    ///
-    /// # Panics
-    /// This will panic if `NaiveDate` fails
-    pub fn build(self) -> anyhow::Result<Contact, anyhow::Error> {
+    /// This errors if there is an invalid email, phone number, birthday or frequency, or if required fields are missing
+    pub fn build(self) -> crate::Result<Contact> {
         let first_name = self.first_name.unwrap_or_default();
         let last_name = self.last_name.unwrap_or_default();
         let email = self.email.unwrap_or_default();
         let phone_number = self.phone_number.unwrap_or_default();
         let birthday = self.birthday.unwrap_or_default();
+        let region = self.region.as_deref().unwrap_or(utils::DEFAULT_REGION);
+
+        let mut contact = Contact::new(
+            &first_name,
+            &last_name,
+            &email,
+            &phone_number,
+            &birthday,
+            region,
+        )?;
+
+        if let Some(frequency) = self.frequency.filter(|value| !value.trim().is_empty()) {
+            contact.frequency = Some(frequency.parse()?);
+            contact.next_reminder_at = contact.due_at();
+        }
 
-        Contact::new(&first_name, &last_name, &email, &phone_number, &birthday)
+        Ok(contact)
     }
 }
 
@@ -90,35 +135,31 @@ impl Contact {
 
     /// # Errors
     ///
-    /// This errors if there is an invalid email, phone number, or birthday
-    ///
-    /// # Panics
-    /// This will panic if `NaiveDate` fails
+    /// This errors if there is an invalid email, phone number, or birthday.
+    /// The birthday may leave out the year, as in `--12-10`, and the phone
+    /// number its country code when it belongs to `region`.
     pub fn new(
         first_name: &str,
         last_name: &str,
         email: &str,
         phone_number: &str,
         birthday: &str,
-    ) -> anyhow::Result<Self> {
+        region: &str,
+    ) -> crate::Result<Self> {
         let display_name = format!("{first_name} {last_name}");
 
         if utils::is_not_valid_email(email) && !email.is_empty() {
-            anyhow::bail!("{} is invalid", email.to_owned())
+            return Err(Error::InvalidEmail(email.to_owned()));
         }
 
-        if utils::is_not_valid_phone_number(phone_number) && !phone_number.is_empty() {
-            anyhow::bail!("{} is invalid", phone_number.to_owned())
+        if utils::is_not_valid_phone_number(phone_number, region) && !phone_number.is_empty() {
+            return Err(Error::InvalidPhone(phone_number.to_owned()));
         }
 
         let birthday = if birthday.trim().is_empty() {
-            default_date()
+            None
         } else {
-            let Ok(parsed_date) = NaiveDate::parse_from_str(birthday, "%Y-%m-%d") else {
-                anyhow::bail!("{birthday} is invalid")
-            };
-
-            parsed_date
+            Some(birthday.parse()?)
         };
 
         let now = Utc::now();
@@ -129,26 +170,425 @@ impl Contact {
             display_name,
             email: email.to_owned(),
             phone_number: phone_number.to_owned(),
+            phone_e164: utils::normalize_phone_number(phone_number, region),
             birthday,
             starred: false,
             is_archived: false,
             created_at: now,
             updated_at: now,
             last_seen_at: None,
+            next_reminder_at: None,
             frequency: None,
             last_reminder_at: None,
+            external_id: None,
         })
     }
+
+    /// When the contact is next due a catch-up: one cadence after they were
+    /// last seen, or after they were added if never. `None` without a cadence.
+    #[must_use]
+    pub fn due_at(&self) -> Option<DateTime<Utc>> {
+        next_reminder_at(self.frequency, self.created_at, self.last_seen_at)
+    }
+
+    /// The fields of `Field::MERGEABLE` that hold a different, non-empty value
+    /// in each contact, and so need a choice when merging.
+    #[must_use]
+    pub fn conflicts_with(&self, other: &Self) -> Vec<Field> {
+        Field::MERGEABLE
+            .into_iter()
+            .filter(|field| {
+                let ours = field.value_of(self);
+                let theirs = field.value_of(other);
+                !ours.trim().is_empty() && !theirs.trim().is_empty() && ours != theirs
+            })
+            .collect()
+    }
+
+    /// Folds `other` into this contact. Fields listed in `take`, and fields
+    /// this contact leaves empty, come from `other`.
+    ///
+    /// The result keeps the earliest `created_at`, the latest `last_seen_at`
+    /// and `last_reminder_at`, is starred if either contact was, and stays
+    /// archived only if both were.
+    #[must_use]
+    pub fn merged_with(&self, other: &Self, take: &[Field]) -> Self {
+        let mut merged = self.clone();
+
+        for field in Field::MERGEABLE {
+            if take.contains(&field) || field.value_of(self).trim().is_empty() {
+                merged.copy_field(field, other);
+            }
+        }
+
+        merged.created_at = self.created_at.min(other.created_at);
+        merged.last_seen_at = self.last_seen_at.max(other.last_seen_at);
+        merged.last_reminder_at = self.last_reminder_at.max(other.last_reminder_at);
+        merged.starred = self.starred || other.starred;
+        merged.is_archived = self.is_archived && other.is_archived;
+        merged.external_id = self
+            .external_id
+            .clone()
+            .or_else(|| other.external_id.clone());
+        merged.next_reminder_at = merged.due_at();
+
+        merged
+    }
+
+    fn copy_field(&mut self, field: Field, other: &Self) {
+        match field {
+            Field::FirstName => self.first_name.clone_from(&other.first_name),
+            Field::LastName => self.last_name.clone_from(&other.last_name),
+            Field::DisplayName => self.display_name.clone_from(&other.display_name),
+            Field::Email => self.email.clone_from(&other.email),
+            Field::PhoneNumber => {
+                self.phone_number.clone_from(&other.phone_number);
+                self.phone_e164.clone_from(&other.phone_e164);
+            }
+            Field::Birthday => self.birthday = other.birthday,
+            Field::Frequency => self.frequency = other.frequency,
+            _ => {}
+        }
+    }
+}
+
+/// Computes `next_reminder_at` from a contact's cadence and when they were
+/// added and last seen.
+#[must_use]
+pub fn next_reminder_at(
+    frequency: Option<Cadence>,
+    created_at: DateTime<Utc>,
+    last_seen_at: Option<DateTime<Utc>>,
+) -> Option<DateTime<Utc>> {
+    frequency?.after(last_seen_at.unwrap_or(created_at))
+}
+
+/// How often to keep in touch with a contact.
+///
+/// Parsed from `daily`, `weekly`, `fortnightly`, `monthly`, `quarterly`,
+/// `yearly`, `every 2 weeks` or the short `10d`/`2w`/`6m`/`1y` forms, and
+/// stored in its canonical spelling.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cadence {
+    count: u32,
+    unit: CadenceUnit,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CadenceUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl CadenceUnit {
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Year => "year",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "d" | "day" | "days" => Some(Self::Day),
+            "w" | "week" | "weeks" => Some(Self::Week),
+            "m" | "month" | "months" => Some(Self::Month),
+            "y" | "year" | "years" => Some(Self::Year),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled)]
+impl Cadence {
+    /// Returns `None` when `count` is zero.
+    #[must_use]
+    pub const fn new(count: u32, unit: CadenceUnit) -> Option<Self> {
+        if count == 0 {
+            None
+        } else {
+            Some(Self { count, unit })
+        }
+    }
+
+    #[must_use]
+    pub const fn count(self) -> u32 {
+        self.count
+    }
+
+    #[must_use]
+    pub const fn unit(self) -> CadenceUnit {
+        self.unit
+    }
+
+    /// The moment one cadence after `since`, or `None` past the end of time.
+    #[must_use]
+    pub fn after(self, since: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self.unit {
+            CadenceUnit::Day => since.checked_add_days(Days::new(self.count.into())),
+            CadenceUnit::Week => {
+                since.checked_add_days(Days::new(u64::from(self.count).checked_mul(7)?))
+            }
+            CadenceUnit::Month => since.checked_add_months(Months::new(self.count)),
+            CadenceUnit::Year => since.checked_add_months(Months::new(self.count.checked_mul(12)?)),
+        }
+    }
+}
+
+impl fmt::Display for Cadence {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.count, self.unit) {
+            (1, CadenceUnit::Day) => formatter.write_str("daily"),
+            (1, CadenceUnit::Week) => formatter.write_str("weekly"),
+            (1, CadenceUnit::Month) => formatter.write_str("monthly"),
+            (3, CadenceUnit::Month) => formatter.write_str("quarterly"),
+            (1, CadenceUnit::Year) => formatter.write_str("yearly"),
+            (count, unit) => write!(formatter, "every {count} {}s", unit.name()),
+        }
+    }
+}
+
+impl FromStr for Cadence {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let normalized = value.trim().to_lowercase();
+        let invalid = || Error::InvalidCadence(value.to_owned());
+
+        let (count, unit) = match normalized.as_str() {
+            "daily" => (1, CadenceUnit::Day),
+            "weekly" => (1, CadenceUnit::Week),
+            "biweekly" | "fortnightly" => (2, CadenceUnit::Week),
+            "monthly" => (1, CadenceUnit::Month),
+            "quarterly" => (3, CadenceUnit::Month),
+            "yearly" | "annually" => (1, CadenceUnit::Year),
+            other => {
+                let words: Vec<&str> = other.split_whitespace().collect();
+
+                match words.as_slice() {
+                    ["every", unit] => (1, CadenceUnit::parse(unit).ok_or_else(invalid)?),
+                    ["every", count, unit] => (
+                        count.parse().map_err(|_| invalid())?,
+                        CadenceUnit::parse(unit).ok_or_else(invalid)?,
+                    ),
+                    [short] => {
+                        let split = short
+                            .find(|character: char| !character.is_ascii_digit())
+                            .ok_or_else(invalid)?;
+                        let (count, unit) = short.split_at(split);
+                        (
+                            count.parse().map_err(|_| invalid())?,
+                            CadenceUnit::parse(unit).ok_or_else(invalid)?,
+                        )
+                    }
+                    _ => return Err(invalid()),
+                }
+            }
+        };
+
+        Self::new(count, unit).ok_or_else(invalid)
+    }
+}
+
+impl serde::Serialize for Cadence {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Cadence {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl sqlx::Type<sqlx::Sqlite> for Cadence {
+    fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
+        <String as sqlx::Type<sqlx::Sqlite>>::type_info()
+    }
+
+    fn compatible(type_info: &sqlx::sqlite::SqliteTypeInfo) -> bool {
+        <String as sqlx::Type<sqlx::Sqlite>>::compatible(type_info)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Sqlite> for Cadence {
+    fn encode_by_ref(
+        &self,
+        buffer: &mut Vec<sqlx::sqlite::SqliteArgumentValue<'_>>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <String as sqlx::Encode<sqlx::Sqlite>>::encode(self.to_string(), buffer)
+    }
+}
+
+impl sqlx::Decode<'_, sqlx::Sqlite> for Cadence {
+    fn decode(value: sqlx::sqlite::SqliteValueRef<'_>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as sqlx::Decode<sqlx::Sqlite>>::decode(value)?;
+        Ok(value.parse()?)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
 pub struct Indexed {
     pub id: i64,
     #[sqlx(flatten)]
     #[tabled(inline)]
+    #[serde(flatten)]
     pub contact: Contact,
 }
 
+impl Indexed {
+    /// The ID exports give the contact: the one it was imported with, or else
+    /// one made from its ID and creation time.
+    #[must_use]
+    pub fn uid(&self) -> String {
+        self.contact.external_id.clone().unwrap_or_else(|| {
+            format!(
+                "nbd-{}-{}",
+                self.id,
+                self.contact.created_at.format("%Y%m%dT%H%M%S%.6fZ")
+            )
+        })
+    }
+}
+
+/// A column of the `contacts` table, named as the CSV importer expects it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Field {
+    Id,
+    FirstName,
+    LastName,
+    DisplayName,
+    Email,
+    PhoneNumber,
+    Birthday,
+    Starred,
+    IsArchived,
+    CreatedAt,
+    UpdatedAt,
+    LastSeenAt,
+    NextReminderAt,
+    Frequency,
+    LastReminderAt,
+    ExternalId,
+}
+
+impl Field {
+    pub const ALL: [Self; 16] = [
+        Self::Id,
+        Self::FirstName,
+        Self::LastName,
+        Self::DisplayName,
+        Self::Email,
+        Self::PhoneNumber,
+        Self::Birthday,
+        Self::Starred,
+        Self::IsArchived,
+        Self::CreatedAt,
+        Self::UpdatedAt,
+        Self::LastSeenAt,
+        Self::NextReminderAt,
+        Self::Frequency,
+        Self::LastReminderAt,
+        Self::ExternalId,
+    ];
+
+    /// Fields a merge may take from the contact being folded in
+    pub const MERGEABLE: [Self; 7] = [
+        Self::FirstName,
+        Self::LastName,
+        Self::DisplayName,
+        Self::Email,
+        Self::PhoneNumber,
+        Self::Birthday,
+        Self::Frequency,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Id => "id",
+            Self::FirstName => "first_name",
+            Self::LastName => "last_name",
+            Self::DisplayName => "display_name",
+            Self::Email => "email",
+            Self::PhoneNumber => "phone_number",
+            Self::Birthday => "birthday",
+            Self::Starred => "starred",
+            Self::IsArchived => "is_archived",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::LastSeenAt => "last_seen_at",
+            Self::NextReminderAt => "next_reminder_at",
+            Self::Frequency => "frequency",
+            Self::LastReminderAt => "last_reminder_at",
+            Self::ExternalId => "external_id",
+        }
+    }
+
+    /// Formats the field of `indexed` the way the CSV importer reads it back:
+    /// dates as `YYYY-MM-DD`, timestamps as RFC 3339 and unknown values empty.
+    #[must_use]
+    pub fn value(self, indexed: &Indexed) -> String {
+        match self {
+            Self::Id => indexed.id.to_string(),
+            field => field.value_of(&indexed.contact),
+        }
+    }
+
+    /// Like `value`, for a contact without an ID; `Id` is always empty.
+    #[must_use]
+    pub fn value_of(self, contact: &Contact) -> String {
+        let timestamp = |value: DateTime<Utc>| value.to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        match self {
+            Self::Id => String::new(),
+            Self::FirstName => contact.first_name.clone(),
+            Self::LastName => contact.last_name.clone(),
+            Self::DisplayName => contact.display_name.clone(),
+            Self::Email => contact.email.clone(),
+            Self::PhoneNumber => contact.phone_number.clone(),
+            Self::Birthday => contact
+                .birthday
+                .map(|birthday| birthday.to_string())
+                .unwrap_or_default(),
+            Self::Starred => contact.starred.to_string(),
+            Self::IsArchived => contact.is_archived.to_string(),
+            Self::CreatedAt => timestamp(contact.created_at),
+            Self::UpdatedAt => timestamp(contact.updated_at),
+            Self::LastSeenAt => contact.last_seen_at.map(timestamp).unwrap_or_default(),
+            Self::NextReminderAt => contact.next_reminder_at.map(timestamp).unwrap_or_default(),
+            Self::Frequency => contact
+                .frequency
+                .map(|frequency| frequency.to_string())
+                .unwrap_or_default(),
+            Self::LastReminderAt => contact.last_reminder_at.map(timestamp).unwrap_or_default(),
+            Self::ExternalId => contact.external_id.clone().unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|field| field.as_str() == name.trim())
+            .ok_or_else(|| Error::UnknownField(name.to_owned()))
+    }
+}
+
 #[derive(Debug, Default, PartialEq, Eq, Clone, serde::Deserialize)]
 pub struct Optional {
     pub first_name: Option<String>,
@@ -156,12 +596,35 @@ pub struct Optional {
     pub display_name: Option<String>,
     pub email: Option<String>,
     pub phone_number: Option<String>,
-    pub birthday: Option<NaiveDate>,
+    pub birthday: Option<Birthday>,
     pub starred: Option<bool>,
     pub is_archived: Option<bool>,
+    /// When the contact was first added, as an export records it; a new
+    /// contact is stamped with the time it is saved otherwise
+    pub created_at: Option<DateTime<Utc>>,
     pub last_seen_at: Option<DateTime<Utc>>,
-    pub frequency: Option<String>,
+    pub frequency: Option<Cadence>,
     pub last_reminder_at: Option<DateTime<Utc>>,
+    pub external_id: Option<String>,
+    /// Tags attached when the contact is saved
+    #[serde(skip)]
+    pub tags: Vec<String>,
+    /// Labelled email addresses saved next to `email`
+    #[serde(skip)]
+    pub emails: Vec<Labeled>,
+    /// Labelled phone numbers saved next to `phone_number`
+    #[serde(skip)]
+    pub phones: Vec<Labeled>,
+    /// Postal addresses saved with the contact
+    #[serde(skip)]
+    pub addresses: Vec<PostalAddress>,
+    /// Bodies of notes saved with the contact
+    #[serde(skip)]
+    pub notes: Vec<String>,
+    /// Fields an update sets back to unknown. Clearing `email` or
+    /// `phone_number` removes the preferred one, and the next takes its place.
+    #[serde(skip)]
+    pub cleared: Vec<Field>,
 }
 
 impl Optional {
@@ -175,9 +638,12 @@ impl Optional {
             && self.birthday.is_none()
             && self.starred.is_none()
             && self.is_archived.is_none()
+            && self.created_at.is_none()
             && self.last_seen_at.is_none()
             && self.frequency.is_none()
             && self.last_reminder_at.is_none()
+            && self.external_id.is_none()
+            && self.cleared.is_empty()
     }
 }
 
@@ -195,12 +661,14 @@ pub struct ConstructBuilder {
     email: Option<String>,
     phone_number: Option<String>,
     display_name: Option<String>,
-    birthday: Option<NaiveDate>,
+    birthday: Option<Birthday>,
     starred: Option<bool>,
     is_archived: Option<bool>,
     last_seen_at: Option<DateTime<Utc>>,
-    frequency: Option<String>,
+    frequency: Option<Cadence>,
     last_reminder_at: Option<DateTime<Utc>>,
+    cleared: Vec<Field>,
+    region: Option<String>,
 }
 
 impl ConstructBuilder {
@@ -238,7 +706,7 @@ impl ConstructBuilder {
         self
     }
 
-    pub const fn birthday(mut self, birthday: NaiveDate) -> Self {
+    pub const fn birthday(mut self, birthday: Birthday) -> Self {
         self.birthday = Some(birthday);
         self
     }
@@ -258,7 +726,7 @@ impl ConstructBuilder {
         self
     }
 
-    pub fn frequency(mut self, frequency: String) -> Self {
+    pub const fn frequency(mut self, frequency: Cadence) -> Self {
         self.frequency = Some(frequency);
         self
     }
@@ -268,20 +736,37 @@ impl ConstructBuilder {
         self
     }
 
+    /// Region of a phone number without a country code, [`utils::DEFAULT_REGION`]
+    /// unless set
+    pub fn region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_owned());
+        self
+    }
+
+    /// Sets `field` back to unknown. Only the email, phone number, birthday,
+    /// cadence and the last-seen and last-reminder times can be cleared.
+    pub fn clear(mut self, field: Field) -> Self {
+        if !self.cleared.contains(&field) {
+            self.cleared.push(field);
+        }
+        self
+    }
+
     /// # Errors
     ///
     /// This errors if there is an invalid email or phone number, missing id, or all fields are empty
-    pub fn build(self) -> anyhow::Result<Construct> {
-        let id = self.id.ok_or_else(|| anyhow::anyhow!("ID is required"))?;
+    pub fn build(self) -> crate::Result<Construct> {
+        let id = self.id.ok_or(Error::MissingId)?;
 
         let maybe_email = self.email.as_deref().unwrap_or("");
         if utils::is_not_valid_email(maybe_email) && self.email.is_some() {
-            anyhow::bail!("{maybe_email} is invalid")
+            return Err(Error::InvalidEmail(maybe_email.to_owned()));
         }
 
         let maybe_phone = self.phone_number.as_deref().unwrap_or("");
-        if utils::is_not_valid_phone_number(maybe_phone) && self.phone_number.is_some() {
-            anyhow::bail!("{maybe_phone} is invalid")
+        let region = self.region.as_deref().unwrap_or(utils::DEFAULT_REGION);
+        if utils::is_not_valid_phone_number(maybe_phone, region) && self.phone_number.is_some() {
+            return Err(Error::InvalidPhone(maybe_phone.to_owned()));
         }
 
         let optional_contact = Optional {
@@ -293,13 +778,21 @@ impl ConstructBuilder {
             birthday: self.birthday,
             starred: self.starred,
             is_archived: self.is_archived,
+            created_at: None,
             last_seen_at: self.last_seen_at,
             frequency: self.frequency,
             last_reminder_at: self.last_reminder_at,
+            external_id: None,
+            tags: Vec::new(),
+            emails: Vec::new(),
+            phones: Vec::new(),
+            addresses: Vec::new(),
+            notes: Vec::new(),
+            cleared: self.cleared,
         };
 
         if optional_contact.is_empty() {
-            anyhow::bail!("No fields provided for update")
+            return Err(Error::EmptyUpdate);
         }
 
         Ok(Construct {
@@ -333,8 +826,8 @@ impl Construct {
         email: Option<String>,
         phone_number: Option<String>,
         display_name: Option<String>,
-        birthday: Option<NaiveDate>,
-    ) -> anyhow::Result<Self> {
+        birthday: Option<Birthday>,
+    ) -> crate::Result<Self> {
         let mut builder = ConstructBuilder::new().id(id);
 
         if let Some(first_name) = first_name {
@@ -372,6 +865,7 @@ impl Construct {
             && self.optional_contact.last_seen_at.is_none()
             && self.optional_contact.frequency.is_none()
             && self.optional_contact.last_reminder_at.is_none()
+            && self.optional_contact.cleared.is_empty()
     }
 }
 
@@ -379,7 +873,8 @@ impl Construct {
 #[allow(clippy::expect_used)]
 mod tests {
 
-    use super::{Construct, Contact};
+    use super::{Cadence, CadenceUnit, Construct, Contact, Field, Indexed};
+    use crate::Error;
 
     #[test]
     fn test_display_name() {
@@ -387,8 +882,9 @@ mod tests {
             "Jason",
             "Ribble",
             "john@example.com",
-            "123-456-7890",
+            "201-555-0123",
             "1970-01-01",
+            "US",
         )
         .expect("Example person");
         let display_name = "Jason Ribble".to_string();
@@ -402,7 +898,7 @@ mod tests {
             .first_name("Alice")
             .last_name("Lovelace")
             .email("ada@lovelace.com")
-            .phone_number("123-321-1233")
+            .phone_number("201-555-0133")
             .birthday("1970-01-01")
             .build()
             .expect("Example contact");
@@ -411,7 +907,7 @@ mod tests {
         assert_eq!(contact.last_name, "Lovelace");
         assert_eq!(contact.display_name, "Alice Lovelace");
         assert_eq!(contact.email, "ada@lovelace.com");
-        assert_eq!(contact.phone_number, "123-321-1233");
+        assert_eq!(contact.phone_number, "201-555-0133");
     }
 
     #[test]
@@ -421,7 +917,7 @@ mod tests {
             None,
             None,
             None,
-            Some("123-233-1221".to_string()),
+            Some("201-555-0121".to_string()),
             Some("Nickname".to_string()),
             None,
         )
@@ -434,7 +930,7 @@ mod tests {
         );
         assert_eq!(
             edits.optional_contact.phone_number,
-            Some("123-233-1221".to_string())
+            Some("201-555-0121".to_string())
         );
         assert_eq!(edits.optional_contact.first_name, None);
         assert_eq!(edits.optional_contact.last_name, None);
@@ -471,7 +967,7 @@ mod tests {
             .id(3)
             .first_name("John".to_string())
             .email("john@example.com".to_string())
-            .phone_number("555-012-3456".to_string())
+            .phone_number("202-555-0156".to_string())
             .build()
             .expect("Contact build");
 
@@ -483,7 +979,7 @@ mod tests {
         );
         assert_eq!(
             edits.optional_contact.phone_number,
-            Some("555-012-3456".to_string())
+            Some("202-555-0156".to_string())
         );
         assert_eq!(edits.optional_contact.last_name, None);
         assert_eq!(edits.optional_contact.display_name, None);
@@ -492,7 +988,13 @@ mod tests {
     #[test]
     fn test_is_empty() {
         let result = Construct::new(1, None, None, None, None, None, None);
-        assert!(result.is_err());
+        assert!(matches!(result, Err(Error::EmptyUpdate)));
+    }
+
+    #[test]
+    fn test_missing_id() {
+        let result = Construct::builder().first_name("Ada".to_string()).build();
+        assert!(matches!(result, Err(Error::MissingId)));
     }
 
     #[test]
@@ -510,6 +1012,7 @@ mod tests {
 
         let err = result.expect_err("Expected invalid email to return an error");
         assert!(err.to_string().contains("invalid"));
+        assert!(matches!(err, Error::InvalidEmail(email) if email == "invalid@example"));
     }
 
     #[test]
@@ -519,7 +1022,7 @@ mod tests {
             None,
             None,
             None,
-            Some("123-123-12345".to_string()),
+            Some("201-555-01234".to_string()),
             None,
             None,
         );
@@ -529,6 +1032,158 @@ mod tests {
 
         let err = result.expect_err("Expected invalid phone number to return an error");
         assert!(err.to_string().contains("invalid"));
+        assert!(matches!(err, Error::InvalidPhone(_)));
+    }
+
+    #[test]
+    fn should_parse_field_names() {
+        assert_eq!("is_archived".parse::<Field>().ok(), Some(Field::IsArchived));
+
+        let err = "nickname".parse::<Field>().expect_err("Unknown column");
+        assert_eq!(err.to_string(), "Unknown column: nickname");
+    }
+
+    #[test]
+    fn should_leave_unknown_birthday_empty() {
+        let mut contact = Contact::new("Ada", "Lovelace", "", "", "", "US").expect("Contact build");
+        contact.frequency = Some(Cadence::new(1, CadenceUnit::Month).expect("Cadence"));
+        let indexed = Indexed { id: 7, contact };
+
+        assert_eq!(Field::Id.value(&indexed), "7");
+        assert_eq!(Field::Birthday.value(&indexed), "");
+        assert_eq!(Field::Starred.value(&indexed), "false");
+        assert_eq!(Field::Frequency.value(&indexed), "monthly");
+        assert_eq!(Field::LastSeenAt.value(&indexed), "");
+    }
+
+    #[test]
+    fn should_parse_cadences() {
+        let parse = |value: &str| value.parse::<Cadence>().expect("Valid cadence");
+
+        assert_eq!(
+            parse("Monthly"),
+            Cadence::new(1, CadenceUnit::Month).unwrap()
+        );
+        assert_eq!(
+            parse("quarterly"),
+            Cadence::new(3, CadenceUnit::Month).unwrap()
+        );
+        assert_eq!(
+            parse("fortnightly"),
+            Cadence::new(2, CadenceUnit::Week).unwrap()
+        );
+        assert_eq!(
+            parse("every 2 weeks"),
+            Cadence::new(2, CadenceUnit::Week).unwrap()
+        );
+        assert_eq!(
+            parse("every year"),
+            Cadence::new(1, CadenceUnit::Year).unwrap()
+        );
+        assert_eq!(parse("10d"), Cadence::new(10, CadenceUnit::Day).unwrap());
+    }
+
+    #[test]
+    fn should_reject_invalid_cadences() {
+        for value in [
+            "sometimes",
+            "every 0 weeks",
+            "every -1 days",
+            "2",
+            "every 2 fortnights",
+        ] {
+            let err = value.parse::<Cadence>().expect_err("Invalid cadence");
+            assert!(matches!(err, Error::InvalidCadence(ref invalid) if invalid == value));
+        }
+    }
+
+    #[test]
+    fn should_display_cadences_canonically() {
+        let display = |value: &str| value.parse::<Cadence>().expect("Valid cadence").to_string();
+
+        assert_eq!(display("1w"), "weekly");
+        assert_eq!(display("every 3 months"), "quarterly");
+        assert_eq!(display("biweekly"), "every 2 weeks");
+        assert_eq!(
+            display("every 2 weeks").parse::<Cadence>().ok(),
+            "2w".parse().ok()
+        );
+    }
+
+    #[test]
+    fn should_compute_next_reminder_from_last_seen() {
+        let mut contact = Contact::builder()
+            .first_name("Ada")
+            .frequency("monthly")
+            .build()
+            .expect("Contact build");
+        contact.created_at = "2026-01-31T10:00:00Z".parse().expect("Timestamp");
+
+        assert_eq!(
+            contact.due_at(),
+            Some("2026-02-28T10:00:00Z".parse().expect("Timestamp"))
+        );
+
+        contact.last_seen_at = Some("2026-03-15T10:00:00Z".parse().expect("Timestamp"));
+
+        assert_eq!(
+            contact.due_at(),
+            Some("2026-04-15T10:00:00Z".parse().expect("Timestamp"))
+        );
+    }
+
+    #[test]
+    fn should_merge_fields_and_keep_the_widest_history() {
+        let mut keep = Contact::builder()
+            .first_name("Lewis")
+            .last_name("Carroll")
+            .email("lewis@wonderland.com")
+            .build()
+            .expect("Contact build");
+        keep.created_at = "2026-03-01T00:00:00Z".parse().expect("Timestamp");
+        keep.last_seen_at = Some("2026-04-01T00:00:00Z".parse().expect("Timestamp"));
+
+        let mut drop = Contact::builder()
+            .first_name("Lewis")
+            .last_name("Carroll")
+            .email("carroll@oxford.ac.uk")
+            .phone_number("212-555-0177")
+            .birthday("1832-01-27")
+            .build()
+            .expect("Contact build");
+        drop.created_at = "2026-01-01T00:00:00Z".parse().expect("Timestamp");
+        drop.last_seen_at = Some("2026-02-01T00:00:00Z".parse().expect("Timestamp"));
+        drop.starred = true;
+        drop.is_archived = true;
+
+        assert_eq!(keep.conflicts_with(&drop), [Field::Email]);
+
+        let merged = keep.merged_with(&drop, &[]);
+        assert_eq!(merged.email, "lewis@wonderland.com");
+        assert_eq!(merged.phone_number, "212-555-0177");
+        assert_eq!(merged.birthday, drop.birthday);
+        assert_eq!(merged.created_at, drop.created_at);
+        assert_eq!(merged.last_seen_at, keep.last_seen_at);
+        assert!(merged.starred);
+        assert!(!merged.is_archived);
+
+        let merged = keep.merged_with(&drop, &[Field::Email]);
+        assert_eq!(merged.email, "carroll@oxford.ac.uk");
+    }
+
+    #[test]
+    fn should_serialize_with_stable_field_names() {
+        let mut contact = Contact::new("Ada", "Lovelace", "", "", "", "US").expect("Contact build");
+        contact.created_at = "2024-01-02T03:04:05Z".parse().expect("Timestamp");
+        let indexed = Indexed { id: 7, contact };
+
+        let json = serde_json::to_value(&indexed).expect("Serialized contact");
+
+        assert_eq!(json["id"], 7);
+        assert_eq!(json["first_name"], "Ada");
+        assert_eq!(json["birthday"], serde_json::Value::Null);
+        assert_eq!(json["created_at"], "2024-01-02T03:04:05Z");
+        assert_eq!(json["last_seen_at"], serde_json::Value::Null);
     }
 
     #[test]
@@ -537,12 +1192,13 @@ mod tests {
             "Alice",
             "Lovelace",
             "ada@lovelace.com",
-            "123-321-1233",
+            "201-555-0133",
             "1970-01-01",
+            "US",
         )
         .expect("Contact build");
 
-        let expect_birthday = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).expect("Contact build");
+        let expect_birthday = crate::models::Birthday::new(Some(1970), 1, 1);
 
         assert_eq!(result.birthday, expect_birthday);
     }
@@ -554,11 +1210,13 @@ mod tests {
             "Satoshi",
             "Nakamoto",
             "satoshi@bitcoin.org",
-            "123-321-1234",
+            "201-555-0134",
             invalid_birthday,
+            "US",
         );
 
         let err = contact_result.expect_err("Expected invalid birthday to return an error");
         assert!(err.to_string().contains("invalid"));
+        assert!(matches!(err, Error::InvalidDate(date) if date == invalid_birthday));
     }
 }
@@ -0,0 +1,17 @@
+use chrono::{DateTime, Utc};
+use tabled::Tabled;
+
+use crate::utils;
+
+/// Something that happened with a contact: a call, a coffee, a message
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct Interaction {
+    pub id: i64,
+    #[tabled(skip)]
+    pub contact_id: i64,
+    #[tabled(rename = "when")]
+    #[tabled(display("utils::format_timestamp", utils::DEFAULT_DATE_FORMAT))]
+    pub occurred_at: DateTime<Utc>,
+    pub kind: String,
+    pub note: String,
+}
@@ -0,0 +1,179 @@
+use std::{fmt, str::FromStr};
+
+use tabled::Tabled;
+
+use crate::Error;
+
+/// What kind of address or number an email or phone is
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Label {
+    Home,
+    Work,
+    Mobile,
+    #[default]
+    Other,
+}
+
+impl Label {
+    pub const ALL: [Self; 4] = [Self::Home, Self::Work, Self::Mobile, Self::Other];
+
+    /// Whether a postal address can carry the label; only `mobile` cannot.
+    #[must_use]
+    pub const fn fits_address(self) -> bool {
+        !matches!(self, Self::Mobile)
+    }
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Home => "home",
+            Self::Work => "work",
+            Self::Mobile => "mobile",
+            Self::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Label {
+    type Err = Error;
+
+    fn from_str(value: &str) -> crate::Result<Self> {
+        let name = value.trim().to_lowercase();
+
+        Self::ALL
+            .into_iter()
+            .find(|label| label.as_str() == name)
+            .ok_or_else(|| Error::InvalidLabel(value.to_owned()))
+    }
+}
+
+impl serde::Serialize for Label {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl sqlx::Type<sqlx::Sqlite> for Label {
+    fn type_info() -> sqlx::sqlite::SqliteTypeInfo {
+        <String as sqlx::Type<sqlx::Sqlite>>::type_info()
+    }
+
+    fn compatible(type_info: &sqlx::sqlite::SqliteTypeInfo) -> bool {
+        <String as sqlx::Type<sqlx::Sqlite>>::compatible(type_info)
+    }
+}
+
+impl sqlx::Encode<'_, sqlx::Sqlite> for Label {
+    fn encode_by_ref(
+        &self,
+        buffer: &mut Vec<sqlx::sqlite::SqliteArgumentValue<'_>>,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <&str as sqlx::Encode<sqlx::Sqlite>>::encode(self.as_str(), buffer)
+    }
+}
+
+impl sqlx::Decode<'_, sqlx::Sqlite> for Label {
+    fn decode(value: sqlx::sqlite::SqliteValueRef<'_>) -> Result<Self, sqlx::error::BoxDynError> {
+        let value = <&str as sqlx::Decode<sqlx::Sqlite>>::decode(value)?;
+        Ok(value.parse()?)
+    }
+}
+
+/// An email address or phone number with its label, written `work:a@b.com`
+/// on the command line. Values without a label are `other`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Labeled {
+    pub label: Label,
+    pub value: String,
+}
+
+impl Labeled {
+    #[must_use]
+    pub fn new(label: Label, value: &str) -> Self {
+        Self {
+            label,
+            value: value.trim().to_owned(),
+        }
+    }
+}
+
+impl FromStr for Labeled {
+    type Err = Error;
+
+    /// Reads a leading `label:` when there is one. A prefix of letters that
+    /// is no label is an error, so typos are not saved as part of the value.
+    fn from_str(value: &str) -> crate::Result<Self> {
+        match value.split_once(':') {
+            Some((label, rest)) if label.chars().all(char::is_alphabetic) && !label.is_empty() => {
+                Ok(Self::new(label.parse()?, rest))
+            }
+            _ => Ok(Self::new(Label::Other, value)),
+        }
+    }
+}
+
+/// One of a contact's email addresses
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct Email {
+    #[tabled(skip)]
+    #[serde(skip)]
+    pub contact_id: i64,
+    pub label: Label,
+    pub email: String,
+    #[tabled(rename = "preferred")]
+    #[tabled(display("display_preferred"))]
+    pub is_preferred: bool,
+}
+
+/// One of a contact's phone numbers
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct Phone {
+    #[tabled(skip)]
+    #[serde(skip)]
+    pub contact_id: i64,
+    pub label: Label,
+    pub phone_number: String,
+    /// `phone_number` in E.164 form, or `None` when it is not a valid number
+    #[tabled(skip)]
+    pub phone_e164: Option<String>,
+    #[tabled(rename = "preferred")]
+    #[tabled(display("display_preferred"))]
+    pub is_preferred: bool,
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // signature required by tabled
+fn display_preferred(is_preferred: &bool) -> String {
+    if *is_preferred { "*" } else { "" }.to_owned()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_labeled_values() {
+        assert_eq!(
+            "work:a@b.com".parse::<Labeled>().unwrap(),
+            Labeled::new(Label::Work, "a@b.com")
+        );
+        assert_eq!(
+            "Mobile: +44 7911 123456".parse::<Labeled>().unwrap(),
+            Labeled::new(Label::Mobile, "+44 7911 123456")
+        );
+        assert_eq!(
+            "a@b.com".parse::<Labeled>().unwrap(),
+            Labeled::new(Label::Other, "a@b.com")
+        );
+        assert!(matches!(
+            "wrok:a@b.com".parse::<Labeled>(),
+            Err(Error::InvalidLabel(label)) if label == "wrok"
+        ));
+    }
+}
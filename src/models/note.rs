@@ -0,0 +1,39 @@
+use chrono::{DateTime, Utc};
+use tabled::Tabled;
+
+use crate::utils;
+
+/// A free-form note about a contact, written in Markdown
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct Note {
+    pub id: i64,
+    #[tabled(skip)]
+    pub contact_id: i64,
+    #[tabled(rename = "created")]
+    #[tabled(display("utils::format_timestamp", utils::DEFAULT_DATE_FORMAT))]
+    pub created_at: DateTime<Utc>,
+    #[tabled(rename = "updated")]
+    #[tabled(display("utils::format_timestamp", utils::DEFAULT_DATE_FORMAT))]
+    pub updated_at: DateTime<Utc>,
+    pub body: String,
+}
+
+impl Note {
+    /// Whether the note was changed after it was written.
+    #[must_use]
+    pub fn is_edited(&self) -> bool {
+        self.updated_at > self.created_at
+    }
+}
+
+/// Text a note held before it was edited
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct NoteRevision {
+    #[tabled(skip)]
+    pub note_id: i64,
+    /// When this text was written, either as the note or by an earlier edit
+    #[tabled(rename = "written")]
+    #[tabled(display("utils::format_timestamp", utils::DEFAULT_DATE_FORMAT))]
+    pub written_at: DateTime<Utc>,
+    pub body: String,
+}
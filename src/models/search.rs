@@ -0,0 +1,13 @@
+use super::IndexedContact;
+
+/// A contact found by a full-text search
+#[derive(Debug, PartialEq, Clone, sqlx::FromRow, serde::Serialize)]
+pub struct SearchHit {
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub contact: IndexedContact,
+    /// The best-matching text, with matched terms wrapped in `[` and `]`
+    pub snippet: String,
+    /// BM25 score; lower is a better match
+    pub rank: f64,
+}
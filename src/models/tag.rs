@@ -0,0 +1,8 @@
+use tabled::Tabled;
+
+/// A tag and the number of contacts carrying it
+#[derive(Debug, PartialEq, Eq, Clone, sqlx::FromRow, Tabled, serde::Serialize)]
+pub struct Tag {
+    pub name: String,
+    pub contacts: i64,
+}
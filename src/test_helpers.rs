@@ -49,7 +49,7 @@ mod tests {
 
     #[test]
     fn current_number_of_migrations() {
-        let migrations = 6;
+        let migrations = 18;
 
         let entries = get_migration_entries().unwrap();
 
@@ -1,12 +1,41 @@
 mod config;
 mod csv;
 mod date;
+mod dedupe;
+mod document;
+mod editor;
+mod fuzzy;
+mod ical;
+mod import;
+mod mapping;
+mod phone;
 mod validation;
+mod vcard;
 
 pub use config::{
-    build_database_path, build_database_url, ensure_config_dir, get_config_dir,
-    is_already_initialized,
+    build_book_path, build_config_path, build_database_path, build_database_url, ensure_config_dir,
+    get_config_dir, is_already_initialized, list_books, parse_show_columns, parse_sort_field,
+    resolve_book_name, resolve_database_path, validate_book_name, Config, Setting,
+    CONFIG_FILE_NAME, DEFAULT_BOOK, DEFAULT_DATE_FORMAT, OUTPUT_FORMATS, SHOW_COLUMNS, SORT_FIELDS,
+};
+pub use csv::{process_csv_to_contacts, write_contacts_csv};
+pub use date::{format_birthday, format_date, format_timestamp, parse_timestamp};
+pub use dedupe::{find_duplicates, find_existing, DuplicatePair, DuplicateReason};
+pub use document::{contact_to_document, document_to_update, document_with_error};
+pub use editor::{edit_text, editor_command, DEFAULT_EDITOR};
+pub use fuzzy::{match_contacts_by_name, name_score, resolve_contact_by_name};
+pub use ical::{contacts_to_icalendar, count_calendar_events};
+pub use import::{
+    process_file_to_contacts, read_import_rows, ImportFile, ImportFormat, ImportOptions,
+    ImportReport, ImportRow, OnDuplicate, DEFAULT_TAG_SEPARATOR,
+};
+pub use mapping::{CsvProfile, HeaderMapping};
+pub use phone::{
+    default_region, format_phone_number, is_valid_phone_number_in, normalize_phone_number,
+    PhoneFormat, DEFAULT_REGION,
 };
-pub use csv::process_csv_to_contacts;
-pub use date::default_date;
 pub use validation::{is_not_valid_email, is_not_valid_phone_number};
+pub use vcard::{
+    contact_to_vcard, contacts_to_vcards, process_vcard_to_contacts, vcards_to_contacts,
+    VcardVersion,
+};
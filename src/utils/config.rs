@@ -1,6 +1,55 @@
 use dirs;
+use std::fmt::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
+
+use chrono::NaiveDate;
+
+use super::phone::is_known_region;
+use crate::models::{Cadence, ContactField};
+use crate::Error;
+
+/// Name of the settings file in the config directory
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Name of the book kept in `contacts.db` in the config directory
+pub const DEFAULT_BOOK: &str = "default";
+
+/// Folder of the config directory named books are kept in
+const BOOKS_DIR: &str = "books";
+
+/// Date format used when `date_format` is not set
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Values the `output` setting accepts
+pub const OUTPUT_FORMATS: [&str; 4] = ["table", "json", "jsonl", "csv"];
+
+/// Columns of the `show` table, in the order they are printed
+pub const SHOW_COLUMNS: [ContactField; 7] = [
+    ContactField::Id,
+    ContactField::FirstName,
+    ContactField::LastName,
+    ContactField::DisplayName,
+    ContactField::Email,
+    ContactField::PhoneNumber,
+    ContactField::Birthday,
+];
+
+/// Fields `show` can sort contacts by
+pub const SORT_FIELDS: [ContactField; 11] = [
+    ContactField::Id,
+    ContactField::FirstName,
+    ContactField::LastName,
+    ContactField::DisplayName,
+    ContactField::Email,
+    ContactField::PhoneNumber,
+    ContactField::Birthday,
+    ContactField::CreatedAt,
+    ContactField::UpdatedAt,
+    ContactField::LastSeenAt,
+    ContactField::NextReminderAt,
+];
 
 #[must_use]
 pub fn get_config_dir() -> PathBuf {
@@ -21,11 +70,121 @@ pub fn resolve_config_dir(
     )
 }
 
+#[must_use]
+pub fn build_config_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(CONFIG_FILE_NAME)
+}
+
 #[must_use]
 pub fn build_database_path(config_dir: &Path) -> PathBuf {
     config_dir.join("contacts.db")
 }
 
+/// The database file of the book `name` in `config_dir`: `contacts.db` for
+/// [`DEFAULT_BOOK`], otherwise `books/{name}.db`.
+///
+/// # Errors
+///
+/// Errors with `Error::InvalidBook` when `name` is not a valid book name
+pub fn build_book_path(config_dir: &Path, name: &str) -> crate::Result<PathBuf> {
+    validate_book_name(name)?;
+
+    if name == DEFAULT_BOOK {
+        return Ok(build_database_path(config_dir));
+    }
+
+    Ok(config_dir.join(BOOKS_DIR).join(format!("{name}.db")))
+}
+
+/// Checks that `name` can name a book: letters, digits, `-` and `_` only.
+///
+/// # Errors
+///
+/// Errors with `Error::InvalidBook` for an empty name or any other character
+pub fn validate_book_name(name: &str) -> crate::Result<()> {
+    let invalid = |reason: &str| Error::InvalidBook {
+        name: name.to_owned(),
+        reason: reason.to_owned(),
+    };
+
+    if name.is_empty() {
+        return Err(invalid("a book needs a name"));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(invalid("use only letters, digits, - and _"));
+    }
+
+    Ok(())
+}
+
+/// Names of the books in `config_dir`, sorted, with [`DEFAULT_BOOK`] first
+/// when it has been initialized.
+///
+/// # Errors
+///
+/// This function will return an error if the books folder cannot be read
+pub fn list_books(config_dir: &Path) -> crate::Result<Vec<String>> {
+    let mut books = Vec::new();
+    let books_dir = config_dir.join(BOOKS_DIR);
+
+    if books_dir.is_dir() {
+        for entry in std::fs::read_dir(&books_dir)? {
+            let path = entry?.path();
+            let name = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .filter(|_| path.extension().is_some_and(|extension| extension == "db"))
+                .filter(|name| validate_book_name(name).is_ok() && *name != DEFAULT_BOOK);
+            if let Some(name) = name {
+                books.push(name.to_owned());
+            }
+        }
+    }
+    books.sort();
+
+    if build_database_path(config_dir).exists() {
+        books.insert(0, DEFAULT_BOOK.to_owned());
+    }
+
+    Ok(books)
+}
+
+/// The contact book to open: `db_flag` when given, otherwise the database
+/// of `book`, or of the `book` setting, in the config directory.
+///
+/// # Errors
+///
+/// Errors with `Error::InvalidBook` when the book name is not valid
+pub fn resolve_database_path(
+    db_flag: Option<&Path>,
+    book: Option<&str>,
+    config: &Config,
+) -> crate::Result<PathBuf> {
+    if let Some(db_path) = db_flag {
+        return Ok(db_path.to_path_buf());
+    }
+
+    build_book_path(&get_config_dir(), book.unwrap_or_else(|| config.book()))
+}
+
+/// The name of the contact book [`resolve_database_path`] opens: the file
+/// name of `db_flag` when given, otherwise `book` or the `book` setting.
+#[must_use]
+pub fn resolve_book_name(db_flag: Option<&Path>, book: Option<&str>, config: &Config) -> String {
+    db_flag.map_or_else(
+        || book.unwrap_or_else(|| config.book()).to_owned(),
+        |db_path| {
+            db_path.file_stem().map_or_else(
+                || DEFAULT_BOOK.to_owned(),
+                |stem| stem.to_string_lossy().into_owned(),
+            )
+        },
+    )
+}
+
 #[must_use]
 pub fn build_database_url(path: &Path) -> String {
     format!("sqlite://{}", path.to_string_lossy())
@@ -50,6 +209,319 @@ pub fn ensure_config_dir(target: &Path) -> std::io::Result<()> {
     std::fs::create_dir_all(target)
 }
 
+/// A key of `config.toml`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Setting {
+    /// Region of phone numbers written without a country code
+    Region,
+    /// `strftime` format dates are shown in
+    DateFormat,
+    /// Columns `show` prints in a table
+    ShowColumns,
+    /// Field `show` sorts contacts by
+    ShowSort,
+    /// Keep-in-touch cadence of contacts created without one
+    Cadence,
+    /// Command notes and contacts are edited with
+    Editor,
+    /// Output format used without `--output`
+    Output,
+    /// Book opened without `--book` or `--db`
+    Book,
+}
+
+impl Setting {
+    pub const ALL: [Self; 8] = [
+        Self::Region,
+        Self::DateFormat,
+        Self::ShowColumns,
+        Self::ShowSort,
+        Self::Cadence,
+        Self::Editor,
+        Self::Output,
+        Self::Book,
+    ];
+
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Region => "region",
+            Self::DateFormat => "date_format",
+            Self::ShowColumns => "show_columns",
+            Self::ShowSort => "show_sort",
+            Self::Cadence => "cadence",
+            Self::Editor => "editor",
+            Self::Output => "output",
+            Self::Book => "book",
+        }
+    }
+}
+
+impl FromStr for Setting {
+    type Err = Error;
+
+    fn from_str(key: &str) -> crate::Result<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|setting| setting.as_str() == key.trim())
+            .ok_or_else(|| Error::UnknownSetting(key.to_owned()))
+    }
+}
+
+/// The settings of `config.toml`. Unset settings fall back to the
+/// built-in defaults.
+#[derive(Debug, Default, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    region: Option<String>,
+    date_format: Option<String>,
+    show_columns: Option<Vec<String>>,
+    show_sort: Option<String>,
+    cadence: Option<String>,
+    editor: Option<String>,
+    output: Option<String>,
+    book: Option<String>,
+}
+
+impl Config {
+    /// Reads the settings in `path`. A missing file holds no settings.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - The file cannot be read
+    /// - It is not valid TOML or has a key that is no setting
+    /// - A setting holds an invalid value
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents =
+            std::fs::read_to_string(path).map_err(|error| Error::open_file(path, &error))?;
+
+        let file: Self = toml::from_str(&contents).map_err(|error| {
+            Error::InvalidFile(format!(
+                "Invalid config file {}: {}",
+                path.display(),
+                error.message()
+            ))
+        })?;
+
+        let mut config = Self::default();
+        for setting in Setting::ALL {
+            if let Some(value) = file.get(setting) {
+                config.set(setting, &value)?;
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Writes the settings to `path`, creating its directory first.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file cannot be written
+    pub fn save(&self, path: &Path) -> crate::Result<()> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            ensure_config_dir(parent)?;
+        }
+
+        let contents = toml::to_string(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, contents)?;
+
+        Ok(())
+    }
+
+    /// The value of `setting` as `config get` prints it, or `None` when unset.
+    #[must_use]
+    pub fn get(&self, setting: Setting) -> Option<String> {
+        match setting {
+            Setting::Region => self.region.clone(),
+            Setting::DateFormat => self.date_format.clone(),
+            Setting::ShowColumns => self.show_columns.as_ref().map(|columns| columns.join(",")),
+            Setting::ShowSort => self.show_sort.clone(),
+            Setting::Cadence => self.cadence.clone(),
+            Setting::Editor => self.editor.clone(),
+            Setting::Output => self.output.clone(),
+            Setting::Book => self.book.clone(),
+        }
+    }
+
+    /// Checks `value` and stores it in its canonical spelling. An empty
+    /// value unsets the setting.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the value is not valid for
+    /// the setting: `Error::InvalidSetting`, `Error::UnknownField` for a
+    /// column or sort field, or `Error::InvalidCadence`
+    pub fn set(&mut self, setting: Setting, value: &str) -> crate::Result<()> {
+        let value = value.trim();
+        let invalid = |reason: String| Error::InvalidSetting {
+            key: setting.as_str().to_owned(),
+            reason,
+        };
+
+        if value.is_empty() {
+            match setting {
+                Setting::Region => self.region = None,
+                Setting::DateFormat => self.date_format = None,
+                Setting::ShowColumns => self.show_columns = None,
+                Setting::ShowSort => self.show_sort = None,
+                Setting::Cadence => self.cadence = None,
+                Setting::Editor => self.editor = None,
+                Setting::Output => self.output = None,
+                Setting::Book => self.book = None,
+            }
+            return Ok(());
+        }
+
+        match setting {
+            Setting::Region => {
+                let region = value.to_uppercase();
+                if !is_known_region(&region) {
+                    return Err(invalid(format!(
+                        "{value} is not an ISO 3166 region code such as US or GB"
+                    )));
+                }
+                self.region = Some(region);
+            }
+            Setting::DateFormat => {
+                if !is_date_format(value) {
+                    return Err(invalid(format!(
+                        "{value} is not a date format such as %d/%m/%Y"
+                    )));
+                }
+                self.date_format = Some(value.to_owned());
+            }
+            Setting::ShowColumns => {
+                self.show_columns = Some(
+                    parse_show_columns(value)?
+                        .into_iter()
+                        .map(|field| field.as_str().to_owned())
+                        .collect(),
+                );
+            }
+            Setting::ShowSort => self.show_sort = Some(parse_sort_field(value)?.to_string()),
+            Setting::Cadence => self.cadence = Some(value.parse::<Cadence>()?.to_string()),
+            Setting::Editor => self.editor = Some(value.to_owned()),
+            Setting::Output => {
+                let output = value.to_lowercase();
+                if !OUTPUT_FORMATS.contains(&output.as_str()) {
+                    return Err(invalid(format!(
+                        "{value} is not one of {}",
+                        OUTPUT_FORMATS.join(", ")
+                    )));
+                }
+                self.output = Some(output);
+            }
+            Setting::Book => {
+                validate_book_name(value).map_err(|error| invalid(error.to_string()))?;
+                self.book = Some(value.to_owned());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn region(&self) -> Option<&str> {
+        self.region.as_deref()
+    }
+
+    #[must_use]
+    pub fn date_format(&self) -> &str {
+        self.date_format.as_deref().unwrap_or(DEFAULT_DATE_FORMAT)
+    }
+
+    /// The columns `show` prints, all of them unless configured.
+    #[must_use]
+    pub fn show_columns(&self) -> Vec<ContactField> {
+        self.show_columns
+            .as_ref()
+            .and_then(|columns| parse_show_columns(&columns.join(",")).ok())
+            .unwrap_or_else(|| SHOW_COLUMNS.to_vec())
+    }
+
+    /// The field `show` sorts by, the contact ID unless configured.
+    #[must_use]
+    pub fn show_sort(&self) -> ContactField {
+        self.show_sort
+            .as_deref()
+            .and_then(|field| parse_sort_field(field).ok())
+            .unwrap_or(ContactField::Id)
+    }
+
+    #[must_use]
+    pub fn cadence(&self) -> Option<Cadence> {
+        self.cadence.as_deref()?.parse().ok()
+    }
+
+    #[must_use]
+    pub fn editor(&self) -> Option<&str> {
+        self.editor.as_deref()
+    }
+
+    /// One of [`OUTPUT_FORMATS`], when configured.
+    #[must_use]
+    pub fn output(&self) -> Option<&str> {
+        self.output.as_deref()
+    }
+
+    /// The book opened by default, [`DEFAULT_BOOK`] unless configured.
+    #[must_use]
+    pub fn book(&self) -> &str {
+        self.book.as_deref().unwrap_or(DEFAULT_BOOK)
+    }
+}
+
+/// Reads a comma-separated list of [`SHOW_COLUMNS`].
+///
+/// # Errors
+///
+/// Errors with `Error::UnknownField` for a name that is no column of the
+/// `show` table
+pub fn parse_show_columns(value: &str) -> crate::Result<Vec<ContactField>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            name.parse()
+                .ok()
+                .filter(|field| SHOW_COLUMNS.contains(field))
+                .ok_or_else(|| Error::UnknownField(name.to_owned()))
+        })
+        .collect()
+}
+
+/// Reads one of [`SORT_FIELDS`].
+///
+/// # Errors
+///
+/// Errors with `Error::UnknownField` for a name that is no sortable field
+pub fn parse_sort_field(value: &str) -> crate::Result<ContactField> {
+    value
+        .parse()
+        .ok()
+        .filter(|field| SORT_FIELDS.contains(field))
+        .ok_or_else(|| Error::UnknownField(value.to_owned()))
+}
+
+/// Whether `format` writes a date without error; time fields such as `%H`
+/// do not apply to dates.
+fn is_date_format(format: &str) -> bool {
+    NaiveDate::from_ymd_opt(2026, 10, 18).is_some_and(|date| {
+        let mut written = String::new();
+        write!(written, "{}", date.format(format)).is_ok()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,6 +550,127 @@ mod tests {
         assert_eq!(result, PathBuf::from("/home/user/.config/nbd/contacts.db"));
     }
 
+    #[test]
+    fn should_validate_settings_and_store_them_canonically() {
+        let mut config = Config::default();
+
+        config.set(Setting::Region, "gb").unwrap();
+        config.set(Setting::Cadence, "every 1 month").unwrap();
+        config
+            .set(Setting::ShowColumns, "id, display_name,email")
+            .unwrap();
+
+        assert_eq!(config.region(), Some("GB"));
+        assert_eq!(config.get(Setting::Cadence).as_deref(), Some("monthly"));
+        assert_eq!(
+            config.show_columns(),
+            vec![
+                ContactField::Id,
+                ContactField::DisplayName,
+                ContactField::Email
+            ]
+        );
+        assert!(matches!(
+            config.set(Setting::Region, "XX"),
+            Err(Error::InvalidSetting { key, .. }) if key == "region"
+        ));
+        assert!(matches!(
+            config.set(Setting::DateFormat, "%H:%M"),
+            Err(Error::InvalidSetting { .. })
+        ));
+        assert!(matches!(
+            config.set(Setting::ShowSort, "nickname"),
+            Err(Error::UnknownField(_))
+        ));
+        assert!(matches!(
+            "colour".parse::<Setting>(),
+            Err(Error::UnknownSetting(key)) if key == "colour"
+        ));
+
+        config.set(Setting::Region, "").unwrap();
+        assert_eq!(config.region(), None);
+    }
+
+    #[test]
+    fn should_save_and_load_the_config_file() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = build_config_path(&temp.path().join("nbd"));
+        assert_eq!(Config::load(&path).unwrap(), Config::default());
+
+        let mut config = Config::default();
+        config.set(Setting::DateFormat, "%d/%m/%Y").unwrap();
+        config.set(Setting::Output, "JSON").unwrap();
+        config.save(&path).unwrap();
+
+        let loaded = Config::load(&path).unwrap();
+        assert_eq!(loaded, config);
+        assert_eq!(loaded.date_format(), "%d/%m/%Y");
+        assert_eq!(loaded.output(), Some("json"));
+
+        std::fs::write(&path, "colour = \"blue\"\n").unwrap();
+        assert!(matches!(Config::load(&path), Err(Error::InvalidFile(_))));
+    }
+
+    #[test]
+    fn should_prefer_the_db_flag_to_the_config_dir() {
+        let mut config = Config::default();
+        config.set(Setting::Book, "volunteers").unwrap();
+
+        let result =
+            resolve_database_path(Some(Path::new("/tmp/work.db")), Some("family"), &config);
+
+        assert_eq!(result.unwrap(), PathBuf::from("/tmp/work.db"));
+    }
+
+    #[test]
+    fn should_name_the_book_that_is_opened() {
+        let mut config = Config::default();
+
+        assert_eq!(resolve_book_name(None, None, &config), DEFAULT_BOOK);
+
+        config.set(Setting::Book, "volunteers").unwrap();
+        assert_eq!(resolve_book_name(None, None, &config), "volunteers");
+        assert_eq!(resolve_book_name(None, Some("family"), &config), "family");
+        assert_eq!(
+            resolve_book_name(Some(Path::new("/tmp/work.db")), Some("family"), &config),
+            "work"
+        );
+    }
+
+    #[test]
+    fn should_keep_named_books_in_the_books_folder() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let config_dir = temp.path();
+
+        assert_eq!(
+            build_book_path(config_dir, DEFAULT_BOOK).unwrap(),
+            config_dir.join("contacts.db")
+        );
+        assert_eq!(
+            build_book_path(config_dir, "volunteers").unwrap(),
+            config_dir.join("books/volunteers.db")
+        );
+        assert!(matches!(
+            build_book_path(config_dir, "../work"),
+            Err(Error::InvalidBook { name, .. }) if name == "../work"
+        ));
+
+        std::fs::create_dir_all(config_dir.join("books")).unwrap();
+        for file in [
+            "contacts.db",
+            "books/work.db",
+            "books/family.db",
+            "books/notes.txt",
+        ] {
+            std::fs::write(config_dir.join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            list_books(config_dir).unwrap(),
+            vec![DEFAULT_BOOK, "family", "work"]
+        );
+    }
+
     #[test]
     fn ensure_config_dir_creates_dir_when_missing() {
         let temp = tempfile::TempDir::new().unwrap();
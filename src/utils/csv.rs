@@ -1,8 +1,9 @@
-use crate::utils;
-use csv::Reader;
-use std::path::Path;
+use crate::{utils, Error, RowError};
+use csv::{Reader, StringRecord, Writer};
+use std::{io, path::Path};
 
-use crate::models::OptionalContact;
+use super::{mapping::LABELED_COLUMNS, HeaderMapping, ImportFile, ImportRow};
+use crate::models::{ContactField, IndexedContact, Label, Labeled, OptionalContact, PostalAddress};
 
 /// # Errors
 ///
@@ -11,65 +12,312 @@ use crate::models::OptionalContact;
 /// - Is an empty CSV
 /// - Fails to open the file
 /// - Is an invalid CSV
-pub fn process_csv_to_contacts(filename: &str) -> anyhow::Result<Vec<OptionalContact>> {
+/// - A row holds an invalid value, reported as [`Error::CsvRow`]
+///
+/// Phone numbers without a country code are read as belonging to `region`.
+pub fn process_csv_to_contacts(
+    filename: &str,
+    region: &str,
+) -> crate::Result<Vec<OptionalContact>> {
     let path = Path::new(filename);
 
     validate_csv_extension(path)?;
+
+    read_csv_contacts(
+        path,
+        &HeaderMapping::default(),
+        utils::DEFAULT_TAG_SEPARATOR,
+        region,
+    )
+}
+
+/// Reads contacts from a CSV file regardless of its extension.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - Is an empty CSV
+/// - Fails to open the file
+/// - Is an invalid CSV
+/// - A row holds an invalid value, reported as [`Error::CsvRow`]
+pub(super) fn read_csv_contacts(
+    path: &Path,
+    mapping: &HeaderMapping,
+    tag_separator: &str,
+    region: &str,
+) -> crate::Result<Vec<OptionalContact>> {
     validate_csv_file(path)?;
     validate_csv_format(path)?;
 
-    csv_to_contacts(path)
+    csv_to_contacts(path, mapping, tag_separator, region)
 }
 
-fn validate_csv_extension(path: &Path) -> anyhow::Result<()> {
+fn validate_csv_extension(path: &Path) -> crate::Result<()> {
     match path.extension().and_then(|ext| ext.to_str()) {
         Some("csv") => Ok(()),
-        _ => Err(anyhow::anyhow!("File must have .csv extension")),
+        _ => Err(Error::InvalidFile(
+            "File must have .csv extension".to_owned(),
+        )),
     }
 }
 
-fn validate_csv_file(path: &Path) -> anyhow::Result<()> {
-    let metadata = std::fs::metadata(path)
-        .map_err(|_| anyhow::anyhow!("Failed to open file: {}", path.display()))?;
+fn validate_csv_file(path: &Path) -> crate::Result<()> {
+    let metadata = std::fs::metadata(path).map_err(|error| Error::open_file(path, &error))?;
 
     if metadata.len() == 0 {
-        return Err(anyhow::anyhow!("CSV file is empty"));
+        return Err(Error::InvalidFile("CSV file is empty".to_owned()));
     }
     Ok(())
 }
 
-fn validate_csv_format(path: &Path) -> anyhow::Result<()> {
-    let reader = Reader::from_path(path)?;
+fn validate_csv_format(path: &Path) -> crate::Result<()> {
+    let reader = Reader::from_path(path).map_err(csv_error)?;
     if !reader.into_records().all(|result| result.is_ok()) {
-        return Err(anyhow::anyhow!("Invalid CSV format"));
+        return Err(Error::InvalidFile("Invalid CSV format".to_owned()));
     }
     Ok(())
 }
 
-fn csv_to_contacts(path: &Path) -> anyhow::Result<Vec<OptionalContact>> {
-    let mut reader = Reader::from_path(path)?;
+fn csv_error(error: csv::Error) -> Error {
+    if error.is_io_error() {
+        Error::Io(error.into())
+    } else {
+        Error::InvalidFile(format!("Invalid CSV format: {error}"))
+    }
+}
 
-    let contacts: Result<Vec<OptionalContact>, csv::Error> = reader.deserialize().collect();
-    let contacts = contacts?;
+fn csv_to_contacts(
+    path: &Path,
+    mapping: &HeaderMapping,
+    tag_separator: &str,
+    region: &str,
+) -> crate::Result<Vec<OptionalContact>> {
+    csv_to_rows(path, mapping, tag_separator, region)?
+        .rows
+        .into_iter()
+        .map(|row| {
+            row.map(|row| row.contact).map_err(|error| Error::CsvRow {
+                line: error.line,
+                field: error.column,
+            })
+        })
+        .collect()
+}
 
-    let mut valid_contacts: Vec<OptionalContact> = Vec::new();
+/// Reads every row of a CSV file, keeping rows with invalid values as
+/// [`RowError`]s instead of stopping at the first one.
+///
+/// Columns are read into fields through `mapping`, and the headers of the
+/// columns it leaves out are listed in the result.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - Is an empty CSV
+/// - Fails to open the file
+/// - Is an invalid CSV
+pub(super) fn read_csv_rows(
+    path: &Path,
+    mapping: &HeaderMapping,
+    tag_separator: &str,
+    region: &str,
+) -> crate::Result<ImportFile> {
+    validate_csv_file(path)?;
+    validate_csv_format(path)?;
 
-    for contact in &contacts {
-        if let Some(phone_number) = &contact.phone_number {
-            if utils::is_not_valid_phone_number(phone_number) {
-                return Err(anyhow::anyhow!("Invalid Phone Number"));
-            }
+    csv_to_rows(path, mapping, tag_separator, region)
+}
+
+fn csv_to_rows(
+    path: &Path,
+    mapping: &HeaderMapping,
+    tag_separator: &str,
+    region: &str,
+) -> crate::Result<ImportFile> {
+    let mut reader = Reader::from_path(path).map_err(csv_error)?;
+    let mapped = mapping.resolve(reader.headers().map_err(csv_error)?);
+    let headers = mapped.field_names();
+    let tags_column = headers.iter().position(|header| header == "tags");
+
+    let mut rows = Vec::new();
+
+    for record in reader.records() {
+        let record = record.map_err(csv_error)?;
+        let line = record.position().map_or(0, csv::Position::line);
+        let fields = mapped.map_record(&record, mapping, tag_separator);
+
+        rows.push(
+            record_to_contact(&headers, &fields, line, tags_column, tag_separator, region)
+                .map(|contact| ImportRow { line, contact })
+                .map_err(|error| RowError {
+                    column: mapped.source_header(&error.column, &record),
+                    ..error
+                }),
+        );
+    }
+
+    Ok(ImportFile {
+        rows,
+        unmapped_columns: mapped.unmapped,
+    })
+}
+
+fn record_to_contact(
+    headers: &StringRecord,
+    record: &StringRecord,
+    line: u64,
+    tags_column: Option<usize>,
+    tag_separator: &str,
+    region: &str,
+) -> Result<OptionalContact, RowError> {
+    let row_error = |column: &str, value: &str| RowError {
+        line,
+        column: column.to_owned(),
+        reason: format!("{value:?} is not a valid {}", column.replace('_', " ")),
+    };
+
+    let mut contact: OptionalContact = record.deserialize(Some(headers)).map_err(|_| {
+        find_invalid_field(headers, record).map_or_else(
+            || RowError {
+                line,
+                column: "row".to_owned(),
+                reason: "the row does not match the header".to_owned(),
+            },
+            |(column, value)| row_error(column, value),
+        )
+    })?;
+
+    if let Some(phone_number) = &contact.phone_number {
+        if utils::is_not_valid_phone_number(phone_number, region) {
+            return Err(row_error("phone_number", phone_number));
+        }
+    }
+
+    if let Some(email) = &contact.email {
+        if utils::is_not_valid_email(email) {
+            return Err(row_error("email", email));
         }
+    }
+
+    if let Some(tags) = tags_column.and_then(|index| record.get(index)) {
+        contact.tags = split_tags(tags, tag_separator);
+    }
 
-        if let Some(email) = &contact.email {
-            if utils::is_not_valid_email(email) {
-                return Err(anyhow::anyhow!("Invalid Email"));
+    for (column, value) in headers.iter().zip(record.iter()) {
+        let value = value.trim();
+        if value.is_empty() || !LABELED_COLUMNS.contains(&column) {
+            continue;
+        }
+
+        let (label, kind) = column.split_once('_').unwrap_or_default();
+        let label = label.parse::<Label>().unwrap_or_default();
+
+        if kind == "email" {
+            if utils::is_not_valid_email(value) {
+                return Err(row_error(column, value));
+            }
+            contact.emails.push(Labeled::new(label, value));
+        } else {
+            if utils::is_not_valid_phone_number(value, region) {
+                return Err(row_error(column, value));
             }
+            contact.phones.push(Labeled::new(label, value));
         }
+    }
 
-        valid_contacts.push(contact.clone());
+    let address_part = |column: &str| {
+        headers
+            .iter()
+            .position(|header| header == column)
+            .and_then(|index| record.get(index))
+            .map(|value| value.trim().to_owned())
+            .unwrap_or_default()
+    };
+    let address = PostalAddress {
+        // Address books use labels of their own, which are kept as `other`
+        label: PostalAddress::parse_label(&address_part("address_label")).unwrap_or_default(),
+        street: address_part("street"),
+        locality: address_part("locality"),
+        region: address_part("region"),
+        postal_code: address_part("postal_code"),
+        country: address_part("country"),
+    };
+    if !address.is_empty() {
+        contact.addresses.push(address);
     }
-    Ok(valid_contacts)
+
+    contact.external_id = contact
+        .external_id
+        .map(|external_id| external_id.trim().to_owned())
+        .filter(|external_id| !external_id.is_empty());
+
+    Ok(contact)
+}
+
+fn split_tags(tags: &str, separator: &str) -> Vec<String> {
+    tags.split(separator)
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Finds the first column whose value alone fails to deserialize, since
+/// errors raised by field types such as dates do not carry their column.
+fn find_invalid_field<'a>(
+    headers: &'a StringRecord,
+    record: &'a StringRecord,
+) -> Option<(&'a str, &'a str)> {
+    headers
+        .iter()
+        .zip(record.iter())
+        .find_map(|(header, value)| {
+            let single_header = StringRecord::from(vec![header]);
+            let single_value = StringRecord::from(vec![value]);
+
+            single_value
+                .deserialize::<OptionalContact>(Some(&single_header))
+                .is_err()
+                .then_some((header, value))
+        })
+}
+
+/// Writes contacts as CSV, one column per entry of `columns`.
+///
+/// Every column of the `contacts` table is written when `columns` is empty.
+/// The headers are the ones [`process_csv_to_contacts`] reads, so the output
+/// can be re-imported. Only `id` and `updated_at` are not read back: an
+/// import gives contacts new IDs and stamps them with the time it ran.
+///
+/// # Errors
+///
+/// This function will return an error if writing to `writer` fails
+pub fn write_contacts_csv<W: io::Write>(
+    contacts: &[IndexedContact],
+    columns: &[ContactField],
+    writer: W,
+) -> crate::Result<()> {
+    let columns = if columns.is_empty() {
+        &ContactField::ALL[..]
+    } else {
+        columns
+    };
+
+    let mut writer = Writer::from_writer(writer);
+
+    writer
+        .write_record(columns.iter().map(|column| column.as_str()))
+        .map_err(csv_error)?;
+
+    for contact in contacts {
+        writer
+            .write_record(columns.iter().map(|column| column.value(contact)))
+            .map_err(csv_error)?;
+    }
+
+    writer.flush()?;
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -84,7 +332,7 @@ mod tests {
 
     #[test]
     fn should_return_error_when_invalid_extension() {
-        let invalid_call = process_csv_to_contacts("not_a_csv.txt");
+        let invalid_call = process_csv_to_contacts("not_a_csv.txt", utils::DEFAULT_REGION);
 
         assert!(invalid_call.is_err());
     }
@@ -95,7 +343,7 @@ mod tests {
         writeln!(temp_csv, "first_name\nAlice").expect("Write mock csv");
 
         let temp_csv = temp_csv.path().to_str().expect("The path to the CSV");
-        let result = process_csv_to_contacts(temp_csv);
+        let result = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION);
 
         assert!(result.is_ok());
     }
@@ -105,7 +353,7 @@ mod tests {
     fn should_return_error_when_file_not_found() {
         let non_existent_file_path = "non_existent.csv";
 
-        let result = process_csv_to_contacts(non_existent_file_path);
+        let result = process_csv_to_contacts(non_existent_file_path, utils::DEFAULT_REGION);
 
         assert!(result.is_err());
         assert_eq!(
@@ -120,7 +368,7 @@ mod tests {
         let temp_csv = NamedTempFile::with_suffix(".csv").unwrap();
 
         let temp_csv = temp_csv.path().to_str().unwrap();
-        let result = process_csv_to_contacts(temp_csv);
+        let result = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION);
 
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().to_string(), "CSV file is empty");
@@ -131,11 +379,11 @@ mod tests {
     fn should_error_if_invalid_csv_format() {
         let mut temp_csv = NamedTempFile::with_suffix(".csv").unwrap();
 
-        let malformed_csv = "first_name,phone_number,email\nAlice,1234567890";
+        let malformed_csv = "first_name,phone_number,email\nAlice,2015550123";
         write!(temp_csv, "{malformed_csv}").unwrap();
 
         let temp_csv = temp_csv.path().to_str().unwrap();
-        let result = process_csv_to_contacts(temp_csv);
+        let result = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION);
 
         match result {
             Ok(_) => panic!("Expected invalid CSV, but was valid"),
@@ -152,10 +400,13 @@ mod tests {
         write!(temp_csv, "{malformed_csv}").unwrap();
 
         let temp_csv = temp_csv.path().to_str().unwrap();
-        let result = process_csv_to_contacts(temp_csv);
+        let result = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION);
 
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err().to_string(), "Invalid Phone Number");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid phone_number on line 2"
+        );
     }
 
     #[allow(clippy::unwrap_used)]
@@ -168,10 +419,13 @@ mod tests {
 
         let temp_csv_path = temp_csv.path().to_str().unwrap();
 
-        let contacts = process_csv_to_contacts(temp_csv_path);
+        let contacts = process_csv_to_contacts(temp_csv_path, utils::DEFAULT_REGION);
 
         assert!(contacts.is_err());
-        assert_eq!(contacts.unwrap_err().to_string(), "Invalid Email");
+        assert!(matches!(
+            contacts.unwrap_err(),
+            Error::CsvRow { line: 2, field } if field == "email"
+        ));
     }
 
     #[allow(clippy::unwrap_used)]
@@ -179,34 +433,58 @@ mod tests {
     fn should_read_csv_with_multiple_rows() -> anyhow::Result<()> {
         let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
         let three_contacts =
-            "first_name,phone_number\nAlice,1234567890\nBob,0989878721\nCharlie,1989878721";
+            "first_name,phone_number\nAlice,2015550123\nBob,2025550187\nCharlie,3035550187";
 
         writeln!(temp_csv, "{three_contacts}")?;
 
         let temp_csv = temp_csv.path().to_str().unwrap();
-        let result = process_csv_to_contacts(temp_csv)?;
+        let result = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION)?;
 
         assert_eq!(result.len(), 3);
 
         Ok(())
     }
 
+    #[test]
+    fn should_report_line_and_column_of_unparsable_value() -> anyhow::Result<()> {
+        let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
+        writeln!(
+            temp_csv,
+            "first_name,birthday\nAlice,1970-01-01\nBob,1970-13-32"
+        )?;
+
+        let temp_csv = temp_csv.path().to_str().unwrap();
+        let result = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Invalid birthday on line 3"
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn should_return_contact_when_given_csv() -> anyhow::Result<()> {
         let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
-        let alice_firstname_and_phone = "first_name,phone_number\nAlice,1234567890";
+        let alice_firstname_and_phone = "first_name,phone_number\nAlice,2015550123";
 
         writeln!(temp_csv, "{alice_firstname_and_phone}")?;
 
         let temp_csv = temp_csv.path();
-        let contacts = csv_to_contacts(temp_csv);
+        let contacts = csv_to_contacts(
+            temp_csv,
+            &HeaderMapping::default(),
+            utils::DEFAULT_TAG_SEPARATOR,
+            utils::DEFAULT_REGION,
+        );
 
         let alice = contacts.unwrap();
         let alice = alice.first();
 
         let expected_contact = OptionalContact {
             first_name: Some("Alice".to_string()),
-            phone_number: Some("1234567890".to_string()),
+            phone_number: Some("2015550123".to_string()),
             ..OptionalContact::default()
         };
 
@@ -215,31 +493,127 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn should_read_tags_column() -> anyhow::Result<()> {
+        let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
+        writeln!(temp_csv, "first_name,tags\nAlice,work; climbing;\nBob,")?;
+
+        let temp_csv = temp_csv.path().to_str().unwrap();
+        let contacts = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().tags,
+            vec!["work".to_string(), "climbing".to_string()]
+        );
+        assert!(contacts.get(1).unwrap().tags.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_labelled_email_and_phone_columns() -> anyhow::Result<()> {
+        let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
+        writeln!(
+            temp_csv,
+            "first_name,email,work_email,Mobile_Phone\n\
+             Alice,alice@home.com,alice@work.com,201-555-0123\n\
+             Bob,,bob@work,"
+        )?;
+
+        let contacts = csv_to_contacts(
+            temp_csv.path(),
+            &HeaderMapping::default(),
+            utils::DEFAULT_TAG_SEPARATOR,
+            utils::DEFAULT_REGION,
+        );
+
+        assert!(matches!(
+            contacts,
+            Err(Error::CsvRow { line: 3, field }) if field == "work_email"
+        ));
+
+        let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
+        writeln!(
+            temp_csv,
+            "first_name,email,work_email,Mobile_Phone\n\
+             Alice,alice@home.com,alice@work.com,201-555-0123"
+        )?;
+
+        let contacts =
+            process_csv_to_contacts(temp_csv.path().to_str().unwrap(), utils::DEFAULT_REGION)?;
+        let alice = contacts.first().unwrap();
+
+        assert_eq!(alice.email.as_deref(), Some("alice@home.com"));
+        assert_eq!(
+            alice.emails,
+            vec![Labeled::new(Label::Work, "alice@work.com")]
+        );
+        assert_eq!(
+            alice.phones,
+            vec![Labeled::new(Label::Mobile, "201-555-0123")]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_address_columns() -> anyhow::Result<()> {
+        let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
+        writeln!(
+            temp_csv,
+            "first_name,address_label,street,locality,postal_code\n\
+             Alice,Home,1 Main St,Springfield,12345\n\
+             Bob,Cottage,,,"
+        )?;
+
+        let contacts =
+            process_csv_to_contacts(temp_csv.path().to_str().unwrap(), utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().addresses,
+            vec![PostalAddress {
+                label: Label::Home,
+                street: "1 Main St".to_string(),
+                locality: "Springfield".to_string(),
+                postal_code: "12345".to_string(),
+                ..PostalAddress::default()
+            }]
+        );
+        assert!(contacts.get(1).unwrap().addresses.is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn should_return_multiple_contacts_when_given_csv() -> anyhow::Result<()> {
         let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
         let three_contacts =
-            "first_name,phone_number\nAlice,1234567890\nBob,0989878721\nCharlie,1989878721";
+            "first_name,phone_number\nAlice,2015550123\nBob,2025550187\nCharlie,3035550187";
 
         writeln!(temp_csv, "{three_contacts}")?;
 
         let temp_csv = temp_csv.path();
-        let contacts = csv_to_contacts(temp_csv)?;
+        let contacts = csv_to_contacts(
+            temp_csv,
+            &HeaderMapping::default(),
+            utils::DEFAULT_TAG_SEPARATOR,
+            utils::DEFAULT_REGION,
+        )?;
 
         let expected_contacts: Vec<OptionalContact> = vec![
             OptionalContact {
                 first_name: Some("Alice".to_string()),
-                phone_number: Some("1234567890".to_string()),
+                phone_number: Some("2015550123".to_string()),
                 ..OptionalContact::default()
             },
             OptionalContact {
                 first_name: Some("Bob".to_string()),
-                phone_number: Some("0989878721".to_string()),
+                phone_number: Some("2025550187".to_string()),
                 ..OptionalContact::default()
             },
             OptionalContact {
                 first_name: Some("Charlie".to_string()),
-                phone_number: Some("1989878721".to_string()),
+                phone_number: Some("3035550187".to_string()),
                 ..OptionalContact::default()
             },
         ];
@@ -251,8 +625,79 @@ mod tests {
         Ok(())
     }
 
+    fn example_indexed_contacts() -> Vec<IndexedContact> {
+        let mut ada = crate::models::Contact::new(
+            "Ada",
+            "Lovelace",
+            "ada@lovelace.com",
+            "201-555-0133",
+            "1815-12-10",
+            "US",
+        )
+        .expect("Example contact");
+        ada.starred = true;
+        ada.frequency = "monthly".parse().ok();
+
+        let alice = crate::models::Contact::new("Alice", "Liddell", "", "", "", "US")
+            .expect("Example contact");
+
+        vec![
+            IndexedContact {
+                id: 1,
+                contact: ada,
+            },
+            IndexedContact {
+                id: 2,
+                contact: alice,
+            },
+        ]
+    }
+
+    #[test]
+    fn should_write_selected_columns() -> anyhow::Result<()> {
+        let mut output = Vec::new();
+
+        write_contacts_csv(
+            &example_indexed_contacts(),
+            &[ContactField::FirstName, ContactField::Birthday],
+            &mut output,
+        )?;
+
+        assert_eq!(
+            String::from_utf8(output)?,
+            "first_name,birthday\nAda,1815-12-10\nAlice,\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_round_trip_every_column_through_import() -> anyhow::Result<()> {
+        let contacts = example_indexed_contacts();
+        let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
+
+        write_contacts_csv(&contacts, &[], &mut temp_csv)?;
+
+        let temp_csv = temp_csv.path().to_str().unwrap();
+        let imported = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION)?;
+        let ada = imported.first().unwrap();
+
+        assert_eq!(imported.len(), 2);
+        assert_eq!(ada.first_name, Some("Ada".to_string()));
+        assert_eq!(ada.email, Some("ada@lovelace.com".to_string()));
+        assert_eq!(
+            ada.birthday,
+            crate::models::Birthday::new(Some(1815), 12, 10)
+        );
+        assert_eq!(ada.starred, Some(true));
+        assert_eq!(ada.is_archived, Some(false));
+        assert_eq!(ada.frequency, "monthly".parse().ok());
+        assert_eq!(imported.get(1).unwrap().birthday, None);
+
+        Ok(())
+    }
+
     #[test]
-    #[ignore = "deferring feature"]
     fn should_accept_csv_that_has_iso8601_birthday() -> anyhow::Result<()> {
         let mut temp_csv = NamedTempFile::with_suffix(".csv")?;
         let first_name_iso8601_birthday = "first_name,birthday\nAlice,1987-07-11T00:00:00Z";
@@ -260,7 +705,7 @@ mod tests {
         writeln!(temp_csv, "{first_name_iso8601_birthday}")?;
 
         let temp_csv = temp_csv.path().to_str().expect("Path to csv");
-        let result = process_csv_to_contacts(temp_csv);
+        let result = process_csv_to_contacts(temp_csv, utils::DEFAULT_REGION);
 
         println!("{result:?}");
 
@@ -1,8 +1,96 @@
-use chrono::NaiveDate;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
 
-/// # Panics
-/// Panics if a strange date is put in with `NaiveDate::from_ymd_opt`
+use crate::{models::Birthday, Error};
+
+/// Writes a date for people to read, in `date_format`, such as the
+/// `date_format` setting.
+#[must_use]
+pub fn format_date(date: NaiveDate, date_format: &str) -> String {
+    date.format(date_format).to_string()
+}
+
+/// Writes a point in time for people to read: the local date in
+/// `date_format`, then the time of day.
 #[must_use]
-pub fn default_date() -> NaiveDate {
-    NaiveDate::from_ymd_opt(1, 1, 1).unwrap_or_default()
+pub fn format_timestamp(timestamp: &DateTime<Utc>, date_format: &str) -> String {
+    let local = timestamp.with_timezone(&Local);
+
+    format!(
+        "{} {}",
+        format_date(local.date_naive(), date_format),
+        local.format("%H:%M")
+    )
+}
+
+/// Writes a birthday like [`format_date`]. Birthdays without a year keep
+/// their `--MM-DD` form, as date formats need one.
+#[must_use]
+pub fn format_birthday(birthday: Birthday, date_format: &str) -> String {
+    birthday.date().map_or_else(
+        || birthday.to_string(),
+        |date| format_date(date, date_format),
+    )
+}
+
+/// Parses a user-supplied point in time.
+///
+/// Accepts RFC 3339 (`2026-10-01T18:30:00Z`), or a local `2026-10-01 18:30`
+/// or bare `2026-10-01`, which is read as local midnight.
+///
+/// # Errors
+///
+/// Errors with `Error::InvalidDate` when the value matches none of these forms
+pub fn parse_timestamp(value: &str) -> crate::Result<DateTime<Utc>> {
+    let value = value.trim();
+
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+        .ok()
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| Error::InvalidDate(value.to_owned()))?;
+
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+        .ok_or_else(|| Error::InvalidDate(value.to_owned()))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_rfc3339_timestamps() {
+        let timestamp = parse_timestamp("2026-10-01T18:30:00+02:00").unwrap();
+
+        assert_eq!(
+            timestamp,
+            Utc.with_ymd_and_hms(2026, 10, 1, 16, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn should_read_bare_dates_as_local_midnight() {
+        let timestamp = parse_timestamp("2026-10-01").unwrap();
+        let expected = Local.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap();
+
+        assert_eq!(timestamp, expected.with_timezone(&Utc));
+    }
+
+    #[test]
+    fn should_reject_unknown_formats() {
+        let result = parse_timestamp("last tuesday");
+
+        assert!(matches!(result, Err(Error::InvalidDate(value)) if value == "last tuesday"));
+    }
 }
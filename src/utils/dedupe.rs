@@ -0,0 +1,291 @@
+use std::fmt;
+
+use super::fuzzy;
+use crate::models::{IndexedContact, OptionalContact};
+
+/// Lowest score, out of 100, at which a pair is reported as a duplicate
+const DUPLICATE_THRESHOLD: usize = 40;
+/// Lowest name similarity that counts towards a duplicate
+const SIMILAR_NAME_THRESHOLD: usize = 85;
+/// Fewest digits a phone number needs before it is compared
+const MIN_PHONE_DIGITS: usize = 7;
+
+/// Why two contacts look like the same person
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DuplicateReason {
+    SameEmail,
+    SamePhone,
+    SameName,
+    SimilarName,
+}
+
+impl DuplicateReason {
+    const fn weight(self) -> usize {
+        match self {
+            Self::SameEmail => 50,
+            Self::SamePhone | Self::SameName => 40,
+            Self::SimilarName => 25,
+        }
+    }
+}
+
+impl fmt::Display for DuplicateReason {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(match self {
+            Self::SameEmail => "same email",
+            Self::SamePhone => "same phone",
+            Self::SameName => "same name",
+            Self::SimilarName => "similar name",
+        })
+    }
+}
+
+/// Two contacts that probably describe the same person; `first` has the
+/// lower ID.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DuplicatePair<'a> {
+    pub first: &'a IndexedContact,
+    pub second: &'a IndexedContact,
+    /// How sure the match is, from 40 to 100
+    pub score: usize,
+    pub reasons: Vec<DuplicateReason>,
+}
+
+/// Pairs up contacts that share a normalised email or phone number, or have
+/// the same or nearly the same name. Most likely duplicates come first.
+#[must_use]
+pub fn find_duplicates(contacts: &[IndexedContact]) -> Vec<DuplicatePair<'_>> {
+    let mut sorted: Vec<&IndexedContact> = contacts.iter().collect();
+    sorted.sort_by_key(|indexed| indexed.id);
+
+    let mut pairs: Vec<DuplicatePair> = sorted
+        .iter()
+        .enumerate()
+        .flat_map(|(position, first)| {
+            sorted
+                .iter()
+                .skip(position.saturating_add(1))
+                .filter_map(move |second| compare(first, second))
+        })
+        .collect();
+
+    pairs.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.first.id.cmp(&b.first.id))
+            .then(a.second.id.cmp(&b.second.id))
+    });
+
+    pairs
+}
+
+/// Finds the contact an imported row describes.
+///
+/// A row with an `external_id` only matches the contact with that
+/// [`IndexedContact::uid`], so re-importing an export finds its contacts.
+/// Otherwise the row matches on a normalised email or phone number, or on the
+/// same name when its email and phone do not contradict the contact's.
+#[must_use]
+pub fn find_existing<'a>(
+    row: &OptionalContact,
+    contacts: &'a [IndexedContact],
+) -> Option<&'a IndexedContact> {
+    if let Some(external_id) = &row.external_id {
+        return contacts
+            .iter()
+            .find(|indexed| indexed.uid() == *external_id);
+    }
+
+    let email = normalize_email(row.email.as_deref().unwrap_or_default());
+    let phone_number = row.phone_number.as_deref().unwrap_or_default();
+    let display_name = row.display_name.clone().unwrap_or_else(|| {
+        format!(
+            "{} {}",
+            row.first_name.as_deref().unwrap_or_default(),
+            row.last_name.as_deref().unwrap_or_default()
+        )
+    });
+
+    let same_email = |indexed: &&IndexedContact| {
+        !email.is_empty() && normalize_email(&indexed.contact.email) == email
+    };
+    let same_name_without_conflict = |indexed: &&IndexedContact| {
+        let contact = &indexed.contact;
+        let other_email = normalize_email(&contact.email);
+        let other_phone = &contact.phone_number;
+
+        fuzzy::name_similarity(&display_name, &contact.display_name) == 100
+            && (email.is_empty() || other_email.is_empty() || email == other_email)
+            && (phone_number.is_empty()
+                || other_phone.is_empty()
+                || same_phone(phone_number, other_phone))
+    };
+
+    contacts
+        .iter()
+        .find(same_email)
+        .or_else(|| {
+            contacts
+                .iter()
+                .find(|indexed| same_phone(phone_number, &indexed.contact.phone_number))
+        })
+        .or_else(|| contacts.iter().find(same_name_without_conflict))
+}
+
+fn compare<'a>(first: &'a IndexedContact, second: &'a IndexedContact) -> Option<DuplicatePair<'a>> {
+    let (a, b) = (&first.contact, &second.contact);
+    let mut reasons = Vec::new();
+
+    let email = normalize_email(&a.email);
+    if !email.is_empty() && email == normalize_email(&b.email) {
+        reasons.push(DuplicateReason::SameEmail);
+    }
+
+    if same_phone(&a.phone_number, &b.phone_number) {
+        reasons.push(DuplicateReason::SamePhone);
+    }
+
+    match fuzzy::name_similarity(&a.display_name, &b.display_name) {
+        100 => reasons.push(DuplicateReason::SameName),
+        similarity if similarity >= SIMILAR_NAME_THRESHOLD => {
+            reasons.push(DuplicateReason::SimilarName);
+        }
+        _ => {}
+    }
+
+    let score = reasons
+        .iter()
+        .map(|reason| reason.weight())
+        .sum::<usize>()
+        .min(100);
+
+    (score >= DUPLICATE_THRESHOLD).then_some(DuplicatePair {
+        first,
+        second,
+        score,
+        reasons,
+    })
+}
+
+fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+/// Compares phone numbers on their digits, allowing one to carry a country
+/// code the other leaves out.
+fn same_phone(a: &str, b: &str) -> bool {
+    let digits = |phone: &str| -> String { phone.chars().filter(char::is_ascii_digit).collect() };
+    let (a, b) = (digits(a), digits(b));
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+
+    shorter.len() >= MIN_PHONE_DIGITS && longer.ends_with(&shorter)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::models::Contact;
+
+    fn indexed(id: i64, first_name: &str, email: &str, phone_number: &str) -> IndexedContact {
+        let mut contact = Contact::builder()
+            .first_name(first_name)
+            .last_name("Carroll")
+            .build()
+            .unwrap();
+        contact.email = email.to_owned();
+        contact.phone_number = phone_number.to_owned();
+
+        IndexedContact { id, contact }
+    }
+
+    #[test]
+    fn should_match_emails_and_phones_after_normalising() {
+        let contacts = vec![
+            indexed(1, "Lewis", "Lewis@Wonderland.com ", "+1 (212) 555-0177"),
+            indexed(2, "Charles", "lewis@wonderland.com", "212.555.0177"),
+        ];
+
+        let pairs = find_duplicates(&contacts);
+
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(
+            pairs.first().unwrap().reasons,
+            [DuplicateReason::SameEmail, DuplicateReason::SamePhone]
+        );
+        assert_eq!(pairs.first().unwrap().score, 90);
+    }
+
+    #[test]
+    fn should_rank_stronger_matches_first() {
+        let contacts = vec![
+            indexed(1, "Lewis", "", ""),
+            indexed(2, "Lewis", "lewis@wonderland.com", ""),
+            indexed(3, "Lewsi", "lewis@wonderland.com", ""),
+            indexed(4, "Alice", "alice@wonderland.com", ""),
+        ];
+
+        let pairs: Vec<(i64, i64, usize)> = find_duplicates(&contacts)
+            .iter()
+            .map(|pair| (pair.first.id, pair.second.id, pair.score))
+            .collect();
+
+        assert_eq!(pairs, [(2, 3, 75), (1, 2, 40)]);
+    }
+
+    #[test]
+    fn should_find_the_contact_an_imported_row_describes() {
+        let mut with_external_id = indexed(3, "Alice", "", "");
+        with_external_id.contact.external_id = Some("people/c42".to_string());
+        let contacts = vec![
+            indexed(1, "Lewis", "lewis@wonderland.com", ""),
+            indexed(2, "Bill", "", "212-555-0177"),
+            with_external_id,
+        ];
+        let row = |first_name: &str, email: Option<&str>| OptionalContact {
+            first_name: Some(first_name.to_string()),
+            last_name: Some("Carroll".to_string()),
+            email: email.map(str::to_owned),
+            ..OptionalContact::default()
+        };
+        let found = |row: &OptionalContact| find_existing(row, &contacts).map(|found| found.id);
+
+        assert_eq!(
+            found(&row("Charles", Some("LEWIS@wonderland.com"))),
+            Some(1)
+        );
+        assert_eq!(found(&row("Lewis", None)), Some(1));
+        assert_eq!(found(&row("Lewis", Some("other@wonderland.com"))), None);
+        assert_eq!(
+            found(&OptionalContact {
+                phone_number: Some("(212) 555 0177".to_string()),
+                ..row("William", None)
+            }),
+            Some(2)
+        );
+        assert_eq!(
+            found(&OptionalContact {
+                external_id: Some("people/c42".to_string()),
+                ..row("Lewis", None)
+            }),
+            Some(3)
+        );
+        assert_eq!(
+            found(&OptionalContact {
+                external_id: Some("people/c43".to_string()),
+                ..row("Lewis", None)
+            }),
+            None
+        );
+    }
+
+    #[test]
+    fn should_not_match_short_or_empty_values() {
+        let contacts = vec![
+            indexed(1, "Lewis", "", "911"),
+            indexed(2, "Alice", "", "911"),
+        ];
+
+        assert!(find_duplicates(&contacts).is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::{
+    models::{Birthday, Cadence, ContactBuilder, ContactField, IndexedContact},
+    utils::parse_timestamp,
+    Error,
+};
+
+/// Start of the comment lines `document_with_error` puts above a document
+const ERROR_PREFIX: &str = "# Error: ";
+
+/// The editable fields of a contact, as `edit --interactive` writes them.
+/// A missing key leaves the field as it is; an empty string clears it.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Document {
+    first_name: Option<String>,
+    last_name: Option<String>,
+    display_name: Option<String>,
+    email: Option<String>,
+    phone_number: Option<String>,
+    birthday: Option<String>,
+    starred: Option<bool>,
+    is_archived: Option<bool>,
+    frequency: Option<String>,
+    last_seen_at: Option<String>,
+    last_reminder_at: Option<String>,
+}
+
+/// Writes the editable fields of `indexed` as a TOML document, under a
+/// comment saying how to save or cancel. Unknown values are empty strings.
+#[must_use]
+pub fn contact_to_document(indexed: &IndexedContact) -> String {
+    let value = |field: ContactField| Some(field.value_of(&indexed.contact));
+
+    let document = Document {
+        first_name: value(ContactField::FirstName),
+        last_name: value(ContactField::LastName),
+        display_name: value(ContactField::DisplayName),
+        email: value(ContactField::Email),
+        phone_number: value(ContactField::PhoneNumber),
+        birthday: value(ContactField::Birthday),
+        starred: Some(indexed.contact.starred),
+        is_archived: Some(indexed.contact.is_archived),
+        frequency: value(ContactField::Frequency),
+        last_seen_at: value(ContactField::LastSeenAt),
+        last_reminder_at: value(ContactField::LastReminderAt),
+    };
+
+    format!(
+        "# Contact {}, added {}\n\
+         # Save and close the editor to apply your changes, or delete everything\n\
+         # to cancel. An empty value (\"\") clears a field.\n\n{}",
+        indexed.id,
+        ContactField::CreatedAt.value_of(&indexed.contact),
+        toml::to_string(&document).unwrap_or_default()
+    )
+}
+
+/// Reads a document written by `contact_to_document` back, keeping only
+/// the fields that differ from `indexed`. Values are checked the way
+/// `Contact::new` checks them, with phone numbers in `region`.
+///
+/// Returns `Ok(None)` when the document holds nothing but comments and blank
+/// lines, which cancels the edit.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - The document is not valid TOML or has a key that is not a field
+/// - An email, phone number, birthday, cadence or timestamp is invalid
+/// - Nothing was changed, as `Error::EmptyUpdate`
+pub fn document_to_update(
+    indexed: &IndexedContact,
+    document: &str,
+    region: &str,
+) -> crate::Result<Option<ContactBuilder>> {
+    let is_blank = document
+        .lines()
+        .map(str::trim)
+        .all(|line| line.is_empty() || line.starts_with('#'));
+    if is_blank {
+        return Ok(None);
+    }
+
+    let edited: Document = toml::from_str(document)
+        .map_err(|error| Error::InvalidFile(format!("Invalid contact: {}", error.message())))?;
+
+    let contact = &indexed.contact;
+    let changed = |field: ContactField, value: Option<String>| {
+        value
+            .map(|value| value.trim().to_owned())
+            .filter(|value| *value != field.value_of(contact))
+    };
+
+    let mut builder = ContactBuilder::builder().id(indexed.id).region(region);
+
+    if let Some(first_name) = changed(ContactField::FirstName, edited.first_name) {
+        builder = builder.first_name(first_name);
+    }
+    if let Some(last_name) = changed(ContactField::LastName, edited.last_name) {
+        builder = builder.last_name(last_name);
+    }
+    if let Some(display_name) = changed(ContactField::DisplayName, edited.display_name) {
+        builder = builder.display_name(display_name);
+    }
+    match changed(ContactField::Email, edited.email) {
+        Some(email) if email.is_empty() => builder = builder.clear(ContactField::Email),
+        Some(email) => builder = builder.email(email),
+        None => {}
+    }
+    match changed(ContactField::PhoneNumber, edited.phone_number) {
+        Some(phone_number) if phone_number.is_empty() => {
+            builder = builder.clear(ContactField::PhoneNumber);
+        }
+        Some(phone_number) => builder = builder.phone_number(phone_number),
+        None => {}
+    }
+    match changed(ContactField::Birthday, edited.birthday) {
+        Some(birthday) if birthday.is_empty() => builder = builder.clear(ContactField::Birthday),
+        Some(birthday) => builder = builder.birthday(birthday.parse::<Birthday>()?),
+        None => {}
+    }
+    if let Some(starred) = edited.starred.filter(|starred| *starred != contact.starred) {
+        builder = builder.starred(starred);
+    }
+    if let Some(archived) = edited
+        .is_archived
+        .filter(|archived| *archived != contact.is_archived)
+    {
+        builder = builder.archived(archived);
+    }
+    match changed(ContactField::Frequency, edited.frequency) {
+        Some(frequency) if frequency.is_empty() => {
+            builder = builder.clear(ContactField::Frequency);
+        }
+        Some(frequency) => builder = builder.frequency(frequency.parse::<Cadence>()?),
+        None => {}
+    }
+    match changed(ContactField::LastSeenAt, edited.last_seen_at) {
+        Some(last_seen_at) if last_seen_at.is_empty() => {
+            builder = builder.clear(ContactField::LastSeenAt);
+        }
+        Some(last_seen_at) => builder = builder.last_seen_at(parse_timestamp(&last_seen_at)?),
+        None => {}
+    }
+    match changed(ContactField::LastReminderAt, edited.last_reminder_at) {
+        Some(last_reminder_at) if last_reminder_at.is_empty() => {
+            builder = builder.clear(ContactField::LastReminderAt);
+        }
+        Some(last_reminder_at) => {
+            builder = builder.last_reminder_at(parse_timestamp(&last_reminder_at)?);
+        }
+        None => {}
+    }
+
+    builder.build().map(Some)
+}
+
+/// Puts `error` above `document` as comment lines, replacing the error of
+/// an earlier attempt, so it is the first thing seen when the editor opens.
+#[must_use]
+pub fn document_with_error(document: &str, error: &Error) -> String {
+    let rest = document
+        .lines()
+        .skip_while(|line| line.starts_with(ERROR_PREFIX) || line.starts_with("#   "))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let message = error.to_string().replace('\n', "\n#   ");
+
+    format!("{ERROR_PREFIX}{message}\n{rest}\n")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{models::Contact, utils::DEFAULT_REGION};
+
+    fn ada() -> IndexedContact {
+        let mut contact =
+            Contact::new("Ada", "Lovelace", "ada@example.com", "", "1815-12-10", "US").unwrap();
+        contact.frequency = Some("monthly".parse().unwrap());
+
+        IndexedContact { id: 3, contact }
+    }
+
+    #[test]
+    fn should_change_only_the_edited_fields() {
+        let ada = ada();
+        let document = contact_to_document(&ada)
+            .replace("starred = false", "starred = true")
+            .replace("\"monthly\"", "\"quarterly\"")
+            .replace("\"1815-12-10\"", "\"\"");
+
+        let update = document_to_update(&ada, &document, DEFAULT_REGION)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(update.id, 3);
+        assert_eq!(update.optional_contact.starred, Some(true));
+        assert_eq!(
+            update.optional_contact.frequency,
+            Some("quarterly".parse().unwrap())
+        );
+        assert_eq!(
+            update.optional_contact.cleared,
+            vec![ContactField::Birthday]
+        );
+        assert_eq!(update.optional_contact.first_name, None);
+        assert_eq!(update.optional_contact.email, None);
+    }
+
+    #[test]
+    fn should_reject_unchanged_or_invalid_documents() {
+        let ada = ada();
+        let document = contact_to_document(&ada);
+
+        assert!(matches!(
+            document_to_update(&ada, &document, DEFAULT_REGION),
+            Err(Error::EmptyUpdate)
+        ));
+        assert!(matches!(
+            document_to_update(&ada, &document.replace("ada@example.com", "ada@"), DEFAULT_REGION),
+            Err(Error::InvalidEmail(email)) if email == "ada@"
+        ));
+        assert!(matches!(
+            document_to_update(&ada, "nickname = \"Addy\"", DEFAULT_REGION),
+            Err(Error::InvalidFile(_))
+        ));
+        assert!(document_to_update(&ada, "# Contact 3\n\n", DEFAULT_REGION)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn should_replace_the_error_of_an_earlier_attempt() {
+        let document = "first_name = \"Ada\"\n";
+
+        let once = document_with_error(document, &Error::InvalidEmail("a@".to_string()));
+        let twice = document_with_error(&once, &Error::InvalidDate("1815-13-10".to_string()));
+
+        assert_eq!(
+            twice,
+            "# Error: 1815-13-10 is invalid\nfirst_name = \"Ada\"\n"
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use chrono::Utc;
+
+use crate::Error;
+
+/// Editor run when no editor is configured and neither `VISUAL` nor
+/// `EDITOR` is set
+pub const DEFAULT_EDITOR: &str = "vi";
+
+/// `configured`, such as the `editor` setting, else the editor command in
+/// `VISUAL` or `EDITOR`, or [`DEFAULT_EDITOR`]. It may carry arguments, such
+/// as `code --wait`.
+#[must_use]
+pub fn editor_command(configured: Option<&str>) -> String {
+    if let Some(editor) = configured {
+        return editor.to_owned();
+    }
+
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_owned())
+}
+
+/// Opens `text` in `editor`, a command like [`editor_command`] returns, and
+/// returns what was saved.
+///
+/// The text is written to a temporary file ending in `.{extension}`, so
+/// editors can pick a syntax, and the file is removed afterwards.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - The temporary file cannot be written or read
+/// - The editor cannot be started or exits unsuccessfully
+pub fn edit_text(text: &str, extension: &str, editor: &str) -> crate::Result<String> {
+    let path = temporary_path(extension);
+    std::fs::write(&path, text)?;
+
+    let edited = run_editor(editor, &path)
+        .and_then(|()| std::fs::read_to_string(&path).map_err(Error::from));
+
+    // The text is already read, so a file left behind is not worth failing on
+    let _ = std::fs::remove_file(&path);
+
+    edited
+}
+
+fn run_editor(editor: &str, path: &Path) -> crate::Result<()> {
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or(DEFAULT_EDITOR);
+
+    let status = Command::new(program).args(words).arg(path).status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Io(io::Error::other(format!(
+            "{editor} exited with {status}"
+        ))))
+    }
+}
+
+fn temporary_path(extension: &str) -> PathBuf {
+    let unique = Utc::now().timestamp_nanos_opt().unwrap_or_default();
+
+    std::env::temp_dir().join(format!("nbd-{}-{unique}.{extension}", std::process::id()))
+}
@@ -0,0 +1,265 @@
+use crate::{
+    models::{Contact, IndexedContact},
+    Error,
+};
+
+/// Lowest score, out of 100, at which a name still counts as a match
+const MATCH_THRESHOLD: usize = 70;
+/// How far the best match must lead the runner-up to be picked on its own
+const CLEAR_LEAD: usize = 10;
+
+const EXACT_SCORE: usize = 100;
+const PREFIX_SCORE: usize = 90;
+
+/// Scores how well `query` names `contact`, from 0 to 100.
+///
+/// Compares case-insensitively against the display, first, last and full
+/// name, and each of their words. An exact name scores 100, a prefix 90, and
+/// anything else by how few typos separate it from the query.
+#[must_use]
+pub fn name_score(query: &str, contact: &Contact) -> usize {
+    let query = normalize(query);
+    if query.is_empty() {
+        return 0;
+    }
+
+    let full_name = format!("{} {}", contact.first_name, contact.last_name);
+    let names = [
+        contact.display_name.as_str(),
+        contact.first_name.as_str(),
+        contact.last_name.as_str(),
+        full_name.as_str(),
+    ];
+
+    names
+        .iter()
+        .map(|name| normalize(name))
+        .filter(|name| !name.is_empty())
+        .flat_map(|name| {
+            let words: Vec<String> = name.split(' ').map(str::to_owned).collect();
+            std::iter::once(name).chain(words)
+        })
+        .map(|name| score(&query, &name))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Contacts whose names are close to `query`, best match first.
+#[must_use]
+pub fn match_contacts_by_name<'a>(
+    query: &str,
+    contacts: &'a [IndexedContact],
+) -> Vec<(&'a IndexedContact, usize)> {
+    let mut matches: Vec<(&IndexedContact, usize)> = contacts
+        .iter()
+        .map(|indexed| (indexed, name_score(query, &indexed.contact)))
+        .filter(|(_, score)| *score >= MATCH_THRESHOLD)
+        .collect();
+
+    matches.sort_by(|(a, a_score), (b, b_score)| b_score.cmp(a_score).then(a.id.cmp(&b.id)));
+
+    matches
+}
+
+/// Picks the one contact that `query` clearly names.
+///
+/// # Errors
+///
+/// Errors with `Error::NoMatch` when no name is close to `query`, or
+/// `Error::AmbiguousName` listing the candidates when no match stands out
+pub fn resolve_contact_by_name(
+    query: &str,
+    contacts: &[IndexedContact],
+) -> crate::Result<IndexedContact> {
+    let matches = match_contacts_by_name(query, contacts);
+
+    match matches.as_slice() {
+        [] => Err(Error::NoMatch(query.to_owned())),
+        [(best, _)] => Ok((*best).clone()),
+        [(best, best_score), (_, runner_up_score), ..]
+            if best_score.saturating_sub(*runner_up_score) >= CLEAR_LEAD =>
+        {
+            Ok((*best).clone())
+        }
+        [(_, best_score), ..] => {
+            let candidates = matches
+                .iter()
+                .filter(|(_, score)| best_score.saturating_sub(*score) < CLEAR_LEAD)
+                .map(|(indexed, _)| (indexed.id, indexed.contact.display_name.clone()))
+                .collect();
+
+            Err(Error::AmbiguousName {
+                query: query.to_owned(),
+                candidates,
+            })
+        }
+    }
+}
+
+/// Scores how alike two whole names are, from 0 to 100, ignoring case and
+/// spacing. Unlike `name_score`, a shared first name alone is no match.
+pub(super) fn name_similarity(a: &str, b: &str) -> usize {
+    let (a, b) = (normalize(a), normalize(b));
+    if a.is_empty() || b.is_empty() {
+        return 0;
+    }
+
+    typo_score(&a, &b)
+}
+
+fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn score(query: &str, name: &str) -> usize {
+    if query == name {
+        return EXACT_SCORE;
+    }
+    if query.chars().count() >= 2 && name.starts_with(query) {
+        return PREFIX_SCORE;
+    }
+
+    typo_score(query, name)
+}
+
+fn typo_score(query: &str, name: &str) -> usize {
+    let query: Vec<char> = query.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let longest = query.len().max(name.len());
+
+    longest
+        .saturating_sub(edit_distance(&query, &name))
+        .saturating_mul(EXACT_SCORE)
+        .checked_div(longest)
+        .unwrap_or(0)
+}
+
+/// Optimal string alignment distance: Levenshtein distance that also counts
+/// swapping two adjacent characters as a single typo.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut before_previous: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i.saturating_add(1)];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let cost = |row: &[usize], column: usize, extra: usize| {
+                row.get(column)
+                    .map_or(usize::MAX, |distance| distance.saturating_add(extra))
+            };
+
+            let mut distance = cost(&previous, j, usize::from(a_char != b_char))
+                .min(cost(&current, j, 1))
+                .min(cost(&previous, j.saturating_add(1), 1));
+
+            if let (Some(previous_i), Some(previous_j)) = (i.checked_sub(1), j.checked_sub(1)) {
+                if a.get(previous_i) == Some(b_char) && b.get(previous_j) == Some(a_char) {
+                    distance = distance.min(cost(&before_previous, previous_j, 1));
+                }
+            }
+
+            current.push(distance);
+        }
+
+        before_previous = std::mem::replace(&mut previous, current);
+    }
+
+    previous.last().copied().unwrap_or(0)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn indexed(id: i64, first_name: &str, last_name: &str) -> IndexedContact {
+        IndexedContact {
+            id,
+            contact: Contact::builder()
+                .first_name(first_name)
+                .last_name(last_name)
+                .build()
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn should_count_typos_and_swapped_letters() {
+        let distance = |a: &str, b: &str| {
+            edit_distance(
+                &a.chars().collect::<Vec<_>>(),
+                &b.chars().collect::<Vec<_>>(),
+            )
+        };
+
+        assert_eq!(distance("john", "john"), 0);
+        assert_eq!(distance("jhon", "john"), 1);
+        assert_eq!(distance("smyth", "smith"), 1);
+        assert_eq!(distance("", "ada"), 3);
+        assert_eq!(distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn should_score_exact_names_above_prefixes_above_typos() {
+        let lewis = indexed(1, "Lewis", "Carroll");
+
+        assert_eq!(name_score("lewis carroll", &lewis.contact), EXACT_SCORE);
+        assert_eq!(name_score("CARROLL", &lewis.contact), EXACT_SCORE);
+        assert_eq!(name_score("carr", &lewis.contact), PREFIX_SCORE);
+        assert!(
+            (MATCH_THRESHOLD..PREFIX_SCORE).contains(&name_score("Lewis Carol", &lewis.contact))
+        );
+        assert!(name_score("lewsi", &lewis.contact) >= MATCH_THRESHOLD);
+        assert!(name_score("alice", &lewis.contact) < MATCH_THRESHOLD);
+        assert_eq!(name_score("  ", &lewis.contact), 0);
+    }
+
+    #[test]
+    fn should_resolve_a_clear_match_despite_typos() {
+        let contacts = vec![
+            indexed(1, "Lewis", "Carroll"),
+            indexed(2, "Alice", "Liddell"),
+        ];
+
+        assert_eq!(resolve_contact_by_name("alcie", &contacts).unwrap().id, 2);
+        assert_eq!(resolve_contact_by_name("Caroll", &contacts).unwrap().id, 1);
+    }
+
+    #[test]
+    fn should_prefer_an_exact_name_over_a_longer_one() {
+        let contacts = vec![indexed(1, "Danielle", "Smith"), indexed(2, "Dan", "Brown")];
+
+        assert_eq!(resolve_contact_by_name("dan", &contacts).unwrap().id, 2);
+    }
+
+    #[test]
+    fn should_list_candidates_when_ambiguous() {
+        let contacts = vec![
+            indexed(1, "Dan", "Brown"),
+            indexed(2, "Dan", "Smith"),
+            indexed(3, "Alice", "Liddell"),
+        ];
+
+        let error = resolve_contact_by_name("dan", &contacts).unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::AmbiguousName { ref candidates, .. }
+                if *candidates == [(1, "Dan Brown".to_string()), (2, "Dan Smith".to_string())]
+        ));
+    }
+
+    #[test]
+    fn should_fail_when_nothing_is_close() {
+        let contacts = vec![indexed(1, "Lewis", "Carroll")];
+
+        assert!(matches!(
+            resolve_contact_by_name("zebra", &contacts),
+            Err(Error::NoMatch(query)) if query == "zebra"
+        ));
+    }
+}
@@ -0,0 +1,240 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
+
+use crate::models::IndexedContact;
+
+use super::vcard::{escape_text, fold_line};
+
+const PRODUCT_ID: &str = "-//nbd//nbd-cli//EN";
+
+/// Year-less birthdays recur from a leap year so February 29 is valid.
+const YEAR_LESS_START: i32 = 2000;
+
+/// Writes an RFC 5545 calendar with a yearly all-day event for each birthday
+/// and an all-day event on each contact's next reminder.
+///
+/// UIDs derive from the contact id and the name of its `book`, so importing
+/// a newer file updates the events instead of adding copies, and calendars of
+/// different books do not overwrite each other. Archived contacts are left
+/// out.
+#[must_use]
+pub fn contacts_to_icalendar(
+    contacts: &[IndexedContact],
+    book: &str,
+    generated_at: DateTime<Utc>,
+) -> String {
+    let stamp = generated_at.format("%Y%m%dT%H%M%SZ").to_string();
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        format!("PRODID:{PRODUCT_ID}"),
+        "CALSCALE:GREGORIAN".to_owned(),
+    ];
+
+    for indexed in contacts
+        .iter()
+        .filter(|indexed| !indexed.contact.is_archived)
+    {
+        lines.extend(birthday_event(indexed, book, &stamp));
+        lines.extend(reminder_event(indexed, book, &stamp));
+    }
+
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// The number of events `contacts_to_icalendar` writes for `contacts`.
+#[must_use]
+pub fn count_calendar_events(contacts: &[IndexedContact]) -> usize {
+    contacts
+        .iter()
+        .filter(|indexed| !indexed.contact.is_archived)
+        .map(|indexed| {
+            usize::from(indexed.contact.birthday.is_some())
+                .saturating_add(usize::from(indexed.contact.next_reminder_at.is_some()))
+        })
+        .sum()
+}
+
+fn birthday_event(indexed: &IndexedContact, book: &str, stamp: &str) -> Vec<String> {
+    let Some(birthday) = indexed.contact.birthday else {
+        return Vec::new();
+    };
+
+    let Some(start) = birthday
+        .date()
+        .or_else(|| birthday.in_year(YEAR_LESS_START))
+    else {
+        return Vec::new();
+    };
+
+    // A plain yearly rule skips February 29 in common years; pinning the
+    // last day of February keeps it on the 28th, as `birthdays` does.
+    let rule = if birthday.month() == 2 && birthday.day() == 29 {
+        "RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1"
+    } else {
+        "RRULE:FREQ=YEARLY"
+    };
+
+    let name = name_of(indexed);
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_owned(),
+        format!(
+            "UID:{}",
+            escape_text(&format!("birthday-{}@{book}.nbd", indexed.id))
+        ),
+        format!("DTSTAMP:{stamp}"),
+        format!("DTSTART;VALUE=DATE:{}", format_date(start)),
+        rule.to_owned(),
+        format!("SUMMARY:{}", escape_text(&format!("{name}'s birthday"))),
+    ];
+
+    if let Some(year) = birthday.year() {
+        lines.push(format!("DESCRIPTION:Born {year}"));
+    }
+
+    lines.push("TRANSP:TRANSPARENT".to_owned());
+    lines.push("END:VEVENT".to_owned());
+    lines
+}
+
+fn reminder_event(indexed: &IndexedContact, book: &str, stamp: &str) -> Vec<String> {
+    let contact = &indexed.contact;
+
+    let Some(next_reminder_at) = contact.next_reminder_at else {
+        return Vec::new();
+    };
+
+    let name = name_of(indexed);
+    let mut description = Vec::new();
+
+    if let Some(frequency) = contact.frequency {
+        description.push(format!("Keep in touch {frequency}."));
+    }
+
+    match contact.last_seen_at {
+        Some(last_seen_at) => description.push(format!(
+            "Last seen {}.",
+            last_seen_at.with_timezone(&Local).format("%Y-%m-%d")
+        )),
+        None => description.push("Not seen yet.".to_owned()),
+    }
+
+    vec![
+        "BEGIN:VEVENT".to_owned(),
+        format!(
+            "UID:{}",
+            escape_text(&format!("reminder-{}@{book}.nbd", indexed.id))
+        ),
+        format!("DTSTAMP:{stamp}"),
+        format!(
+            "DTSTART;VALUE=DATE:{}",
+            format_date(next_reminder_at.with_timezone(&Local).date_naive())
+        ),
+        format!("SUMMARY:{}", escape_text(&format!("Catch up with {name}"))),
+        format!("DESCRIPTION:{}", escape_text(&description.join(" "))),
+        "TRANSP:TRANSPARENT".to_owned(),
+        "END:VEVENT".to_owned(),
+    ]
+}
+
+fn name_of(indexed: &IndexedContact) -> String {
+    let contact = &indexed.contact;
+
+    if contact.display_name.trim().is_empty() {
+        format!("{} {}", contact.first_name, contact.last_name)
+            .trim()
+            .to_owned()
+    } else {
+        contact.display_name.trim().to_owned()
+    }
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::models::{Birthday, Contact};
+
+    fn example_contact(id: i64, first_name: &str) -> IndexedContact {
+        let contact = Contact::new(first_name, "", "", "", "", "US").unwrap();
+        IndexedContact { id, contact }
+    }
+
+    fn generated_at() -> DateTime<Utc> {
+        "2026-10-18T09:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn should_write_yearly_birthday_events_with_stable_uids() {
+        let mut ada = example_contact(7, "Ada");
+        ada.contact.birthday = Birthday::new(Some(1815), 12, 10);
+
+        let calendar = contacts_to_icalendar(&[ada], "default", generated_at());
+
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.contains("UID:birthday-7@default.nbd\r\n"));
+        assert!(calendar.contains("DTSTAMP:20261018T090000Z\r\n"));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:18151210\r\n"));
+        assert!(calendar.contains("RRULE:FREQ=YEARLY\r\n"));
+        assert!(calendar.contains("SUMMARY:Ada's birthday\r\n"));
+        assert!(calendar.contains("DESCRIPTION:Born 1815\r\n"));
+        assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn should_keep_uids_of_different_books_apart() {
+        let mut ada = example_contact(7, "Ada");
+        ada.contact.birthday = Birthday::new(Some(1815), 12, 10);
+        ada.contact.next_reminder_at = "2026-11-01T12:00:00Z".parse().ok();
+
+        let calendar = contacts_to_icalendar(&[ada], "volunteers", generated_at());
+
+        assert!(calendar.contains("UID:birthday-7@volunteers.nbd\r\n"));
+        assert!(calendar.contains("UID:reminder-7@volunteers.nbd\r\n"));
+    }
+
+    #[test]
+    fn should_start_year_less_birthdays_in_a_leap_year() {
+        let mut bob = example_contact(2, "Bob");
+        bob.contact.birthday = Birthday::new(None, 2, 29);
+
+        let calendar = contacts_to_icalendar(&[bob], "default", generated_at());
+
+        assert!(calendar.contains("DTSTART;VALUE=DATE:20000229\r\n"));
+        assert!(calendar.contains("RRULE:FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=-1\r\n"));
+        assert!(!calendar.contains("DESCRIPTION:Born"));
+    }
+
+    #[test]
+    fn should_write_reminder_events_on_next_reminder() {
+        let mut ada = example_contact(7, "Ada");
+        ada.contact.frequency = "monthly".parse().ok();
+        ada.contact.next_reminder_at = "2026-11-01T12:00:00Z".parse().ok();
+
+        let calendar = contacts_to_icalendar(&[ada], "default", generated_at());
+
+        assert!(calendar.contains("UID:reminder-7@default.nbd\r\n"));
+        assert!(calendar.contains("SUMMARY:Catch up with Ada\r\n"));
+        assert!(calendar.contains("DESCRIPTION:Keep in touch monthly. Not seen yet.\r\n"));
+        assert!(!calendar.contains("RRULE"));
+    }
+
+    #[test]
+    fn should_leave_out_archived_contacts() {
+        let mut ada = example_contact(7, "Ada");
+        ada.contact.birthday = Birthday::new(Some(1815), 12, 10);
+        ada.contact.is_archived = true;
+
+        let contacts = [ada];
+        let calendar = contacts_to_icalendar(&contacts, "default", generated_at());
+
+        assert!(!calendar.contains("BEGIN:VEVENT"));
+        assert_eq!(count_calendar_events(&contacts), 0);
+    }
+}
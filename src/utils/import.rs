@@ -0,0 +1,246 @@
+use std::path::Path;
+
+use crate::{models::OptionalContact, Error, RowError};
+
+use super::{csv, vcard, HeaderMapping};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportFormat {
+    Csv,
+    Vcard,
+}
+
+impl ImportFormat {
+    /// Detects the import format from the file extension.
+    ///
+    /// # Errors
+    ///
+    /// Errors when the extension is neither `.csv` nor `.vcf`/`.vcard`
+    pub fn from_path(path: &Path) -> crate::Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("csv") => Ok(Self::Csv),
+            Some("vcf" | "vcard") => Ok(Self::Vcard),
+            _ => Err(Error::InvalidFile(format!(
+                "Cannot detect the format of {}: expected a .csv or .vcf extension",
+                path.display()
+            ))),
+        }
+    }
+}
+
+pub const DEFAULT_TAG_SEPARATOR: &str = ";";
+
+/// What an import does with a row that describes a contact already in the
+/// book, matched by `external_id`, email, phone number or name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnDuplicate {
+    /// Leave the existing contact alone
+    Skip,
+    /// Fill the existing contact in with the row's values
+    #[default]
+    Update,
+    /// Add the row as a new contact anyway, unless it shares an `external_id`
+    Create,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOptions {
+    /// File format, detected from the extension when `None`
+    pub format: Option<ImportFormat>,
+    /// Separator between tags in the CSV `tags` column, unless `mapping`
+    /// sets its own
+    pub tag_separator: String,
+    /// How CSV headers map onto contact fields
+    pub mapping: HeaderMapping,
+    /// Work out what would change, then roll it all back
+    pub dry_run: bool,
+    /// Import the valid rows of a file with invalid ones, instead of nothing
+    pub skip_invalid: bool,
+    pub on_duplicate: OnDuplicate,
+}
+
+impl ImportOptions {
+    fn csv_tag_separator(&self) -> &str {
+        self.mapping
+            .tag_separator
+            .as_deref()
+            .unwrap_or(&self.tag_separator)
+    }
+}
+
+impl Default for ImportOptions {
+    fn default() -> Self {
+        Self {
+            format: None,
+            tag_separator: DEFAULT_TAG_SEPARATOR.to_owned(),
+            mapping: HeaderMapping::default(),
+            dry_run: false,
+            skip_invalid: false,
+            on_duplicate: OnDuplicate::default(),
+        }
+    }
+}
+
+/// A contact read from an imported file, with the 1-based line it came from
+/// for CSV files, or its position for vCard files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportRow {
+    pub line: u64,
+    pub contact: OptionalContact,
+}
+
+/// The rows read from an imported file
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportFile {
+    pub rows: Vec<Result<ImportRow, RowError>>,
+    /// Headers of the CSV columns that no contact field was read from
+    pub unmapped_columns: Vec<String>,
+}
+
+/// What an import did, or would do on a dry run
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ImportReport {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    /// Rows left out for holding invalid values
+    pub rejected: Vec<RowError>,
+    /// Headers of the CSV columns that were not imported
+    pub unmapped_columns: Vec<String>,
+}
+
+impl ImportReport {
+    /// Rows that created or updated a contact
+    #[must_use]
+    pub const fn imported(&self) -> usize {
+        self.created.saturating_add(self.updated)
+    }
+}
+
+/// Reads contacts from `filename` with the format in `options`, or the
+/// format detected from its extension when none is given. Phone numbers
+/// without a country code are read as belonging to `region`.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - The format cannot be detected from the extension
+/// - The file fails to parse as the chosen format
+pub fn process_file_to_contacts(
+    filename: &str,
+    options: &ImportOptions,
+    region: &str,
+) -> crate::Result<Vec<OptionalContact>> {
+    let path = Path::new(filename);
+
+    let format = match options.format {
+        Some(format) => format,
+        None => ImportFormat::from_path(path)?,
+    };
+
+    match format {
+        ImportFormat::Csv => {
+            csv::read_csv_contacts(path, &options.mapping, options.csv_tag_separator(), region)
+        }
+        ImportFormat::Vcard => vcard::process_vcard_to_contacts(filename, region),
+    }
+}
+
+/// Reads the rows of `filename` like [`process_file_to_contacts`], but keeps
+/// invalid CSV rows as [`RowError`]s so all of them can be reported, along
+/// with the CSV columns that were left out.
+///
+/// # Errors
+///
+/// This function will return an error if
+/// - The format cannot be detected from the extension
+/// - The file cannot be read or is malformed as a whole
+pub fn read_import_rows(
+    filename: &str,
+    options: &ImportOptions,
+    region: &str,
+) -> crate::Result<ImportFile> {
+    let path = Path::new(filename);
+
+    let format = match options.format {
+        Some(format) => format,
+        None => ImportFormat::from_path(path)?,
+    };
+
+    match format {
+        ImportFormat::Csv => {
+            csv::read_csv_rows(path, &options.mapping, options.csv_tag_separator(), region)
+        }
+        ImportFormat::Vcard => Ok(ImportFile {
+            rows: vcard::process_vcard_to_contacts(filename, region)?
+                .into_iter()
+                .zip(1..)
+                .map(|(contact, line)| Ok(ImportRow { line, contact }))
+                .collect(),
+            unmapped_columns: Vec::new(),
+        }),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn should_detect_format_from_extension() {
+        assert_eq!(
+            ImportFormat::from_path(Path::new("contacts.csv")).unwrap(),
+            ImportFormat::Csv
+        );
+        assert_eq!(
+            ImportFormat::from_path(Path::new("contacts.VCF")).unwrap(),
+            ImportFormat::Vcard
+        );
+        assert!(ImportFormat::from_path(Path::new("contacts.txt")).is_err());
+    }
+
+    #[test]
+    fn should_split_tags_with_the_given_separator() -> anyhow::Result<()> {
+        let mut temp_file = NamedTempFile::with_suffix(".csv")?;
+        writeln!(temp_file, "first_name,tags\nAlice,work | climbing")?;
+
+        let temp_file = temp_file.path().to_str().unwrap();
+        let options = ImportOptions {
+            tag_separator: "|".to_string(),
+            ..ImportOptions::default()
+        };
+        let contacts = process_file_to_contacts(temp_file, &options, crate::utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().tags,
+            vec!["work".to_string(), "climbing".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_csv_without_extension_when_format_is_given() -> anyhow::Result<()> {
+        let mut temp_file = NamedTempFile::with_suffix(".txt")?;
+        writeln!(temp_file, "first_name\nAlice")?;
+
+        let temp_file = temp_file.path().to_str().unwrap();
+        let options = ImportOptions {
+            format: Some(ImportFormat::Csv),
+            ..ImportOptions::default()
+        };
+        let contacts = process_file_to_contacts(temp_file, &options, crate::utils::DEFAULT_REGION)?;
+
+        assert_eq!(contacts.len(), 1);
+
+        Ok(())
+    }
+}
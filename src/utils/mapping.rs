@@ -0,0 +1,558 @@
+use std::{collections::BTreeMap, path::Path};
+
+use chrono::NaiveDate;
+use csv::StringRecord;
+use serde::Deserialize;
+
+use crate::{models::ContactField, Error};
+
+/// Address books whose CSV exports have a built-in [`HeaderMapping`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvProfile {
+    Google,
+    Outlook,
+    Apple,
+}
+
+/// Columns an import fills a contact from
+const IMPORTABLE_FIELDS: [&str; 14] = [
+    "first_name",
+    "last_name",
+    "display_name",
+    "email",
+    "phone_number",
+    "birthday",
+    "starred",
+    "is_archived",
+    "created_at",
+    "last_seen_at",
+    "frequency",
+    "last_reminder_at",
+    "external_id",
+    "tags",
+];
+
+/// Columns holding one labelled email address or phone number each
+pub(super) const LABELED_COLUMNS: [&str; 8] = [
+    "home_email",
+    "work_email",
+    "mobile_email",
+    "other_email",
+    "home_phone",
+    "work_phone",
+    "mobile_phone",
+    "other_phone",
+];
+
+/// Columns holding the parts of one postal address
+const ADDRESS_COLUMNS: [&str; 6] = [
+    "address_label",
+    "street",
+    "locality",
+    "region",
+    "postal_code",
+    "country",
+];
+
+/// How the headers of a CSV file map onto contact fields.
+///
+/// Headers are matched ignoring case and surrounding spaces, and columns
+/// already named after a contact field need no entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderMapping {
+    /// Contact field to the headers that may hold it, most preferred first
+    pub fields: BTreeMap<String, Vec<String>>,
+    /// Separator between tags, used instead of the one given to the import
+    pub tag_separator: Option<String>,
+    /// Separator between several values packed in one cell, of which only
+    /// the first is kept
+    pub value_separator: Option<String>,
+    /// `chrono` format of birthdays that are not written as ISO 8601
+    pub date_format: Option<String>,
+    /// Prefix of the tags an address book adds on its own, which are left out
+    pub system_tag_prefix: Option<String>,
+}
+
+/// A mapping file as written by users, where a field maps to one header or
+/// a list of them
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MappingFile {
+    #[serde(default)]
+    columns: BTreeMap<String, Headers>,
+    tag_separator: Option<String>,
+    value_separator: Option<String>,
+    date_format: Option<String>,
+    system_tag_prefix: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Headers {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl HeaderMapping {
+    /// The mapping for the CSV export of `profile`.
+    #[must_use]
+    pub fn from_profile(profile: CsvProfile) -> Self {
+        match profile {
+            CsvProfile::Google => Self {
+                fields: fields(&[
+                    ("first_name", &["Given Name", "First Name"]),
+                    ("last_name", &["Family Name", "Last Name"]),
+                    ("display_name", &["Name", "File As"]),
+                    ("email", &["E-mail 1 - Value", "E-mail 2 - Value"]),
+                    ("phone_number", &["Phone 1 - Value", "Phone 2 - Value"]),
+                    ("other_email", &["E-mail 2 - Value"]),
+                    ("other_phone", &["Phone 2 - Value"]),
+                    ("address_label", &["Address 1 - Type"]),
+                    ("street", &["Address 1 - Street"]),
+                    ("locality", &["Address 1 - City"]),
+                    ("region", &["Address 1 - Region"]),
+                    ("postal_code", &["Address 1 - Postal Code"]),
+                    ("country", &["Address 1 - Country"]),
+                    ("birthday", &["Birthday"]),
+                    ("tags", &["Labels", "Group Membership"]),
+                ]),
+                tag_separator: Some(":::".to_owned()),
+                value_separator: Some(":::".to_owned()),
+                date_format: None,
+                system_tag_prefix: Some("*".to_owned()),
+            },
+            CsvProfile::Outlook => Self {
+                fields: fields(&[
+                    ("first_name", &["First Name"]),
+                    ("last_name", &["Last Name"]),
+                    (
+                        "email",
+                        &["E-mail Address", "E-mail 2 Address", "E-mail 3 Address"],
+                    ),
+                    (
+                        "phone_number",
+                        &[
+                            "Mobile Phone",
+                            "Primary Phone",
+                            "Home Phone",
+                            "Business Phone",
+                        ],
+                    ),
+                    ("other_email", &["E-mail 2 Address", "E-mail 3 Address"]),
+                    ("mobile_phone", &["Mobile Phone"]),
+                    ("home_phone", &["Home Phone"]),
+                    ("work_phone", &["Business Phone"]),
+                    ("street", &["Home Street"]),
+                    ("locality", &["Home City"]),
+                    ("region", &["Home State"]),
+                    ("postal_code", &["Home Postal Code"]),
+                    ("country", &["Home Country/Region"]),
+                    ("birthday", &["Birthday"]),
+                    ("tags", &["Categories"]),
+                ]),
+                tag_separator: Some(";".to_owned()),
+                value_separator: None,
+                date_format: Some("%m/%d/%Y".to_owned()),
+                system_tag_prefix: None,
+            },
+            CsvProfile::Apple => Self {
+                fields: fields(&[
+                    ("first_name", &["First Name"]),
+                    ("last_name", &["Last Name"]),
+                    ("display_name", &["Nickname"]),
+                    ("email", &["Email", "E-mail", "Home Email", "Work Email"]),
+                    (
+                        "phone_number",
+                        &["Mobile", "iPhone", "Phone", "Home Phone", "Work Phone"],
+                    ),
+                    ("home_email", &["Home Email"]),
+                    ("work_email", &["Work Email"]),
+                    ("mobile_phone", &["Mobile", "iPhone"]),
+                    ("home_phone", &["Home Phone"]),
+                    ("work_phone", &["Work Phone"]),
+                    ("street", &["Street"]),
+                    ("locality", &["City"]),
+                    ("region", &["State"]),
+                    ("postal_code", &["ZIP", "Postal Code"]),
+                    ("country", &["Country"]),
+                    ("birthday", &["Birthday"]),
+                    ("tags", &["Groups", "Group"]),
+                ]),
+                tag_separator: Some(",".to_owned()),
+                value_separator: None,
+                date_format: None,
+                system_tag_prefix: None,
+            },
+        }
+    }
+
+    /// Reads a TOML mapping file, such as
+    ///
+    /// ```toml
+    /// tag_separator = "|"
+    ///
+    /// [columns]
+    /// first_name = "Given"
+    /// phone_number = ["Cell", "Landline"]
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if
+    /// - Fails to open the file
+    /// - Is not a valid mapping file
+    /// - Maps a column to something other than a contact field, a labelled
+    ///   column such as `work_email` or an address column such as `street`
+    pub fn from_file(path: &Path) -> crate::Result<Self> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|error| Error::open_file(path, &error))?;
+
+        let file: MappingFile = toml::from_str(&contents).map_err(|error| {
+            Error::InvalidFile(format!(
+                "Invalid mapping file {}: {}",
+                path.display(),
+                error.message()
+            ))
+        })?;
+
+        let mut fields = BTreeMap::new();
+        for (field, headers) in file.columns {
+            if !IMPORTABLE_FIELDS.contains(&field.as_str())
+                && !LABELED_COLUMNS.contains(&field.as_str())
+                && !ADDRESS_COLUMNS.contains(&field.as_str())
+            {
+                return Err(Error::UnknownField(field));
+            }
+
+            let headers = match headers {
+                Headers::One(header) => vec![header],
+                Headers::Many(headers) => headers,
+            };
+            fields.insert(field, headers);
+        }
+
+        Ok(Self {
+            fields,
+            tag_separator: file.tag_separator,
+            value_separator: file.value_separator,
+            date_format: file.date_format,
+            system_tag_prefix: file.system_tag_prefix,
+        })
+    }
+
+    /// Works out which columns of a file with `headers` feed which field.
+    pub(super) fn resolve(&self, headers: &StringRecord) -> MappedHeaders {
+        let position = |wanted: &str| {
+            headers
+                .iter()
+                .position(|header| normalize(header) == normalize(wanted))
+        };
+
+        let mut fields: Vec<(String, Vec<usize>)> = Vec::new();
+        for (field, wanted) in &self.fields {
+            let mut sources: Vec<usize> = Vec::new();
+            for index in wanted
+                .iter()
+                .chain(std::iter::once(field))
+                .filter_map(|header| position(header))
+            {
+                if !sources.contains(&index) {
+                    sources.push(index);
+                }
+            }
+            fields.push((field.clone(), sources));
+        }
+
+        let known_fields = ContactField::ALL
+            .iter()
+            .map(|field| field.as_str())
+            .chain(std::iter::once("tags"))
+            .chain(LABELED_COLUMNS)
+            .chain(ADDRESS_COLUMNS);
+        for field in known_fields {
+            if self.fields.contains_key(field) {
+                continue;
+            }
+            if let Some(index) = position(field) {
+                fields.push((field.to_owned(), vec![index]));
+            }
+        }
+
+        fields.retain(|(_, sources)| !sources.is_empty());
+
+        let unmapped = headers
+            .iter()
+            .enumerate()
+            .filter(|(index, header)| {
+                !header.trim().is_empty()
+                    && !fields.iter().any(|(_, sources)| sources.contains(index))
+            })
+            .map(|(_, header)| header.to_owned())
+            .collect();
+
+        MappedHeaders {
+            headers: headers.clone(),
+            fields,
+            unmapped,
+        }
+    }
+}
+
+/// The columns of a CSV file resolved against a [`HeaderMapping`]
+pub(super) struct MappedHeaders {
+    /// The headers as written in the file
+    headers: StringRecord,
+    /// Each mapped field with the columns that may hold it, most preferred
+    /// first
+    fields: Vec<(String, Vec<usize>)>,
+    /// Headers of the columns that no field is read from
+    pub unmapped: Vec<String>,
+}
+
+impl MappedHeaders {
+    /// The field names a mapped record is read with.
+    pub fn field_names(&self) -> StringRecord {
+        self.fields
+            .iter()
+            .map(|(field, _)| field.as_str())
+            .collect()
+    }
+
+    /// Rebuilds `record` with one value per mapped field, in the order of
+    /// [`Self::field_names`].
+    pub fn map_record(
+        &self,
+        record: &StringRecord,
+        mapping: &HeaderMapping,
+        tag_separator: &str,
+    ) -> StringRecord {
+        self.fields
+            .iter()
+            .map(|(field, sources)| {
+                let mut values = sources
+                    .iter()
+                    .filter_map(|index| record.get(*index))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty());
+
+                match field.as_str() {
+                    "tags" => map_tags(values, mapping, tag_separator),
+                    "birthday" => values
+                        .next()
+                        .map(|value| map_date(first_value(value, mapping), mapping))
+                        .unwrap_or_default(),
+                    _ => values
+                        .next()
+                        .map(|value| first_value(value, mapping).to_owned())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
+    /// The header of the column `field` was read from in `record`, so errors
+    /// point at the column as it is named in the file.
+    pub fn source_header(&self, field: &str, record: &StringRecord) -> String {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .and_then(|(_, sources)| {
+                sources.iter().find(|index| {
+                    record
+                        .get(**index)
+                        .is_some_and(|value| !value.trim().is_empty())
+                })
+            })
+            .and_then(|index| self.headers.get(*index))
+            .map_or_else(|| field.to_owned(), str::to_owned)
+    }
+}
+
+fn fields(fields: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+    fields
+        .iter()
+        .map(|(field, headers)| {
+            (
+                (*field).to_owned(),
+                headers.iter().map(|header| (*header).to_owned()).collect(),
+            )
+        })
+        .collect()
+}
+
+fn normalize(header: &str) -> String {
+    header.trim().to_lowercase()
+}
+
+fn first_value<'a>(value: &'a str, mapping: &HeaderMapping) -> &'a str {
+    mapping
+        .value_separator
+        .as_deref()
+        .and_then(|separator| value.split(separator).next())
+        .map_or(value, str::trim)
+}
+
+fn map_tags<'a>(
+    values: impl Iterator<Item = &'a str>,
+    mapping: &HeaderMapping,
+    tag_separator: &str,
+) -> String {
+    values
+        .flat_map(|value| value.split(tag_separator))
+        .map(str::trim)
+        .filter(|tag| {
+            !tag.is_empty()
+                && mapping
+                    .system_tag_prefix
+                    .as_deref()
+                    .is_none_or(|prefix| !tag.starts_with(prefix))
+        })
+        .collect::<Vec<_>>()
+        .join(tag_separator)
+}
+
+/// Rewrites a birthday in the mapping's date format as ISO 8601, and blanks
+/// the all-zero dates some exporters write for no birthday.
+fn map_date(value: &str, mapping: &HeaderMapping) -> String {
+    let Some(format) = &mapping.date_format else {
+        return value.to_owned();
+    };
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, format) {
+        return date.format("%Y-%m-%d").to_string();
+    }
+
+    if value
+        .chars()
+        .filter(char::is_ascii_digit)
+        .all(|digit| digit == '0')
+    {
+        return String::new();
+    }
+
+    value.to_owned()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn record(values: &[&str]) -> StringRecord {
+        StringRecord::from(values.to_vec())
+    }
+
+    #[test]
+    fn should_map_google_headers_and_list_the_rest() {
+        let mapping = HeaderMapping::from_profile(CsvProfile::Google);
+        let headers = record(&[
+            "Given Name",
+            "Family Name",
+            "Nickname",
+            "E-mail 1 - Value",
+            "Phone 1 - Value",
+            "Labels",
+        ]);
+
+        let mapped = mapping.resolve(&headers);
+        let values = mapped.map_record(
+            &record(&[
+                "Ada",
+                "Lovelace",
+                "Countess",
+                "ada@example.com ::: ada@work.example.com",
+                "",
+                "* myContacts ::: Friends ::: Math",
+            ]),
+            &mapping,
+            ":::",
+        );
+
+        assert_eq!(
+            mapped.field_names(),
+            record(&["email", "first_name", "last_name", "phone_number", "tags"])
+        );
+        assert_eq!(
+            values,
+            record(&["ada@example.com", "Ada", "Lovelace", "", "Friends:::Math"])
+        );
+        assert_eq!(mapped.unmapped, vec!["Nickname".to_string()]);
+    }
+
+    #[test]
+    fn should_prefer_earlier_headers_and_read_outlook_dates() {
+        let mapping = HeaderMapping::from_profile(CsvProfile::Outlook);
+        let headers = record(&["First Name", "Business Phone", "Mobile Phone", "Birthday"]);
+
+        let mapped = mapping.resolve(&headers);
+        let row = record(&["Ada", "555-0100", "555-0199", "12/10/1815"]);
+        let values = mapped.map_record(&row, &mapping, ";");
+        let no_birthday =
+            mapped.map_record(&record(&["Ada", "555-0100", "", "0/0/00"]), &mapping, ";");
+
+        assert_eq!(
+            mapped.field_names(),
+            record(&[
+                "birthday",
+                "first_name",
+                "mobile_phone",
+                "phone_number",
+                "work_phone"
+            ])
+        );
+        assert_eq!(
+            values,
+            record(&["1815-12-10", "Ada", "555-0199", "555-0199", "555-0100"])
+        );
+        assert_eq!(
+            no_birthday,
+            record(&["", "Ada", "", "555-0100", "555-0100"])
+        );
+        assert_eq!(mapped.source_header("phone_number", &row), "Mobile Phone");
+        assert!(mapped.unmapped.is_empty());
+    }
+
+    #[test]
+    fn should_keep_columns_named_after_fields() {
+        let mapping = HeaderMapping::default();
+        let headers = record(&["id", "First_Name", "Company"]);
+
+        let mapped = mapping.resolve(&headers);
+
+        assert_eq!(mapped.field_names(), record(&["id", "first_name"]));
+        assert_eq!(mapped.unmapped, vec!["Company".to_string()]);
+    }
+
+    #[test]
+    fn should_read_a_mapping_file() -> anyhow::Result<()> {
+        let mut file = NamedTempFile::with_suffix(".toml")?;
+        writeln!(
+            file,
+            "tag_separator = \"|\"\n\n[columns]\nfirst_name = \"Given\"\nphone_number = [\"Cell\", \"Landline\"]"
+        )?;
+
+        let mapping = HeaderMapping::from_file(file.path())?;
+
+        assert_eq!(mapping.tag_separator.as_deref(), Some("|"));
+        assert_eq!(
+            mapping.fields.get("phone_number").unwrap(),
+            &vec!["Cell".to_string(), "Landline".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_reject_mappings_to_unknown_fields() -> anyhow::Result<()> {
+        let mut file = NamedTempFile::with_suffix(".toml")?;
+        writeln!(file, "[columns]\ncompany = \"Company\"")?;
+
+        let error = HeaderMapping::from_file(file.path()).unwrap_err();
+
+        assert!(matches!(error, Error::UnknownField(field) if field == "company"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::str::FromStr;
+
+use phonenumber::{country, Mode, PhoneNumber};
+
+/// Region numbers written without a country code belong to, unless the
+/// `NBD_REGION` environment variable or the `region` setting names another
+pub const DEFAULT_REGION: &str = "US";
+
+/// How phone numbers are written out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PhoneFormat {
+    /// As it was entered
+    #[default]
+    Original,
+    /// The way it is dialled within its region, such as `(201) 555-0123`
+    National,
+    /// With its country code, such as `+1 201-555-0123`
+    International,
+    /// Digits only with the country code, such as `+12015550123`
+    E164,
+}
+
+/// The ISO 3166 region code in `NBD_REGION`, else `configured`, or
+/// [`DEFAULT_REGION`] when neither names a known region.
+#[must_use]
+pub fn default_region(configured: Option<&str>) -> String {
+    std::env::var("NBD_REGION")
+        .ok()
+        .map(|region| region.trim().to_uppercase())
+        .filter(|region| is_known_region(region))
+        .or_else(|| configured.map(str::to_owned))
+        .unwrap_or_else(|| DEFAULT_REGION.to_owned())
+}
+
+/// Whether `region` is an upper-case ISO 3166 region code.
+pub(super) fn is_known_region(region: &str) -> bool {
+    country::Id::from_str(region).is_ok()
+}
+
+/// Checks `phone` against the numbering plan it belongs to: the one of its
+/// country code, or of `region` when it has none.
+#[must_use]
+pub fn is_valid_phone_number_in(phone: &str, region: &str) -> bool {
+    parse(phone, region).is_some()
+}
+
+/// The E.164 form of `phone` in `region`, or `None` when it is not a valid
+/// number. Extensions are left out.
+#[must_use]
+pub fn normalize_phone_number(phone: &str, region: &str) -> Option<String> {
+    parse(phone, region).map(|number| number.format().mode(Mode::E164).to_string())
+}
+
+/// Writes a phone number out in `format`. Numbers that do not parse in
+/// `region` are read from their E.164 form, and left as entered when that
+/// is missing too.
+#[must_use]
+pub fn format_phone_number(
+    phone_number: &str,
+    phone_e164: Option<&str>,
+    format: PhoneFormat,
+    region: &str,
+) -> String {
+    let mode = match format {
+        PhoneFormat::Original => return phone_number.to_owned(),
+        PhoneFormat::National => Mode::National,
+        PhoneFormat::International => Mode::International,
+        PhoneFormat::E164 => Mode::E164,
+    };
+
+    parse(phone_number, region)
+        .or_else(|| parse(phone_e164?, region))
+        .map_or_else(
+            || phone_number.to_owned(),
+            |number| number.format().mode(mode).to_string(),
+        )
+}
+
+fn parse(phone: &str, region: &str) -> Option<PhoneNumber> {
+    let region = country::Id::from_str(&region.trim().to_uppercase()).ok();
+    let number = phonenumber::parse(region, phone).ok()?;
+
+    // "ext" with no digits after it parses as an empty extension
+    let has_empty_extension = number
+        .extension()
+        .is_some_and(|extension| extension.is_empty());
+
+    (!has_empty_extension && phonenumber::is_valid(&number)).then_some(number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_validate_numbers_against_their_region() {
+        assert!(is_valid_phone_number_in("020 7946 0958", "GB"));
+        assert!(is_valid_phone_number_in("+44 20 7946 0958", "US"));
+        assert!(!is_valid_phone_number_in("020 7946 0958", "US"));
+        assert!(is_valid_phone_number_in("03-1234-5678", "jp"));
+        assert!(!is_valid_phone_number_in("555-0123", "GB"));
+    }
+
+    #[test]
+    fn should_normalize_to_e164() {
+        assert_eq!(
+            normalize_phone_number("(201) 555-0123 ext. 12", "US").as_deref(),
+            Some("+12015550123")
+        );
+        assert_eq!(
+            normalize_phone_number("+49 30 901820", "US").as_deref(),
+            Some("+4930901820")
+        );
+        assert_eq!(normalize_phone_number("not a number", "US"), None);
+    }
+
+    #[test]
+    fn should_format_in_national_or_international_style() {
+        let ada = ("2015550123", Some("+12015550123"));
+        assert_eq!(
+            format_phone_number(ada.0, ada.1, PhoneFormat::National, "US"),
+            "(201) 555-0123"
+        );
+        assert_eq!(
+            format_phone_number(ada.0, ada.1, PhoneFormat::International, "US"),
+            "+1 201-555-0123"
+        );
+        assert_eq!(
+            format_phone_number("+81 3-1234-5678", None, PhoneFormat::National, "US"),
+            "03-1234-5678"
+        );
+        assert_eq!(
+            format_phone_number(
+                "020 7946 0958",
+                Some("+442079460958"),
+                PhoneFormat::International,
+                "US"
+            ),
+            "+44 20 7946 0958"
+        );
+        assert_eq!(
+            format_phone_number("call me", None, PhoneFormat::International, "US"),
+            "call me"
+        );
+        assert_eq!(
+            format_phone_number(ada.0, ada.1, PhoneFormat::Original, "US"),
+            "2015550123"
+        );
+    }
+}
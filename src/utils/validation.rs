@@ -1,16 +1,12 @@
 use regex::Regex;
 
-fn is_valid_phone_number(phone: &str) -> bool {
-    let phone_pattern =
-        r"^\+?1?\s*(\(\d{3}\)|\d{3})[-.\s]*\d{3}[-.\s]*\d{4}(?:\s*(?:ext|x|ex)\.?\s*\d+)?$";
-    let phone_regex = Regex::new(phone_pattern);
-
-    phone_regex.is_ok_and(|r| r.is_match(phone))
-}
+use super::phone::is_valid_phone_number_in;
 
+/// Whether `phone_number` is not a valid number in `region`, the region of
+/// numbers written without a country code.
 #[must_use]
-pub fn is_not_valid_phone_number(phone_number: &str) -> bool {
-    !is_valid_phone_number(phone_number)
+pub fn is_not_valid_phone_number(phone_number: &str, region: &str) -> bool {
+    !is_valid_phone_number_in(phone_number, region)
 }
 
 fn is_valid_email(email: &str) -> bool {
@@ -29,25 +25,29 @@ pub fn is_not_valid_email(email: &str) -> bool {
 mod tests {
     use crate::utils::{is_not_valid_email, is_not_valid_phone_number};
 
-    use super::{is_valid_email, is_valid_phone_number};
+    use super::is_valid_email;
+    use crate::utils::is_valid_phone_number_in;
 
     #[test]
     fn test_valid_phone_numbers() {
         let valid_numbers = [
-            "1234567890",
-            "123-456-7890",
-            "123.456.7890",
-            "(123) 456-7890",
-            "+1 (123) 456-7890",
-            "123-456-7890 ext. 1234",
-            "123.456.7890 x1234",
-            "(123) 456-7890 ex 1234",
-            "1234567890x123",
+            "2015550123",
+            "201-555-0123",
+            "201.555.0123",
+            "(201) 555-0123",
+            "+1 (201) 555-0123",
+            "1 201 555 0123",
+            "201-555-0123 ext. 1234",
+            "201.555.0123 x1234",
+            "(201) 555-0123 ext 1234",
+            "+44 20 7946 0958",
+            "+49 30 901820",
+            "+81 3-1234-5678",
         ];
 
         for number in &valid_numbers {
             assert!(
-                is_valid_phone_number(number),
+                is_valid_phone_number_in(number, "US"),
                 "Valid number '{number}' failed validation",
             );
         }
@@ -56,19 +56,22 @@ mod tests {
     #[test]
     fn test_invalid_phone_numbers() {
         let invalid_numbers = [
-            "123456789",
-            "1234567890123",
-            "123-456-789",
-            "(123 456-7890",
-            "123.456.78901",
-            "123-456-7890 ext",
-            "123.456.7890 x",
-            "(123) 456-7890 ex abc",
+            "20155501",
+            "201555012345",
+            "201-555-012",
+            "201.555.01234",
+            "123-456-7890",
+            "201-555-0123 ext",
+            "201.555.0123 x",
+            "(201) 555-0123 ext abc",
+            "020 7946 0958",
+            "not_a_phone_number",
+            "",
         ];
 
         for number in &invalid_numbers {
             assert!(
-                is_not_valid_phone_number(number),
+                is_not_valid_phone_number(number, "US"),
                 "Invalid number '{number}' passed validation",
             );
         }
@@ -0,0 +1,1128 @@
+use crate::{utils, Error};
+use chrono::{DateTime, Utc};
+use std::path::Path;
+
+use crate::models::{Birthday, ContactCard, Label, Labeled, OptionalContact, PostalAddress};
+
+const STARRED: &str = "X-NBD-STARRED";
+const ARCHIVED: &str = "X-NBD-ARCHIVED";
+const CREATED_AT: &str = "X-NBD-CREATED-AT";
+const LAST_SEEN_AT: &str = "X-NBD-LAST-SEEN-AT";
+const FREQUENCY: &str = "X-NBD-FREQUENCY";
+const LAST_REMINDER_AT: &str = "X-NBD-LAST-REMINDER-AT";
+
+/// # Errors
+///
+/// This function will return an error if
+/// - Fails to open the file
+/// - Is an empty file
+/// - Contains no vCards
+/// - Contains an invalid email, phone number or birthday
+///
+/// Phone numbers without a country code are read as belonging to `region`.
+pub fn process_vcard_to_contacts(
+    filename: &str,
+    region: &str,
+) -> crate::Result<Vec<OptionalContact>> {
+    let path = Path::new(filename);
+
+    validate_vcard_file(path)?;
+
+    let input = std::fs::read_to_string(path).map_err(|error| Error::open_file(path, &error))?;
+
+    let contacts = vcards_to_contacts(&input, region)?;
+
+    if contacts.is_empty() {
+        return Err(Error::InvalidFile(format!(
+            "No vCards found in {}",
+            path.display()
+        )));
+    }
+
+    Ok(contacts)
+}
+
+fn validate_vcard_file(path: &Path) -> crate::Result<()> {
+    let metadata = std::fs::metadata(path).map_err(|error| Error::open_file(path, &error))?;
+
+    if metadata.len() == 0 {
+        return Err(Error::InvalidFile("vCard file is empty".to_owned()));
+    }
+    Ok(())
+}
+
+/// Parses every `BEGIN:VCARD` .. `END:VCARD` block of a vCard 2.1, 3.0 or
+/// 4.0 document into an [`OptionalContact`], reading phone numbers without
+/// a country code as belonging to `region`.
+///
+/// # Errors
+///
+/// Errors when a card holds an invalid email, phone number or birthday
+pub fn vcards_to_contacts(input: &str, region: &str) -> crate::Result<Vec<OptionalContact>> {
+    let mut contacts = Vec::new();
+    let mut card: Option<Vec<Property>> = None;
+
+    for line in unfold_lines(input) {
+        let Some(property) = Property::parse(&line) else {
+            continue;
+        };
+
+        match (
+            property.name.as_str(),
+            property.value.to_ascii_uppercase().as_str(),
+        ) {
+            ("BEGIN", "VCARD") => card = Some(Vec::new()),
+            ("END", "VCARD") => {
+                if let Some(properties) = card.take() {
+                    contacts.push(card_to_contact(&properties, region)?);
+                }
+            }
+            _ => {
+                if let Some(properties) = card.as_mut() {
+                    properties.push(property);
+                }
+            }
+        }
+    }
+
+    Ok(contacts)
+}
+
+/// Joins folded lines (continuations start with a space or tab) and
+/// quoted-printable soft line breaks (a value ending in `=`).
+fn unfold_lines(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for raw_line in input.lines() {
+        let raw_line = raw_line.trim_end_matches('\r');
+
+        if let Some(last) = lines.last_mut() {
+            if let Some(continuation) = raw_line
+                .strip_prefix(' ')
+                .or_else(|| raw_line.strip_prefix('\t'))
+            {
+                last.push_str(continuation);
+                continue;
+            }
+
+            if is_quoted_printable(last) && last.ends_with('=') {
+                last.pop();
+                last.push_str(raw_line);
+                continue;
+            }
+        }
+
+        if !raw_line.trim().is_empty() {
+            lines.push(raw_line.to_owned());
+        }
+    }
+
+    lines
+}
+
+fn is_quoted_printable(line: &str) -> bool {
+    line.split_once(':')
+        .is_some_and(|(head, _)| head.to_ascii_uppercase().contains("QUOTED-PRINTABLE"))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        let (head, raw_value) = split_head(line)?;
+
+        let mut parts = split_unquoted(head, ';').into_iter();
+        let name = parts.next()?;
+        // Drop the optional group prefix, e.g. `item1.EMAIL`
+        let name = name
+            .rsplit('.')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+
+        let params = parts
+            .map(|param| match param.split_once('=') {
+                Some((key, value)) => (
+                    key.trim().to_ascii_uppercase(),
+                    value.trim().trim_matches('"').to_owned(),
+                ),
+                // vCard 2.1 allows bare parameters such as `TEL;CELL` or
+                // `NOTE;QUOTED-PRINTABLE`
+                None if param.eq_ignore_ascii_case("QUOTED-PRINTABLE") => {
+                    ("ENCODING".to_owned(), param.clone())
+                }
+                None => ("TYPE".to_owned(), param.clone()),
+            })
+            .collect::<Vec<_>>();
+
+        let value = if params
+            .iter()
+            .any(|(key, value)| key == "ENCODING" && value.eq_ignore_ascii_case("QUOTED-PRINTABLE"))
+        {
+            decode_quoted_printable(raw_value)
+        } else {
+            raw_value.to_owned()
+        };
+
+        Some(Self {
+            name,
+            params,
+            value,
+        })
+    }
+
+    fn label(&self) -> Label {
+        let kinds = self
+            .params
+            .iter()
+            .filter(|(key, _)| key == "TYPE")
+            .flat_map(|(_, value)| value.split(','))
+            .map(str::to_ascii_lowercase)
+            .collect::<Vec<_>>();
+
+        if kinds.iter().any(|kind| kind == "cell" || kind == "iphone") {
+            Label::Mobile
+        } else if kinds.iter().any(|kind| kind == "work") {
+            Label::Work
+        } else if kinds.iter().any(|kind| kind == "home") {
+            Label::Home
+        } else {
+            Label::Other
+        }
+    }
+
+    fn is_preferred(&self) -> bool {
+        self.params.iter().any(|(key, value)| {
+            (key == "PREF")
+                || (key == "TYPE"
+                    && value
+                        .split(',')
+                        .any(|kind| kind.eq_ignore_ascii_case("pref")))
+        })
+    }
+}
+
+/// Splits a content line at the first `:` that is not inside a quoted
+/// parameter value.
+fn split_head(line: &str) -> Option<(&str, &str)> {
+    let mut in_quotes = false;
+
+    for (index, character) in line.char_indices() {
+        match character {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                let (head, value) = line.split_at(index);
+                return Some((head, value.strip_prefix(':').unwrap_or(value)));
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn split_unquoted(input: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut in_quotes = false;
+
+    for character in input.chars() {
+        if character == '"' {
+            in_quotes = !in_quotes;
+        }
+
+        if character == separator && !in_quotes {
+            parts.push(String::new());
+        } else if let Some(part) = parts.last_mut() {
+            part.push(character);
+        }
+    }
+
+    parts
+}
+
+/// Splits a structured value (e.g. `N`) on `separator`, honouring `\`
+/// escapes and unescaping each component.
+fn split_structured(value: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut characters = value.chars();
+
+    while let Some(character) = characters.next() {
+        let Some(part) = parts.last_mut() else {
+            break;
+        };
+
+        if character == '\\' {
+            part.push(character);
+            if let Some(escaped) = characters.next() {
+                part.push(escaped);
+            }
+        } else if character == separator {
+            parts.push(String::new());
+        } else {
+            part.push(character);
+        }
+    }
+
+    parts.iter().map(|part| unescape_text(part)).collect()
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut characters = value.chars();
+
+    while let Some(character) = characters.next() {
+        if character == '\\' {
+            match characters.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(escaped) => unescaped.push(escaped),
+                None => unescaped.push(character),
+            }
+        } else {
+            unescaped.push(character);
+        }
+    }
+
+    unescaped
+}
+
+fn decode_quoted_printable(value: &str) -> String {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut characters = value.chars();
+
+    while let Some(character) = characters.next() {
+        if character != '=' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+
+        let hex: String = characters.by_ref().take(2).collect();
+        if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+            bytes.push(byte);
+        } else {
+            bytes.push(b'=');
+            bytes.extend_from_slice(hex.as_bytes());
+        }
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn pick<'a>(properties: &'a [Property], name: &str) -> Option<&'a Property> {
+    let mut matching = properties
+        .iter()
+        .filter(|property| property.name == name && !property.value.trim().is_empty());
+
+    let first = matching.next()?;
+
+    Some(
+        std::iter::once(first)
+            .chain(matching)
+            .find(|property| property.is_preferred())
+            .unwrap_or(first),
+    )
+}
+
+fn card_to_contact(properties: &[Property], region: &str) -> crate::Result<OptionalContact> {
+    let formatted_name = pick(properties, "FN").map(|property| unescape_text(&property.value));
+    let nickname = pick(properties, "NICKNAME")
+        .and_then(|property| split_structured(&property.value, ',').into_iter().next());
+
+    let (mut first_name, last_name) = pick(properties, "N").map_or((None, None), |property| {
+        let mut components = split_structured(&property.value, ';').into_iter();
+        let last_name = components.next().filter(|name| !name.is_empty());
+        let first_name = components.next().filter(|name| !name.is_empty());
+        (first_name, last_name)
+    });
+
+    if first_name.is_none() && last_name.is_none() {
+        first_name.clone_from(&formatted_name);
+    }
+
+    let email = pick(properties, "EMAIL").map(|property| property.value.trim().to_owned());
+    let emails = labeled_values(properties, "EMAIL");
+    if let Some(invalid) = emails
+        .iter()
+        .find(|email| utils::is_not_valid_email(&email.value))
+    {
+        return Err(Error::InvalidEmail(invalid.value.clone()));
+    }
+
+    let phone_number = pick(properties, "TEL").map(|property| phone_value(property).to_owned());
+    let phones = labeled_values(properties, "TEL");
+    if let Some(invalid) = phones
+        .iter()
+        .find(|phone| utils::is_not_valid_phone_number(&phone.value, region))
+    {
+        return Err(Error::InvalidPhone(invalid.value.clone()));
+    }
+
+    let birthday = match pick(properties, "BDAY") {
+        Some(property) => Some(property.value.parse::<Birthday>()?),
+        None => None,
+    };
+
+    Ok(OptionalContact {
+        first_name: Some(first_name.unwrap_or_default()),
+        last_name,
+        display_name: nickname.filter(|name| !name.is_empty()).or(formatted_name),
+        email,
+        phone_number,
+        birthday,
+        starred: pick_flag(properties, STARRED),
+        is_archived: pick_flag(properties, ARCHIVED),
+        created_at: pick_timestamp(properties, CREATED_AT),
+        last_seen_at: pick_timestamp(properties, LAST_SEEN_AT),
+        frequency: pick(properties, FREQUENCY)
+            .map(|property| unescape_text(&property.value).parse())
+            .transpose()?,
+        last_reminder_at: pick_timestamp(properties, LAST_REMINDER_AT),
+        external_id: pick(properties, "UID")
+            .map(|property| unescape_text(&property.value))
+            .filter(|uid| !uid.trim().is_empty()),
+        tags: properties
+            .iter()
+            .filter(|property| property.name == "CATEGORIES")
+            .flat_map(|property| split_structured(&property.value, ','))
+            .map(|tag| tag.trim().to_owned())
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        emails,
+        phones,
+        addresses: properties
+            .iter()
+            .filter(|property| property.name == "ADR")
+            .map(card_address)
+            .filter(|address| !address.is_empty())
+            .collect(),
+        notes: properties
+            .iter()
+            .filter(|property| property.name == "NOTE")
+            .map(|property| unescape_text(&property.value).trim().to_owned())
+            .filter(|note| !note.is_empty())
+            .collect(),
+        cleared: Vec::new(),
+    })
+}
+
+/// Reads an `ADR` value: post office box, extended address, street,
+/// locality, region, postal code and country, separated by `;`.
+fn card_address(property: &Property) -> PostalAddress {
+    let mut parts = split_structured(&property.value, ';').into_iter();
+    let mut next = || {
+        parts
+            .next()
+            .map(|part| part.trim().to_owned())
+            .unwrap_or_default()
+    };
+
+    let street = [next(), next(), next()]
+        .into_iter()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    PostalAddress {
+        // A cell address is none of home, work or other, so it is kept as other
+        label: Some(property.label())
+            .filter(|label| label.fits_address())
+            .unwrap_or_default(),
+        street,
+        locality: next(),
+        region: next(),
+        postal_code: next(),
+        country: next(),
+    }
+}
+
+/// Every non-empty `EMAIL` or `TEL` of a card, labelled after its `TYPE`.
+fn labeled_values(properties: &[Property], name: &str) -> Vec<Labeled> {
+    properties
+        .iter()
+        .filter(|property| property.name == name && !property.value.trim().is_empty())
+        .map(|property| Labeled::new(property.label(), phone_value(property)))
+        .collect()
+}
+
+/// The value of a property without the `tel:` scheme vCard 4.0 puts on
+/// phone numbers.
+fn phone_value(property: &Property) -> &str {
+    let value = property.value.trim();
+    value.strip_prefix("tel:").unwrap_or(value)
+}
+
+fn pick_flag(properties: &[Property], name: &str) -> Option<bool> {
+    pick(properties, name).map(|property| property.value.trim().eq_ignore_ascii_case("true"))
+}
+
+fn pick_timestamp(properties: &[Property], name: &str) -> Option<DateTime<Utc>> {
+    pick(properties, name).and_then(|property| {
+        DateTime::parse_from_rfc3339(property.value.trim())
+            .ok()
+            .map(|timestamp| timestamp.with_timezone(&Utc))
+    })
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum VcardVersion {
+    /// vCard 3.0 (RFC 2426), for older address books
+    V3,
+    /// vCard 4.0 (RFC 6350)
+    #[default]
+    V4,
+}
+
+/// Serialises contacts to a vCard document, one card per contact.
+///
+/// Every email, phone number, address, tag and note of a contact is written,
+/// along with a `UID` that importing the file again matches on. Fields
+/// without a standard vCard property (starred, archived and the
+/// keep-in-touch metadata) are written as `X-NBD-*` properties so that
+/// importing the file again restores them.
+#[must_use]
+pub fn contacts_to_vcards(cards: &[ContactCard], version: VcardVersion) -> String {
+    cards
+        .iter()
+        .map(|card| contact_to_vcard(card, version))
+        .collect()
+}
+
+#[must_use]
+pub fn contact_to_vcard(card: &ContactCard, version: VcardVersion) -> String {
+    let contact = &card.contact.contact;
+    let mut lines = vec!["BEGIN:VCARD".to_owned()];
+
+    lines.push(match version {
+        VcardVersion::V3 => "VERSION:3.0".to_owned(),
+        VcardVersion::V4 => "VERSION:4.0".to_owned(),
+    });
+
+    lines.push(format!("UID:{}", escape_text(&card.contact.uid())));
+
+    let formatted_name = if contact.display_name.trim().is_empty() {
+        format!("{} {}", contact.first_name, contact.last_name)
+    } else {
+        contact.display_name.clone()
+    };
+    lines.push(format!("FN:{}", escape_text(formatted_name.trim())));
+    lines.push(format!(
+        "N:{};{};;;",
+        escape_text(&contact.last_name),
+        escape_text(&contact.first_name)
+    ));
+
+    if card.emails.is_empty() {
+        if !contact.email.is_empty() {
+            lines.push(format!("EMAIL:{}", escape_text(&contact.email)));
+        }
+    } else {
+        for email in &card.emails {
+            lines.push(format!(
+                "EMAIL{}:{}",
+                type_params(email.label, email.is_preferred, version),
+                escape_text(&email.email)
+            ));
+        }
+    }
+
+    let tel = match version {
+        VcardVersion::V3 => "TEL",
+        VcardVersion::V4 => "TEL;VALUE=text",
+    };
+
+    if card.phones.is_empty() {
+        if !contact.phone_number.is_empty() {
+            lines.push(format!("{tel}:{}", escape_text(&contact.phone_number)));
+        }
+    } else {
+        for phone in &card.phones {
+            lines.push(format!(
+                "{tel}{}:{}",
+                type_params(phone.label, phone.is_preferred, version),
+                escape_text(&phone.phone_number)
+            ));
+        }
+    }
+
+    for indexed in &card.addresses {
+        let address = &indexed.address;
+        lines.push(format!(
+            "ADR{}:;;{};{};{};{};{}",
+            type_params(address.label, false, version),
+            escape_text(&address.street),
+            escape_text(&address.locality),
+            escape_text(&address.region),
+            escape_text(&address.postal_code),
+            escape_text(&address.country)
+        ));
+    }
+
+    if let Some(birthday) = contact.birthday {
+        lines.push(format_birthday(birthday, version));
+    }
+
+    lines.push(format!(
+        "REV:{}",
+        contact.updated_at.format("%Y%m%dT%H%M%SZ")
+    ));
+    if !card.tags.is_empty() {
+        let tags: Vec<String> = card.tags.iter().map(|tag| escape_text(tag)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+
+    for note in &card.notes {
+        lines.push(format!("NOTE:{}", escape_text(&note.body)));
+    }
+
+    lines.push(format!("{STARRED}:{}", contact.starred));
+    lines.push(format!("{ARCHIVED}:{}", contact.is_archived));
+    lines.push(format!("{CREATED_AT}:{}", contact.created_at.to_rfc3339()));
+
+    if let Some(last_seen_at) = contact.last_seen_at {
+        lines.push(format!("{LAST_SEEN_AT}:{}", last_seen_at.to_rfc3339()));
+    }
+
+    if let Some(frequency) = &contact.frequency {
+        lines.push(format!(
+            "{FREQUENCY}:{}",
+            escape_text(&frequency.to_string())
+        ));
+    }
+
+    if let Some(last_reminder_at) = contact.last_reminder_at {
+        lines.push(format!(
+            "{LAST_REMINDER_AT}:{}",
+            last_reminder_at.to_rfc3339()
+        ));
+    }
+
+    lines.push("END:VCARD".to_owned());
+
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Writes the `TYPE` of an email, phone or address, with the preferred one
+/// marked by `PREF=1` in 4.0 and by `TYPE=pref` in 3.0.
+fn type_params(label: Label, preferred: bool, version: VcardVersion) -> String {
+    let kind = match label {
+        Label::Home => Some("home"),
+        Label::Work => Some("work"),
+        Label::Mobile => Some("cell"),
+        Label::Other => None,
+    };
+
+    match version {
+        VcardVersion::V3 => {
+            let kinds: Vec<&str> = kind
+                .into_iter()
+                .chain(preferred.then_some("pref"))
+                .collect();
+
+            if kinds.is_empty() {
+                String::new()
+            } else {
+                format!(";TYPE={}", kinds.join(","))
+            }
+        }
+        VcardVersion::V4 => {
+            let mut params = kind.map(|kind| format!(";TYPE={kind}")).unwrap_or_default();
+
+            if preferred {
+                params.push_str(";PREF=1");
+            }
+
+            params
+        }
+    }
+}
+
+/// Writes `BDAY` as a basic date in 4.0 and an extended one in 3.0. A
+/// year-less birthday becomes `--MMDD` or `--MM-DD`.
+fn format_birthday(birthday: Birthday, version: VcardVersion) -> String {
+    let (month, day) = (birthday.month(), birthday.day());
+
+    match (birthday.year(), version) {
+        (Some(year), VcardVersion::V3) => format!("BDAY:{year:04}-{month:02}-{day:02}"),
+        (Some(year), VcardVersion::V4) => format!("BDAY:{year:04}{month:02}{day:02}"),
+        (None, VcardVersion::V3) => format!("BDAY:--{month:02}-{day:02}"),
+        (None, VcardVersion::V4) => format!("BDAY:--{month:02}{day:02}"),
+    }
+}
+
+/// Escapes a TEXT value; iCalendar (RFC 5545) uses the same rules.
+pub(super) fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for character in value.chars() {
+        match character {
+            '\\' | ',' | ';' => {
+                escaped.push('\\');
+                escaped.push(character);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(character),
+        }
+    }
+
+    escaped
+}
+
+/// Folds a content line at 75 octets and terminates it with CRLF, as
+/// required by RFC 6350 section 3.2 and RFC 5545 section 3.1.
+pub(super) fn fold_line(line: &str) -> String {
+    const LINE_LIMIT: usize = 75;
+
+    let mut folded = String::with_capacity(line.len());
+    let mut line_length: usize = 0;
+
+    for character in line.chars() {
+        let character_length = character.len_utf8();
+
+        if line_length.saturating_add(character_length) > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+
+        folded.push(character);
+        line_length = line_length.saturating_add(character_length);
+    }
+
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::models::IndexedContact;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn should_parse_a_vcard_4() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\r\n\
+            VERSION:4.0\r\n\
+            FN:Ada Lovelace\r\n\
+            N:Lovelace;Ada;;;\r\n\
+            EMAIL;TYPE=work:ada@lovelace.com\r\n\
+            TEL;VALUE=uri;TYPE=cell:tel:201-555-0133\r\n\
+            ADR;TYPE=home:;;12 St James's Square;London;;SW1Y 4JH;United Kingdom\r\n\
+            BDAY:18151210\r\n\
+            END:VCARD\r\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        let expected_contact = OptionalContact {
+            first_name: Some("Ada".to_string()),
+            last_name: Some("Lovelace".to_string()),
+            display_name: Some("Ada Lovelace".to_string()),
+            email: Some("ada@lovelace.com".to_string()),
+            phone_number: Some("201-555-0133".to_string()),
+            birthday: Birthday::new(Some(1815), 12, 10),
+            emails: vec![Labeled::new(Label::Work, "ada@lovelace.com")],
+            addresses: vec![PostalAddress {
+                label: Label::Home,
+                street: "12 St James's Square".to_string(),
+                locality: "London".to_string(),
+                postal_code: "SW1Y 4JH".to_string(),
+                country: "United Kingdom".to_string(),
+                ..PostalAddress::default()
+            }],
+            phones: vec![Labeled::new(Label::Mobile, "201-555-0133")],
+            ..OptionalContact::default()
+        };
+
+        assert_eq!(contacts, vec![expected_contact]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_multiple_cards() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:3.0\nN:Huxley;Aldous\nFN:Aldous Huxley\nEND:VCARD\n\
+            BEGIN:VCARD\nVERSION:3.0\nN:Lewis;Clive\nFN:C. S. Lewis\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(contacts.len(), 2);
+        assert_eq!(
+            contacts.get(1).unwrap().last_name,
+            Some("Lewis".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_unfold_folded_lines() -> anyhow::Result<()> {
+        let input =
+            "BEGIN:VCARD\nVERSION:4.0\nFN:Augusta Ada King\n , Countess of Lovelace\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().display_name,
+            Some("Augusta Ada King, Countess of Lovelace".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_decode_quoted_printable_values() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:2.1\n\
+            N;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:M=C3=BCller;J=C3=\n=BCrgen\n\
+            END:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+        let contact = contacts.first().unwrap();
+
+        assert_eq!(contact.first_name, Some("Jürgen".to_string()));
+        assert_eq!(contact.last_name, Some("Müller".to_string()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_categories_as_tags() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:4.0\nFN:Ada\nCATEGORIES:work,climbing\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().tags,
+            vec!["work".to_string(), "climbing".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_prefer_nickname_for_display_name() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:3.0\nN:Lovelace;Ada\nFN:Ada Lovelace\nNICKNAME:Addy,Countess\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().display_name,
+            Some("Addy".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_use_formatted_name_when_name_is_missing() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:4.0\nFN:Cher\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().first_name,
+            Some("Cher".to_string())
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_pick_the_preferred_email() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:3.0\nFN:Ada\nEMAIL;TYPE=home:home@example.com\n\
+            EMAIL;TYPE=work,pref:work@example.com\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().email,
+            Some("work@example.com".to_string())
+        );
+        assert_eq!(
+            contacts.first().unwrap().emails,
+            vec![
+                Labeled::new(Label::Home, "home@example.com"),
+                Labeled::new(Label::Work, "work@example.com"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_import_notes() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:3.0\nFN:Ada\nNOTE:Kids: Sam and Ria\\nLikes tea\n\
+            NOTE: \nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().notes,
+            vec!["Kids: Sam and Ria\nLikes tea".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_month_and_day_of_yearless_birthday() -> anyhow::Result<()> {
+        let input = "BEGIN:VCARD\nVERSION:4.0\nFN:Ada\nBDAY:--1210\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION)?;
+
+        assert_eq!(
+            contacts.first().unwrap().birthday,
+            Birthday::new(None, 12, 10)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_export_yearless_birthday() {
+        let mut indexed_contact = example_indexed_contact();
+        indexed_contact.contact.birthday = Birthday::new(None, 12, 10);
+
+        let v4 = contact_to_vcard(&indexed_contact.clone().into(), VcardVersion::V4);
+        let v3 = contact_to_vcard(&indexed_contact.into(), VcardVersion::V3);
+
+        assert!(v4.contains("BDAY:--1210\r\n"));
+        assert!(v3.contains("BDAY:--12-10\r\n"));
+    }
+
+    #[test]
+    fn should_error_when_given_an_invalid_email() {
+        let input = "BEGIN:VCARD\nVERSION:4.0\nFN:Ada\nEMAIL:invalid@email\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION);
+
+        assert_eq!(
+            contacts.unwrap_err().to_string(),
+            "invalid@email is invalid"
+        );
+    }
+
+    #[test]
+    fn should_error_when_given_an_invalid_birthday() {
+        let input = "BEGIN:VCARD\nVERSION:4.0\nFN:Ada\nBDAY:1970-13-32\nEND:VCARD\n";
+
+        let contacts = vcards_to_contacts(input, utils::DEFAULT_REGION);
+
+        assert!(matches!(
+            contacts.unwrap_err(),
+            Error::InvalidDate(date) if date == "1970-13-32"
+        ));
+    }
+
+    fn example_indexed_contact() -> IndexedContact {
+        let mut contact = crate::models::Contact::new(
+            "Ada",
+            "Lovelace",
+            "ada@lovelace.com",
+            "201-555-0133",
+            "1815-12-10",
+            "US",
+        )
+        .expect("Example contact");
+        contact.display_name = "Ada, Countess of Lovelace".to_string();
+        contact.starred = true;
+        contact.frequency = "monthly".parse().ok();
+
+        IndexedContact { id: 1, contact }
+    }
+
+    #[test]
+    fn should_export_a_vcard_4() {
+        let vcard = contact_to_vcard(&example_indexed_contact().into(), VcardVersion::V4);
+
+        assert!(vcard.starts_with("BEGIN:VCARD\r\nVERSION:4.0\r\n"));
+        assert!(vcard.contains("FN:Ada\\, Countess of Lovelace\r\n"));
+        assert!(vcard.contains("N:Lovelace;Ada;;;\r\n"));
+        assert!(vcard.contains("EMAIL:ada@lovelace.com\r\n"));
+        assert!(vcard.contains("TEL;VALUE=text:201-555-0133\r\n"));
+        assert!(vcard.contains("BDAY:18151210\r\n"));
+        assert!(vcard.contains("X-NBD-STARRED:true\r\n"));
+        assert!(vcard.contains("X-NBD-FREQUENCY:monthly\r\n"));
+        assert!(vcard.ends_with("END:VCARD\r\n"));
+    }
+
+    #[test]
+    fn should_export_a_vcard_3() {
+        let vcard = contact_to_vcard(&example_indexed_contact().into(), VcardVersion::V3);
+
+        assert!(vcard.contains("VERSION:3.0\r\n"));
+        assert!(vcard.contains("TEL:201-555-0133\r\n"));
+        assert!(vcard.contains("BDAY:1815-12-10\r\n"));
+    }
+
+    fn example_card() -> ContactCard {
+        use crate::models::{ContactEmail, ContactPhone, IndexedAddress, Note};
+
+        let mut card = ContactCard::from(example_indexed_contact());
+        card.tags = vec!["math".to_string(), "poets, maybe".to_string()];
+        card.emails = vec![
+            ContactEmail {
+                contact_id: 1,
+                label: Label::Home,
+                email: "ada@lovelace.com".to_string(),
+                is_preferred: true,
+            },
+            ContactEmail {
+                contact_id: 1,
+                label: Label::Work,
+                email: "ada@engine.org".to_string(),
+                is_preferred: false,
+            },
+        ];
+        card.phones = vec![ContactPhone {
+            contact_id: 1,
+            label: Label::Mobile,
+            phone_number: "201-555-0133".to_string(),
+            phone_e164: Some("+12015550133".to_string()),
+            is_preferred: true,
+        }];
+        card.addresses = vec![IndexedAddress {
+            id: 1,
+            contact_id: 1,
+            address: PostalAddress {
+                label: Label::Home,
+                street: "12 St James's Square".to_string(),
+                locality: "London".to_string(),
+                region: String::new(),
+                postal_code: "SW1Y 4JH".to_string(),
+                country: "United Kingdom".to_string(),
+            },
+        }];
+        card.notes = vec![Note {
+            id: 1,
+            contact_id: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            body: "Wrote the first program; loves poetry".to_string(),
+        }];
+
+        card
+    }
+
+    #[test]
+    fn should_export_everything_on_a_card() {
+        let card = example_card();
+        let v4 = contact_to_vcard(&card, VcardVersion::V4);
+        let v3 = contact_to_vcard(&card, VcardVersion::V3);
+
+        assert!(v4.contains(&format!("UID:{}\r\n", card.contact.uid())));
+        assert!(v4.contains("EMAIL;TYPE=home;PREF=1:ada@lovelace.com\r\n"));
+        assert!(v4.contains("EMAIL;TYPE=work:ada@engine.org\r\n"));
+        assert!(v4.contains("TEL;VALUE=text;TYPE=cell;PREF=1:201-555-0133\r\n"));
+        assert!(
+            v4.contains("ADR;TYPE=home:;;12 St James's Square;London;;SW1Y 4JH;United Kingdom\r\n")
+        );
+        assert!(v4.contains("CATEGORIES:math,poets\\, maybe\r\n"));
+        assert!(v4.contains("NOTE:Wrote the first program\\; loves poetry\r\n"));
+        assert!(v3.contains("EMAIL;TYPE=home,pref:ada@lovelace.com\r\n"));
+        assert!(v3.contains("TEL;TYPE=cell,pref:201-555-0133\r\n"));
+    }
+
+    #[test]
+    fn should_read_back_everything_on_a_card() -> anyhow::Result<()> {
+        let card = example_card();
+
+        let vcard = contacts_to_vcards(std::slice::from_ref(&card), VcardVersion::V3);
+        let contacts = vcards_to_contacts(&vcard, utils::DEFAULT_REGION)?;
+        let contact = contacts.first().unwrap();
+
+        assert_eq!(contact.external_id, Some(card.contact.uid()));
+        assert_eq!(contact.email, Some("ada@lovelace.com".to_string()));
+        assert_eq!(
+            contact.emails,
+            vec![
+                Labeled::new(Label::Home, "ada@lovelace.com"),
+                Labeled::new(Label::Work, "ada@engine.org"),
+            ]
+        );
+        assert_eq!(
+            contact.phones,
+            vec![Labeled::new(Label::Mobile, "201-555-0133")]
+        );
+        assert_eq!(contact.addresses, vec![card.addresses[0].address.clone()]);
+        assert_eq!(contact.tags, card.tags);
+        assert_eq!(
+            contact.notes,
+            vec!["Wrote the first program; loves poetry".to_string()]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_skip_unknown_birthday_when_exporting() {
+        let mut indexed_contact = example_indexed_contact();
+        indexed_contact.contact.birthday = None;
+
+        let vcard = contact_to_vcard(&indexed_contact.into(), VcardVersion::V4);
+
+        assert!(!vcard.contains("BDAY"));
+    }
+
+    #[test]
+    fn should_fold_long_lines() {
+        let mut indexed_contact = example_indexed_contact();
+        indexed_contact.contact.display_name = "a".repeat(100);
+
+        let vcard = contact_to_vcard(&indexed_contact.into(), VcardVersion::V4);
+
+        assert!(vcard.split("\r\n").all(|line| line.len() <= 75));
+        assert!(vcard.contains(&format!("FN:{}\r\n {}", "a".repeat(72), "a".repeat(28))));
+    }
+
+    #[test]
+    fn should_round_trip_through_import() -> anyhow::Result<()> {
+        let indexed_contact = example_indexed_contact();
+
+        let vcard = contacts_to_vcards(&[indexed_contact.clone().into()], VcardVersion::V4);
+        let contacts = vcards_to_contacts(&vcard, utils::DEFAULT_REGION)?;
+        let contact = contacts.first().unwrap();
+
+        assert_eq!(contact.first_name, Some("Ada".to_string()));
+        assert_eq!(contact.last_name, Some("Lovelace".to_string()));
+        assert_eq!(
+            contact.display_name,
+            Some("Ada, Countess of Lovelace".to_string())
+        );
+        assert_eq!(contact.birthday, indexed_contact.contact.birthday);
+        assert_eq!(contact.starred, Some(true));
+        assert_eq!(contact.is_archived, Some(false));
+        assert_eq!(contact.frequency, "monthly".parse().ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_error_if_file_is_empty() {
+        let temp_vcf = NamedTempFile::with_suffix(".vcf").unwrap();
+
+        let temp_vcf = temp_vcf.path().to_str().unwrap();
+        let result = process_vcard_to_contacts(temp_vcf, utils::DEFAULT_REGION);
+
+        assert_eq!(result.unwrap_err().to_string(), "vCard file is empty");
+    }
+
+    #[test]
+    fn should_error_if_file_has_no_cards() -> anyhow::Result<()> {
+        let mut temp_vcf = NamedTempFile::with_suffix(".vcf")?;
+        writeln!(temp_vcf, "first_name\nAlice")?;
+
+        let temp_vcf = temp_vcf.path().to_str().unwrap();
+        let result = process_vcard_to_contacts(temp_vcf, utils::DEFAULT_REGION);
+
+        assert!(result.is_err());
+
+        Ok(())
+    }
+}
@@ -7,11 +7,22 @@ mod tests {
         db::{ContactRepo, Repo},
         models::Contact,
     };
+    use predicates::prelude::PredicateBooleanExt;
     use serial_test::serial;
     use sqlx::SqlitePool;
 
     fn create_command() -> Command {
-        cargo::cargo_bin_cmd!("nbd-cli")
+        let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+        cmd.arg("--db")
+            .arg(get_database_path())
+            .env("NBD_CONFIG_DIR", get_config_dir());
+        cmd
+    }
+
+    /// A config directory without a config.toml, so the settings of whoever
+    /// runs the tests do not apply
+    fn get_config_dir() -> std::path::PathBuf {
+        std::env::temp_dir().join("nbd-cli-tests-without-config")
     }
 
     fn get_cli_name() -> String {
@@ -24,6 +35,16 @@ mod tests {
         std::env::var("DATABASE_URL").unwrap_or_else(|_| "sqlite://data/contacts.db".to_string())
     }
 
+    /// The file `DATABASE_URL` points at, for the `--db` flag
+    fn get_database_path() -> String {
+        let database_url = get_database_url();
+        let path = database_url
+            .trim_start_matches("sqlite://")
+            .trim_start_matches("sqlite:");
+
+        path.split('?').next().unwrap_or(path).to_string()
+    }
+
     async fn create_repo() -> Result<Repo<SqlitePool>> {
         let database_url = get_database_url();
         let pool = SqlitePool::connect(&database_url).await?;
@@ -31,13 +52,13 @@ mod tests {
     }
 
     fn create_lewis_carroll_contact() -> Result<Contact> {
-        Contact::builder()
+        Ok(Contact::builder()
             .first_name("Lewis")
             .last_name("Carroll")
             .email("lewis@wonderland.com")
-            .phone_number("777-777-7777")
+            .phone_number("212-555-0177")
             .birthday("1832-1-27")
-            .build()
+            .build()?)
     }
 
     fn get_expected_table_header() -> Vec<&'static str> {
@@ -45,7 +66,7 @@ mod tests {
             "+----+------------+-----------+---------------+----------------------+--------------+------------+",
             "| id | first_name | last_name | display_name  | email                | phone_number | birthday   |",
             "+----+------------+-----------+---------------+----------------------+--------------+------------+",
-            "| 1  | Lewis      | Carroll   | Lewis Carroll | lewis@wonderland.com | 777-777-7777 | 1832-01-27 |",
+            "| 1  | Lewis      | Carroll   | Lewis Carroll | lewis@wonderland.com | 212-555-0177 | 1832-01-27 |",
             "+----+------------+-----------+---------------+----------------------+--------------+------------+",
         ]
     }
@@ -60,10 +81,46 @@ mod tests {
 
         sqlx::query!("DELETE FROM contacts").execute(&pool).await?;
 
+        sqlx::query("DELETE FROM contact_tags")
+            .execute(&pool)
+            .await?;
+
+        sqlx::query("DELETE FROM tags").execute(&pool).await?;
+
+        sqlx::query("DELETE FROM interactions")
+            .execute(&pool)
+            .await?;
+
+        sqlx::query("DELETE FROM contact_emails")
+            .execute(&pool)
+            .await?;
+
+        sqlx::query("DELETE FROM contact_phones")
+            .execute(&pool)
+            .await?;
+
+        sqlx::query("DELETE FROM contact_addresses")
+            .execute(&pool)
+            .await?;
+
+        sqlx::query("DELETE FROM notes").execute(&pool).await?;
+
+        sqlx::query("DELETE FROM note_revisions")
+            .execute(&pool)
+            .await?;
+
         sqlx::query!("DELETE FROM SQLITE_SEQUENCE WHERE name = 'contacts'")
             .execute(&pool)
             .await?;
 
+        sqlx::query("DELETE FROM SQLITE_SEQUENCE WHERE name = 'contact_addresses'")
+            .execute(&pool)
+            .await?;
+
+        sqlx::query("DELETE FROM SQLITE_SEQUENCE WHERE name = 'notes'")
+            .execute(&pool)
+            .await?;
+
         sqlx::query!("PRAGMA foreign_keys = ON")
             .execute(&pool)
             .await?;
@@ -83,21 +140,42 @@ mod tests {
         cmd.arg("--help");
 
         let expected_output = [
-            "Usage: nbd-cli <COMMAND>",
+            "Usage: nbd-cli [OPTIONS] <COMMAND>",
             "",
             "Commands:",
-            "  init    Initialize a new contact book",
-            "  create  Create a contact",
-            "  edit    Edit a contact by ID",
-            "  show    Get all contacts",
-            "  get     Get a contact",
-            "  delete  Delete a contact",
-            "  import  Import contact via CSV",
-            "  help    Print this message or the help of the given subcommand(s)",
+            "  init             Initialize a new contact book, or the book named by --book",
+            "  create           Create a contact",
+            "  edit             Edit a contact by ID or name",
+            "  show             Get all contacts",
+            "  get              Get a contact",
+            "  search           Search contacts by name, email, phone or notes",
+            "  delete           Delete a contact",
+            "  dedupe           List contacts that look like duplicates",
+            "  merge            Merge a duplicate contact into another",
+            "  import           Import contacts via CSV or vCard",
+            "  export           Export contacts as vCard or CSV",
+            "  tag              Manage contact tags",
+            "  address          Manage postal addresses",
+            "  labels           Print mailing labels for contacts with an address",
+            "  note             Manage notes about contacts",
+            "  log              Log an interaction with a contact",
+            "  history          List past interactions with a contact",
+            "  due              List contacts who are due a catch-up",
+            "  birthdays        List upcoming birthdays",
+            "  export-calendar  Export birthdays and reminders as an iCalendar file",
+            "  config           Read and change settings in config.toml",
+            "  books            List, rename and remove books, or copy contacts between them",
+            "  db               Inspect, migrate and check the database of the open book",
+            "  backup           Save a copy of the open book and prune old copies",
+            "  restore          Replace the open book with a backup",
+            "  help             Print this message or the help of the given subcommand(s)",
             "",
             "Options:",
-            "  -h, --help     Print help",
-            "  -V, --version  Print version",
+            "      --output <OUTPUT>  Output format of commands that print contacts; defaults to the output setting, or table [possible values: table, json, jsonl, csv]",
+            "      --db <PATH>        Contact book to open instead of contacts.db in the config directory",
+            "      --book <NAME>      Named book of the config directory to open, unless --db is given; defaults to the book setting, or the default book",
+            "  -h, --help             Print help",
+            "  -V, --version          Print version",
         ];
 
         cmd.assert()
@@ -119,7 +197,7 @@ mod tests {
             .arg("--email")
             .arg("test@test.com")
             .arg("--phone-number")
-            .arg("123-321-1233")
+            .arg("201-555-0133")
             .arg("--birthday")
             .arg("1970-01-01");
 
@@ -161,6 +239,7 @@ mod tests {
 
         cmd.assert()
             .failure()
+            .code(3)
             .stderr(predicates::str::contains("That Contact ID does not exist"));
 
         Ok(())
@@ -177,220 +256,1807 @@ mod tests {
             .arg("--email")
             .arg("test@.com")
             .arg("--phone-number")
-            .arg("123-321-1233");
+            .arg("201-555-0133");
 
         cmd.assert()
             .failure()
+            .code(4)
             .stderr(predicates::str::contains("test@.com is invalid"));
     }
 
-    #[test]
-    fn should_error_when_providing_invalid_phone_number() {
-        let mut cmd = create_command();
-        cmd.arg("create")
-            .arg("--first-name")
-            .arg("First")
-            .arg("--last-name")
-            .arg("Last")
-            .arg("--email")
-            .arg("test@com.com")
-            .arg("--phone-number")
-            .arg("123-321-123");
-
-        cmd.assert()
-            .failure()
-            .stderr(predicates::str::contains("123-321-123 is invalid"));
-    }
-
-    #[test]
-    fn should_error_when_invalid_args() {
-        let mut cmd = create_command();
-        cmd.arg("First").arg("Last").arg("32321123");
-
-        let stderr = format!("Usage: {} <COMMAND>", get_cli_name());
-
-        cmd.assert()
-            .failure()
-            .stderr(predicates::str::contains(stderr));
-    }
-
     #[tokio::test]
     #[serial]
-    async fn should_import_one_contact_when_importing_alice_csv() -> Result<()> {
+    async fn should_manage_labelled_emails_and_phones() -> Result<()> {
         clean_database().await.expect("Failed to clean database");
 
         let mut cmd = create_command();
-        cmd.arg("import").arg("tests/fixtures/alice.csv");
+        cmd.args([
+            "create",
+            "--first-name",
+            "Ada",
+            "--email",
+            "work:ada@work.com",
+            "--email",
+            "home:ada@home.com",
+            "--phone-number",
+            "mobile:201-555-0123",
+        ])
+        .assert()
+        .success();
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.args(["--output", "json", "get", "1"])
+            .assert()
             .success()
-            .stdout(predicates::str::contains("Successfully imported 1 contact"));
+            .stdout(predicates::str::contains("\"email\": \"ada@work.com\""))
+            .stdout(predicates::str::contains("\"label\": \"home\""))
+            .stdout(predicates::str::contains("\"label\": \"mobile\""));
 
-        let data_repo = create_repo().await?;
+        let mut cmd = create_command();
+        cmd.args([
+            "edit",
+            "1",
+            "--email",
+            "home:ada@home.com",
+            "--remove-email",
+            "ada@work.com",
+        ])
+        .assert()
+        .success();
+
+        let contact = create_repo().await?.get_contact_by_id(1).await?;
+        assert_eq!(contact.contact.email, "ada@home.com");
 
-        let contacts = data_repo.get_all_contacts().await?;
+        let mut cmd = create_command();
+        cmd.args(["get", "1"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Emails"))
+            .stdout(predicates::str::contains("Phones"))
+            .stdout(predicates::str::contains("ada@work.com").not());
 
-        assert_eq!(contacts.len(), 1);
+        let mut cmd = create_command();
+        cmd.args(["edit", "1", "--email", "wrok:ada@work.com"])
+            .assert()
+            .failure()
+            .code(17);
 
         Ok(())
     }
 
     #[tokio::test]
     #[serial]
-    async fn should_import_example_csv_with_three_rows() -> Result<()> {
-        clean_database().await?;
-        let mut cmd = create_command();
-        cmd.arg("import").arg("tests/fixtures/example.csv");
-
-        cmd.assert()
-            .success()
-            .stdout(predicates::str::contains("Successfully imported"));
+    async fn should_manage_addresses_and_print_mailing_labels() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
 
-        Ok(())
-    }
+        for name in ["Ada", "Charles"] {
+            let mut cmd = create_command();
+            cmd.args(["create", "--first-name", name, "--last-name", "Lovelace"])
+                .assert()
+                .success();
+        }
 
-    #[test]
-    fn should_fail_when_given_incorrect_file() {
         let mut cmd = create_command();
-        cmd.arg("import").arg("tests/fixtures/example.txt");
+        cmd.args([
+            "address",
+            "add",
+            "1",
+            "--label",
+            "work",
+            "--street",
+            "1 Analytical Way",
+            "--locality",
+            "London",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Added address 1 to contact 1"));
 
-        cmd.assert()
-            .failure()
-            .stderr(predicates::str::contains("File must have .csv extension"));
-    }
+        let mut cmd = create_command();
+        cmd.args([
+            "address",
+            "add",
+            "1",
+            "--label",
+            "home",
+            "--street",
+            "12 St James's Square",
+            "--locality",
+            "London",
+            "--postal-code",
+            "SW1Y 4JH",
+        ])
+        .assert()
+        .success();
 
-    #[test]
-    fn should_fail_when_given_blank_csv() {
         let mut cmd = create_command();
-        cmd.arg("import").arg("tests/fixtures/blank.csv");
+        cmd.args(["address", "edit", "2", "--country", "United Kingdom"])
+            .assert()
+            .success();
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.args(["address", "edit", "2"])
+            .assert()
             .failure()
-            .stderr(predicates::str::contains("CSV file is empty"));
-    }
+            .code(7);
 
-    #[tokio::test]
-    #[serial]
-    async fn should_say_no_contacts_when_contacts_are_empty() -> Result<()> {
-        clean_database().await?;
+        let mut cmd = create_command();
+        cmd.args(["labels", "--label", "home"])
+            .assert()
+            .success()
+            .stdout("Ada Lovelace\n12 St James's Square\nLondon, SW1Y 4JH\nUnited Kingdom\n")
+            .stderr(predicates::str::contains(
+                "Skipped 1 contact(s) without an address",
+            ));
 
         let mut cmd = create_command();
-        cmd.arg("show");
+        cmd.args(["--output", "csv", "labels"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "name,street,locality,region,postal_code,country\n\
+                 Ada Lovelace,1 Analytical Way,London,,,\n",
+            ));
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.args(["address", "rm", "1", "7"])
+            .assert()
             .success()
-            .stdout(predicates::str::contains("No contacts yet!"));
+            .stdout("There is no address 7\n");
+
+        let mut cmd = create_command();
+        cmd.args(["address", "ls", "1"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("St James's Square"))
+            .stdout(predicates::str::contains("Analytical Way").not());
+
+        let mut cmd = create_command();
+        cmd.args(["address", "edit", "9", "--street", "Nowhere"])
+            .assert()
+            .failure()
+            .code(3);
+
         Ok(())
     }
 
     #[tokio::test]
     #[serial]
-    async fn should_show_one_contact_when_one_contact_available() -> Result<()> {
-        clean_database().await?;
+    async fn should_apply_settings_from_the_config_file() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
 
-        let data_repo = create_repo().await?;
-        let example_contact = create_lewis_carroll_contact()?;
+        let temp = tempfile::TempDir::new()?;
+        let config_dir = temp.path().join("nbd");
 
-        data_repo.save_contact(example_contact).await?;
+        let mut cmd = create_command();
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .args(["config", "path"])
+            .assert()
+            .success()
+            .stdout(format!("{}\n", config_dir.join("config.toml").display()));
+
+        for (key, value, message) in [
+            ("region", "gb", "Set region to GB\n"),
+            ("date_format", "%d/%m/%Y", "Set date_format to %d/%m/%Y\n"),
+            (
+                "show_columns",
+                "id, display_name, birthday",
+                "Set show_columns to id,display_name,birthday\n",
+            ),
+            ("show_sort", "last_name", "Set show_sort to last_name\n"),
+            ("cadence", "every 1 month", "Set cadence to monthly\n"),
+        ] {
+            let mut cmd = create_command();
+            cmd.env("NBD_CONFIG_DIR", &config_dir)
+                .args(["config", "set", key, value])
+                .assert()
+                .success()
+                .stdout(message);
+        }
 
         let mut cmd = create_command();
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .args(["config", "set", "colour", "blue"])
+            .assert()
+            .failure()
+            .code(19)
+            .stderr(predicates::str::contains("Unknown setting: colour"));
 
-        cmd.arg("show");
+        let mut cmd = create_command();
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .args(["config", "set", "output", "yaml"])
+            .assert()
+            .failure()
+            .code(19);
 
-        let expected = get_expected_table_header();
+        let mut cmd = create_command();
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .args(["config", "get", "cadence"])
+            .assert()
+            .success()
+            .stdout("monthly\n");
+
+        for (first_name, last_name) in [("Charles", "Babbage"), ("Ada", "Lovelace")] {
+            let mut cmd = create_command();
+            cmd.env("NBD_CONFIG_DIR", &config_dir)
+                .env_remove("NBD_REGION")
+                .args([
+                    "create",
+                    "--first-name",
+                    first_name,
+                    "--last-name",
+                    last_name,
+                    "--phone-number",
+                    "020 7946 0958",
+                    "--birthday",
+                    "1815-12-10",
+                ])
+                .assert()
+                .success();
+        }
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .args(["show", "--sort", "first_name"])
+            .assert()
             .success()
-            .stdout(predicates::str::contains(expected.join("\n")));
+            .stdout(predicates::str::contains(
+                "| 2  | Ada Lovelace    | 10/12/1815 |",
+            ))
+            .stdout(predicates::str::contains("phone_number").not());
+
+        let mut cmd = create_command();
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .args(["--output", "json", "show", "--sort", "last_name"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "\"phone_e164\": \"+442079460958\"",
+            ))
+            .stdout(predicates::str::contains("\"frequency\": \"monthly\""));
+
+        let mut cmd = create_command();
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .args(["config", "list"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("| editor       |"))
+            .stdout(predicates::str::contains("| region       | GB"));
+
         Ok(())
     }
 
     #[tokio::test]
     #[serial]
-    async fn should_show_two_contact_when_two_contact_available() -> Result<()> {
+    async fn should_edit_a_contact_as_a_document_until_it_is_valid() -> Result<()> {
         clean_database().await.expect("Failed to clean database");
 
-        let data_repo = create_repo().await?;
-        let example_contact = create_lewis_carroll_contact()?;
+        let mut cmd = create_command();
+        cmd.args(["create", "--first-name", "Ada", "--last-name", "Lovelace"])
+            .assert()
+            .success();
 
-        data_repo.save_contact(example_contact.clone()).await?;
-        data_repo.save_contact(example_contact).await?;
+        // The first save holds a bad email; the second, made with the error
+        // shown at the top, fixes it
+        let temp = tempfile::TempDir::new()?;
+        let editor = temp.path().join("editor.sh");
+        std::fs::write(
+            &editor,
+            "#!/bin/sh\n\
+             if grep -q '^# Error: ada@ is invalid' \"$1\"; then\n\
+               sed 's/^email = .*/email = \"ada@example.com\"/' \"$1\" > \"$1.new\"\n\
+             else\n\
+               sed -e 's/^email = .*/email = \"ada@\"/' -e 's/^starred = false/starred = true/' \\\n\
+                 -e 's/^birthday = .*/birthday = \"1815-12-10\"/' \"$1\" > \"$1.new\"\n\
+             fi\n\
+             mv \"$1.new\" \"$1\"\n",
+        )?;
+        std::process::Command::new("chmod")
+            .arg("+x")
+            .arg(&editor)
+            .status()?;
 
         let mut cmd = create_command();
+        cmd.args(["edit", "Ada", "--interactive"])
+            .env_remove("VISUAL")
+            .env("EDITOR", &editor)
+            .assert()
+            .success()
+            .stdout("Contact updated\n");
 
-        cmd.arg("show");
-
-        let expected = get_expected_table_header();
+        let mut cmd = create_command();
+        cmd.args(["--output", "json", "get", "1"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("\"email\": \"ada@example.com\""))
+            .stdout(predicates::str::contains("\"birthday\": \"1815-12-10\""))
+            .stdout(predicates::str::contains("\"starred\": true"));
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.args(["edit", "1", "-i"])
+            .env_remove("VISUAL")
+            .env("EDITOR", "true")
+            .assert()
             .success()
-            .stdout(predicates::str::contains(expected.join("\n")));
+            .stdout("Nothing changed\n");
+
+        let mut cmd = create_command();
+        cmd.args(["edit", "1", "-i", "--first-name", "Augusta"])
+            .assert()
+            .failure()
+            .code(2);
+
         Ok(())
     }
 
     #[tokio::test]
     #[serial]
-    async fn should_accept_a_firstname_and_birthday() {
+    async fn should_write_notes_and_open_the_editor_without_text() -> Result<()> {
         clean_database().await.expect("Failed to clean database");
 
         let mut cmd = create_command();
-        cmd.arg("create")
-            .arg("--first-name")
-            .arg("Molly")
-            .arg("--birthday")
-            .arg("1970-01-01");
+        cmd.args(["create", "--first-name", "Ada", "--last-name", "Lovelace"])
+            .assert()
+            .success();
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.args(["note", "add", "Ada", "Met", "at", "the", "Royal", "Society"])
+            .assert()
             .success()
-            .stdout(predicates::str::contains("Successfully saved contact"));
-    }
+            .stdout(predicates::str::contains("Added note 1 to contact 1"));
 
-    #[tokio::test]
-    #[serial]
-    async fn should_set_birthday_when_provided() -> Result<()> {
-        clean_database().await.expect("Failed to clean database");
+        let temp = tempfile::TempDir::new()?;
+        let editor = temp.path().join("editor.sh");
+        std::fs::write(
+            &editor,
+            "#!/bin/sh\nprintf '# Gift ideas\\n\\n- Difference engine *parts*\\n' > \"$1\"\n",
+        )?;
+        std::process::Command::new("chmod")
+            .arg("+x")
+            .arg(&editor)
+            .status()?;
 
         let mut cmd = create_command();
-        cmd.arg("create")
-            .arg("--first-name")
-            .arg("nbd")
-            .arg("--birthday")
-            .arg("2024-06-06");
+        cmd.args(["note", "add", "--id", "1"])
+            .env_remove("VISUAL")
+            .env("EDITOR", &editor)
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Added note 2 to contact 1"));
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.args(["note", "edit", "1", "Met at the Royal Institution"])
+            .assert()
+            .success();
+
+        let mut cmd = create_command();
+        cmd.args(["note", "grep", "ENGINE"])
+            .assert()
             .success()
-            .stdout(predicates::str::contains("Successfully saved contact"));
+            .stdout(predicates::str::contains("Difference engine"))
+            .stdout(predicates::str::contains("Ada Lovelace"));
 
-        let data_repo = create_repo().await?;
+        let mut cmd = create_command();
+        cmd.args(["note", "history", "1"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Met at the Royal Society"))
+            .stdout(predicates::str::contains("Met at the Royal Institution"));
 
-        let contact = data_repo.get_contact_by_id(1).await?.contact;
+        let mut cmd = create_command();
+        cmd.args(["get", "--id", "1"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Notes"))
+            .stdout(predicates::str::contains("# Gift ideas"));
+
+        let mut cmd = create_command();
+        cmd.args(["note", "add", "--id", "1"])
+            .env_remove("VISUAL")
+            .env("EDITOR", "true")
+            .assert()
+            .failure()
+            .code(7);
 
-        let birthday =
-            chrono::NaiveDate::from_ymd_opt(2024, 6, 6).expect("Should be June, 6, 2024");
+        let mut cmd = create_command();
+        cmd.args(["note", "rm", "2", "9"])
+            .assert()
+            .success()
+            .stdout("There is no note 9\n");
 
-        assert_eq!(contact.birthday, birthday);
+        let mut cmd = create_command();
+        cmd.args(["note", "ls", "--id", "1"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Royal Institution"))
+            .stdout(predicates::str::contains("Gift ideas").not());
 
         Ok(())
     }
 
     #[tokio::test]
     #[serial]
-    async fn should_allow_only_first_name_when_creating() -> Result<()> {
+    async fn should_accept_international_numbers_and_format_them() -> Result<()> {
         clean_database().await.expect("Failed to clean database");
 
         let mut cmd = create_command();
-        cmd.arg("create").arg("--first-name").arg("Test");
+        cmd.args([
+            "create",
+            "--first-name",
+            "Ada",
+            "--phone-number",
+            "+44 20 7946 0958",
+        ])
+        .assert()
+        .success();
 
-        cmd.assert()
+        let mut cmd = create_command();
+        cmd.args(["get", "1", "--phone-format", "national"])
+            .assert()
             .success()
-            .stdout(predicates::str::contains("Successfully saved contact"));
+            .stdout(predicates::str::contains("020 7946 0958"));
 
-        Ok(())
-    }
+        let mut cmd = create_command();
+        cmd.args(["--output", "json", "get", "1"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(
+                "\"phone_e164\": \"+442079460958\"",
+            ));
 
-    #[tokio::test]
-    #[serial]
-    async fn should_say_already_initialized_when_db_exists() -> Result<()> {
+        let mut cmd = create_command();
+        cmd.args([
+            "create",
+            "--first-name",
+            "Charles",
+            "--phone-number",
+            "020 7946 0000",
+        ])
+        .assert()
+        .failure()
+        .code(5);
+
+        let mut cmd = create_command();
+        cmd.env("NBD_REGION", "GB")
+            .args([
+                "create",
+                "--first-name",
+                "Charles",
+                "--phone-number",
+                "020 7946 0000",
+            ])
+            .assert()
+            .success();
+
+        let mut cmd = create_command();
+        cmd.args(["show", "--phone-format", "international"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("+44 20 7946 0000"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_error_when_providing_invalid_phone_number() {
+        let mut cmd = create_command();
+        cmd.arg("create")
+            .arg("--first-name")
+            .arg("First")
+            .arg("--last-name")
+            .arg("Last")
+            .arg("--email")
+            .arg("test@com.com")
+            .arg("--phone-number")
+            .arg("123-321-123");
+
+        cmd.assert()
+            .failure()
+            .code(5)
+            .stderr(predicates::str::contains("123-321-123 is invalid"));
+    }
+
+    #[test]
+    fn should_error_when_invalid_args() {
+        let mut cmd = create_command();
+        cmd.arg("First").arg("Last").arg("32321123");
+
+        let stderr = format!("Usage: {} [OPTIONS] <COMMAND>", get_cli_name());
+
+        cmd.assert()
+            .failure()
+            .stderr(predicates::str::contains(stderr));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_one_contact_when_importing_alice_csv() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.arg("import").arg("tests/fixtures/alice.csv");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully imported 1 contact"));
+
+        let data_repo = create_repo().await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+
+        assert_eq!(contacts.len(), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_example_csv_with_three_rows() -> Result<()> {
+        clean_database().await?;
+        let mut cmd = create_command();
+        cmd.arg("import").arg("tests/fixtures/example.csv");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully imported"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_fail_when_given_incorrect_file() {
+        let mut cmd = create_command();
+        cmd.arg("import").arg("tests/fixtures/example.txt");
+
+        cmd.assert().failure().stderr(predicates::str::contains(
+            "expected a .csv or .vcf extension",
+        ));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_csv_with_other_extension_when_format_given() -> Result<()> {
+        clean_database().await?;
+
+        let mut cmd = create_command();
+        cmd.arg("import")
+            .arg("tests/fixtures/example.txt")
+            .arg("--format")
+            .arg("csv");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully imported 3 contact"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_two_contacts_when_importing_example_vcard() -> Result<()> {
+        clean_database().await?;
+
+        let mut cmd = create_command();
+        cmd.arg("import").arg("tests/fixtures/example.vcf");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully imported 2 contact"));
+
+        let data_repo = create_repo().await?;
+
+        let contacts = data_repo.get_all_contacts().await?;
+
+        assert_eq!(contacts.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_export_contact_as_vcard() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        let example_contact = create_lewis_carroll_contact()?;
+
+        data_repo.save_contact(example_contact).await?;
+
+        let mut cmd = create_command();
+        cmd.arg("export").arg("1");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("BEGIN:VCARD\r\nVERSION:4.0\r\n"))
+            .stdout(predicates::str::contains("FN:Lewis Carroll\r\n"))
+            .stdout(predicates::str::contains("BDAY:18320127\r\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_export_whole_book_to_file() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        let example_contact = create_lewis_carroll_contact()?;
+
+        data_repo.save_contact(example_contact.clone()).await?;
+        data_repo.save_contact(example_contact).await?;
+
+        let temp = tempfile::TempDir::new()?;
+        let export_path = temp.path().join("contacts.vcf");
+
+        let mut cmd = create_command();
+        cmd.arg("export")
+            .arg("--file")
+            .arg(&export_path)
+            .arg("--vcard-version")
+            .arg("3.0");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully exported 2 contact"));
+
+        let exported = std::fs::read_to_string(export_path)?;
+
+        assert_eq!(exported.matches("VERSION:3.0").count(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_export_csv_that_imports_back() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        let example_contact = create_lewis_carroll_contact()?;
+
+        data_repo.save_contact(example_contact).await?;
+
+        let temp = tempfile::TempDir::new()?;
+        let export_path = temp.path().join("contacts.csv");
+
+        let mut cmd = create_command();
+        cmd.arg("export")
+            .arg("--format")
+            .arg("csv")
+            .arg("--file")
+            .arg(&export_path);
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully exported 1 contact"));
+
+        let exported = std::fs::read_to_string(&export_path)?;
+
+        assert!(exported.starts_with(
+            "id,first_name,last_name,display_name,email,phone_number,birthday,starred,is_archived,created_at,updated_at,last_seen_at,next_reminder_at,frequency,last_reminder_at,external_id\n"
+        ));
+
+        let mut cmd = create_command();
+        cmd.arg("import").arg(&export_path);
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully imported 1 contact"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_export_chosen_csv_columns_without_archived() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        let mut archived_contact = create_lewis_carroll_contact()?;
+        archived_contact.is_archived = true;
+
+        data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+        data_repo.save_contact(archived_contact).await?;
+
+        let mut cmd = create_command();
+        cmd.arg("export")
+            .arg("--format")
+            .arg("csv")
+            .arg("--columns")
+            .arg("id,email")
+            .arg("--exclude-archived");
+
+        cmd.assert()
+            .success()
+            .stdout("id,email\n1,lewis@wonderland.com\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_fail_when_exporting_unknown_column() {
+        let mut cmd = create_command();
+        cmd.arg("export")
+            .arg("--format")
+            .arg("csv")
+            .arg("--columns")
+            .arg("nickname");
+
+        cmd.assert()
+            .failure()
+            .stderr(predicates::str::contains("Unknown column: nickname"));
+    }
+
+    #[test]
+    fn should_fail_when_given_blank_csv() {
+        let mut cmd = create_command();
+        cmd.arg("import").arg("tests/fixtures/blank.csv");
+
+        cmd.assert()
+            .failure()
+            .code(11)
+            .stderr(predicates::str::contains("CSV file is empty"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_say_no_contacts_when_contacts_are_empty() -> Result<()> {
+        clean_database().await?;
+
+        let mut cmd = create_command();
+        cmd.arg("show");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("No contacts yet!"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_show_one_contact_when_one_contact_available() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        let example_contact = create_lewis_carroll_contact()?;
+
+        data_repo.save_contact(example_contact).await?;
+
+        let mut cmd = create_command();
+
+        cmd.arg("show");
+
+        let expected = get_expected_table_header();
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains(expected.join("\n")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_show_two_contact_when_two_contact_available() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let example_contact = create_lewis_carroll_contact()?;
+
+        data_repo.save_contact(example_contact.clone()).await?;
+        data_repo.save_contact(example_contact).await?;
+
+        let mut cmd = create_command();
+
+        cmd.arg("show");
+
+        let expected = get_expected_table_header();
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains(expected.join("\n")));
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_show_contacts_as_json() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.arg("show").arg("--output").arg("json");
+
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let contacts: serde_json::Value = serde_json::from_slice(&output)?;
+
+        assert_eq!(contacts[0]["id"], 1);
+        assert_eq!(contacts[0]["display_name"], "Lewis Carroll");
+        assert_eq!(contacts[0]["birthday"], "1832-01-27");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_show_empty_json_array_when_contacts_are_empty() -> Result<()> {
+        clean_database().await?;
+
+        let mut cmd = create_command();
+        cmd.arg("--output").arg("json").arg("show");
+
+        cmd.assert().success().stdout("[]\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_show_one_json_line_per_contact() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+        data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.arg("show").arg("--output").arg("jsonl");
+
+        let output = cmd.assert().success().get_output().stdout.clone();
+        let output = String::from_utf8(output)?;
+
+        assert_eq!(output.lines().count(), 2);
+        for line in output.lines() {
+            let contact: serde_json::Value = serde_json::from_str(line)?;
+            assert_eq!(contact["first_name"], "Lewis");
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_get_contact_as_csv() -> Result<()> {
+        clean_database().await?;
+
+        let data_repo = create_repo().await?;
+        data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.arg("get").arg("1").arg("--output").arg("csv");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::starts_with("id,first_name,last_name,"))
+            .stdout(predicates::str::contains(
+                "1,Lewis,Carroll,Lewis Carroll,lewis@wonderland.com,212-555-0177,1832-01-27,false,false,",
+            ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_accept_a_firstname_and_birthday() {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.arg("create")
+            .arg("--first-name")
+            .arg("Molly")
+            .arg("--birthday")
+            .arg("1970-01-01");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully saved contact"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_set_birthday_when_provided() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.arg("create")
+            .arg("--first-name")
+            .arg("nbd")
+            .arg("--birthday")
+            .arg("2024-06-06");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully saved contact"));
+
+        let data_repo = create_repo().await?;
+
+        let contact = data_repo.get_contact_by_id(1).await?.contact;
+
+        let birthday = nbd::models::Birthday::new(Some(2024), 6, 6);
+
+        assert_eq!(contact.birthday, birthday);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_add_list_and_remove_tags() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut add_cmd = create_command();
+        add_cmd
+            .args(["tag", "add", &id.to_string(), "author", "oxford"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(format!("Tagged contact {id}")));
+
+        let mut ls_cmd = create_command();
+        ls_cmd
+            .args(["tag", "ls", &id.to_string()])
+            .assert()
+            .success()
+            .stdout("author\noxford\n");
+
+        let mut rm_cmd = create_command();
+        rm_cmd
+            .args(["tag", "rm", &id.to_string(), "oxford"])
+            .assert()
+            .success();
+
+        let mut all_cmd = create_command();
+        all_cmd
+            .args(["tag", "ls", "--output", "json"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("\"name\": \"author\""))
+            .stdout(predicates::str::contains("\"contacts\": 1"))
+            .stdout(predicates::str::contains("oxford").not());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_fail_when_tagging_nonexistent_contact() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.args(["tag", "add", "999", "author"])
+            .assert()
+            .failure()
+            .code(3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_filter_shown_contacts_by_tag() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let lewis = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+        let alice = data_repo
+            .save_contact(Contact::builder().first_name("Alice").build()?)
+            .await?;
+        data_repo.add_tag(lewis, "author").await?;
+        data_repo.add_tag(lewis, "oxford").await?;
+        data_repo.add_tag(alice, "oxford").await?;
+
+        let mut tagged_cmd = create_command();
+        tagged_cmd
+            .args(["show", "--tag", "oxford", "--not-tag", "author"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Alice"))
+            .stdout(predicates::str::contains("Lewis").not());
+
+        let mut both_cmd = create_command();
+        both_cmd
+            .args(["show", "--tag", "oxford", "--tag", "author"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Lewis"))
+            .stdout(predicates::str::contains("Alice").not());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_tags_with_custom_separator() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let temp = tempfile::TempDir::new()?;
+        let csv_path = temp.path().join("tags.csv");
+        std::fs::write(&csv_path, "first_name,tags\nAda,math|poetry\n")?;
+
+        let mut cmd = create_command();
+        cmd.arg("import")
+            .arg(&csv_path)
+            .args(["--tag-separator", "|"])
+            .assert()
+            .success();
+
+        let data_repo = create_repo().await?;
+        let tags = data_repo.get_tags(1).await?;
+
+        assert_eq!(tags, vec!["math".to_string(), "poetry".to_string()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_log_interaction_and_update_last_seen_at() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut log_cmd = create_command();
+        log_cmd
+            .args(["log", &id.to_string(), "--kind", "coffee"])
+            .args([
+                "--note",
+                "Talked about Alice",
+                "--at",
+                "2026-10-01T09:30:00Z",
+            ])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains(format!(
+                "Logged coffee with contact {id}"
+            )));
+
+        let contact = data_repo.get_contact_by_id(id).await?;
+        let expected = chrono::DateTime::parse_from_rfc3339("2026-10-01T09:30:00Z")?;
+
+        assert_eq!(contact.contact.last_seen_at, Some(expected.to_utc()));
+
+        let mut history_cmd = create_command();
+        history_cmd
+            .args(["history", &id.to_string(), "--output", "jsonl"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("\"kind\":\"coffee\""))
+            .stdout(predicates::str::contains("\"note\":\"Talked about Alice\""))
+            .stdout(predicates::str::contains(
+                "\"occurred_at\":\"2026-10-01T09:30:00Z\"",
+            ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_fail_when_logging_with_invalid_date() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.args([
+            "log",
+            &id.to_string(),
+            "--kind",
+            "call",
+            "--at",
+            "yesterday",
+        ])
+        .assert()
+        .failure()
+        .code(6);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_fail_history_of_nonexistent_contact() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.args(["history", "999"]).assert().failure().code(3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_list_overdue_contacts_most_overdue_first() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let lewis = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+        let alice = data_repo
+            .save_contact(Contact::builder().first_name("Alice").build()?)
+            .await?;
+
+        let mut frequency_cmd = create_command();
+        frequency_cmd
+            .args(["edit", &lewis.to_string(), "--frequency", "monthly"])
+            .assert()
+            .success();
+
+        let mut alice_cmd = create_command();
+        alice_cmd
+            .args(["edit", &alice.to_string(), "--frequency", "every 2 weeks"])
+            .assert()
+            .success();
+
+        data_repo
+            .log_interaction(lewis, "call", "", "2026-01-10T12:00:00Z".parse()?)
+            .await?;
+        data_repo
+            .log_interaction(alice, "call", "", "2026-03-01T12:00:00Z".parse()?)
+            .await?;
+
+        let mut cmd = create_command();
+        let output = cmd
+            .args(["due", "--output", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let due: serde_json::Value = serde_json::from_slice(&output)?;
+
+        assert_eq!(due[0]["id"], lewis);
+        assert_eq!(due[0]["frequency"], "monthly");
+        assert_eq!(due[0]["next_reminder_at"], "2026-02-10T12:00:00Z");
+        assert_eq!(due[1]["id"], alice);
+        assert_eq!(due[1]["next_reminder_at"], "2026-03-15T12:00:00Z");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_list_contacts_due_within_span() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut create_cmd = create_command();
+        create_cmd
+            .args(["create", "--first-name", "Alice", "--frequency", "weekly"])
+            .assert()
+            .success();
+
+        let mut due_now_cmd = create_command();
+        due_now_cmd
+            .arg("due")
+            .assert()
+            .success()
+            .stdout("Nobody is due a catch-up\n");
+
+        let mut due_soon_cmd = create_command();
+        due_soon_cmd
+            .args(["due", "--within", "8d"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Alice"))
+            .stdout(predicates::str::contains("weekly"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_fail_when_given_invalid_frequency() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.args([
+            "create",
+            "--first-name",
+            "Alice",
+            "--frequency",
+            "sometimes",
+        ])
+        .assert()
+        .failure()
+        .code(14)
+        .stderr(predicates::str::contains(
+            "sometimes is not a valid cadence",
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_list_upcoming_birthdays_with_age_when_year_is_known() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let today = chrono::Local::now().date_naive();
+        let soon = today.checked_add_days(chrono::Days::new(3)).unwrap();
+        let later = today.checked_add_days(chrono::Days::new(60)).unwrap();
+
+        let data_repo = create_repo().await?;
+        let with_year = data_repo
+            .save_contact(
+                Contact::builder()
+                    .first_name("Ada")
+                    .birthday(&soon.format("1990-%m-%d").to_string())
+                    .build()?,
+            )
+            .await?;
+        let year_less = data_repo
+            .save_contact(
+                Contact::builder()
+                    .first_name("Bob")
+                    .birthday(&soon.format("--%m-%d").to_string())
+                    .build()?,
+            )
+            .await?;
+        data_repo
+            .save_contact(
+                Contact::builder()
+                    .first_name("Cy")
+                    .birthday(&later.format("%m-%d").to_string())
+                    .build()?,
+            )
+            .await?;
+
+        let mut cmd = create_command();
+        let output = cmd
+            .args(["birthdays", "--within", "30d", "--output", "json"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let birthdays: serde_json::Value = serde_json::from_slice(&output)?;
+        let expected_age = chrono::Datelike::year(&soon).checked_sub(1990).unwrap();
+
+        assert_eq!(birthdays.as_array().map(Vec::len), Some(2));
+        assert_eq!(birthdays[0]["id"], with_year);
+        assert_eq!(birthdays[0]["days_until"], 3);
+        assert_eq!(birthdays[0]["turning"], expected_age);
+        assert_eq!(birthdays[1]["id"], year_less);
+        assert_eq!(birthdays[1]["turning"], serde_json::Value::Null);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_accept_a_birthday_without_year() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.args(["create", "--first-name", "Bob", "--birthday", "12-10"])
+            .assert()
+            .success();
+
+        let data_repo = create_repo().await?;
+        let contact = data_repo.get_contact_by_id(1).await?;
+
+        assert_eq!(
+            contact.contact.birthday,
+            nbd::models::Birthday::new(None, 12, 10)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_export_calendar_with_stable_uids() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let temp = tempfile::TempDir::new()?;
+        let first = temp.path().join("first.ics");
+        let second = temp.path().join("second.ics");
+
+        for path in [&first, &second] {
+            let mut cmd = create_command();
+            cmd.arg("export-calendar")
+                .arg(path)
+                .assert()
+                .success()
+                .stdout("Successfully exported 1 calendar event\n");
+        }
+
+        let calendar = std::fs::read_to_string(&first)?;
+        let database_path = get_database_path();
+        let book = std::path::Path::new(&database_path)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let uid = format!("UID:birthday-{id}@{book}.nbd\r\n");
+
+        assert!(calendar.contains(&uid));
+        assert!(calendar.contains("DTSTART;VALUE=DATE:18320127\r\n"));
+        assert!(std::fs::read_to_string(&second)?.contains(&uid));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_search_contacts_by_name_and_notes() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+        data_repo
+            .log_interaction(
+                id,
+                "letter",
+                "Sent a riddle about ravens",
+                chrono::Utc::now(),
+            )
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.args(["search", "lew", "raven"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Lewis Carroll"));
+
+        let mut cmd = create_command();
+        cmd.args(["search", "raven"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Sent a riddle about [ravens]"));
+
+        let mut cmd = create_command();
+        cmd.args(["search", "nobody"])
+            .assert()
+            .success()
+            .stdout("No contacts match\n");
+
+        let mut cmd = create_command();
+        let output = cmd
+            .args(["--output", "json", "search", "carroll"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let hits: serde_json::Value = serde_json::from_slice(&output)?;
+
+        assert_eq!(hits.as_array().map(Vec::len), Some(1));
+        assert_eq!(hits[0]["id"], id);
+        assert_eq!(hits[0]["snippet"], "[Carroll]");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_get_edit_and_delete_contacts_by_fuzzy_name() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.args(["get", "lewis carol"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("lewis@wonderland.com"));
+
+        let mut cmd = create_command();
+        cmd.args(["edit", "Carrol", "--email", "lewis@looking-glass.com"])
+            .assert()
+            .success();
+
+        assert_eq!(
+            data_repo.get_contact_by_id(id).await?.contact.email,
+            "lewis@looking-glass.com"
+        );
+
+        let mut cmd = create_command();
+        cmd.args(["delete", "lweis"])
+            .assert()
+            .success()
+            .stdout(format!("Successfully deleted contact {id}\n"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_list_candidates_when_a_name_is_ambiguous() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let brown = data_repo
+            .save_contact(
+                Contact::builder()
+                    .first_name("Dan")
+                    .last_name("Brown")
+                    .build()?,
+            )
+            .await?;
+        let smith = data_repo
+            .save_contact(
+                Contact::builder()
+                    .first_name("Dan")
+                    .last_name("Smith")
+                    .build()?,
+            )
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.args(["get", "dan"])
+            .assert()
+            .failure()
+            .code(15)
+            .stderr(predicates::str::contains(format!("{brown}\tDan Brown")))
+            .stderr(predicates::str::contains(format!("{smith}\tDan Smith")));
+
+        let mut cmd = create_command();
+        cmd.args(["delete", "dan"]).assert().failure().code(15);
+
+        let mut cmd = create_command();
+        cmd.args(["get", "nobody"]).assert().failure().code(3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_report_every_invalid_row_and_import_nothing() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let temp = tempfile::TempDir::new()?;
+        let csv_path = temp.path().join("contacts.csv");
+        std::fs::write(
+            &csv_path,
+            "first_name,email,birthday\nAlice,alice@example.com,\nBob,bob@,\nCy,,1990-13-45\n",
+        )?;
+
+        let mut cmd = create_command();
+        cmd.arg("import")
+            .arg(&csv_path)
+            .assert()
+            .failure()
+            .code(10)
+            .stderr(predicates::str::contains(
+                "line 3, email: \"bob@\" is not a valid email",
+            ))
+            .stderr(predicates::str::contains(
+                "line 4, birthday: \"1990-13-45\" is not a valid birthday",
+            ));
+
+        let data_repo = create_repo().await?;
+        assert!(data_repo.get_all_contacts().await?.is_empty());
+
+        let mut cmd = create_command();
+        cmd.arg("import")
+            .arg(&csv_path)
+            .arg("--dry-run")
+            .arg("--skip-invalid")
+            .assert()
+            .success()
+            .stderr(predicates::str::contains("Skipped line 3, email"))
+            .stdout(
+                "Dry run: would import 1 contact (1 created, 0 updated, 0 skipped, 2 invalid); nothing was saved\n",
+            );
+
+        assert!(data_repo.get_all_contacts().await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_not_duplicate_contacts_when_importing_twice() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        for expected in [
+            "Successfully imported 3 contact (3 created, 0 updated, 0 skipped, 0 invalid)\n",
+            "Successfully imported 0 contact (0 created, 0 updated, 3 skipped, 0 invalid)\n",
+        ] {
+            let mut cmd = create_command();
+            cmd.args([
+                "import",
+                "tests/fixtures/example.csv",
+                "--on-duplicate",
+                "skip",
+            ])
+            .assert()
+            .success()
+            .stdout(expected);
+        }
+
+        let data_repo = create_repo().await?;
+        assert_eq!(data_repo.get_all_contacts().await?.len(), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_google_csv_with_profile_and_warn_about_unmapped_columns() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.args(["import", "tests/fixtures/google.csv", "--profile", "google"])
+            .assert()
+            .success()
+            .stderr(predicates::str::contains(
+                "Warning: ignored 7 unmapped column(s): Additional Name, Nickname, Notes, \
+                 E-mail 1 - Type, E-mail 2 - Type, Phone 1 - Type, Organization 1 - Name",
+            ))
+            .stdout(predicates::str::contains("Successfully imported 2 contact"));
+
+        let data_repo = create_repo().await?;
+        let contacts = data_repo.get_all_contacts().await?;
+        let ada = contacts.first().expect("Ada was imported");
+
+        assert_eq!(ada.contact.first_name, "Ada");
+        assert_eq!(ada.contact.last_name, "Lovelace");
+        assert_eq!(ada.contact.email, "ada@example.com");
+        assert_eq!(ada.contact.phone_number, "+1 212 555 0958");
+        assert_eq!(data_repo.get_tags(ada.id).await?, vec!["Math".to_string()]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_outlook_csv_with_profile() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.args([
+            "import",
+            "tests/fixtures/outlook.csv",
+            "--profile",
+            "outlook",
+        ])
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Successfully imported 2 contact"));
+
+        let data_repo = create_repo().await?;
+        let contacts = data_repo.get_all_contacts().await?;
+        let grace = &contacts.first().expect("Grace was imported").contact;
+
+        assert_eq!(grace.phone_number, "212-555-0199");
+        assert_eq!(grace.email, "grace@example.com");
+        assert_eq!(
+            grace.birthday.as_ref().map(ToString::to_string).as_deref(),
+            Some("1906-12-09")
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_import_csv_with_mapping_file() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let temp = tempfile::TempDir::new()?;
+        let csv_path = temp.path().join("contacts.csv");
+        std::fs::write(&csv_path, "Given,Cell,Landline\nAlice,,212-555-0100\n")?;
+        let mapping_path = temp.path().join("mapping.toml");
+        std::fs::write(
+            &mapping_path,
+            "[columns]\nfirst_name = \"Given\"\nphone_number = [\"Cell\", \"Landline\"]\n",
+        )?;
+
+        let mut cmd = create_command();
+        cmd.arg("import")
+            .arg(&csv_path)
+            .arg("--mapping")
+            .arg(&mapping_path)
+            .assert()
+            .success()
+            .stderr(predicates::str::contains("Warning").not());
+
+        let data_repo = create_repo().await?;
+        let contacts = data_repo.get_all_contacts().await?;
+        let alice = &contacts.first().expect("Alice was imported").contact;
+
+        assert_eq!(alice.first_name, "Alice");
+        assert_eq!(alice.phone_number, "212-555-0100");
+
+        std::fs::write(&mapping_path, "[columns]\ncompany = \"Company\"\n")?;
+
+        let mut cmd = create_command();
+        cmd.arg("import")
+            .arg(&csv_path)
+            .arg("--mapping")
+            .arg(&mapping_path)
+            .assert()
+            .failure()
+            .code(9);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_list_duplicates_and_merge_them() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let keep = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+        let drop = data_repo
+            .save_contact(
+                Contact::builder()
+                    .first_name("Lewis")
+                    .last_name("Carroll")
+                    .email("carroll@oxford.ac.uk")
+                    .phone_number("+1 212 555 0177")
+                    .build()?,
+            )
+            .await?;
+        data_repo
+            .save_contact(Contact::builder().first_name("Alice").build()?)
+            .await?;
+
+        let mut cmd = create_command();
+        let output = cmd
+            .args(["--output", "json", "dedupe"])
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let duplicates: serde_json::Value = serde_json::from_slice(&output)?;
+
+        assert_eq!(duplicates.as_array().map(Vec::len), Some(1));
+        assert_eq!(duplicates[0]["id"], keep);
+        assert_eq!(duplicates[0]["other_id"], drop);
+        assert_eq!(duplicates[0]["reasons"], "same phone, same name");
+
+        let mut cmd = create_command();
+        cmd.args(["--output", "csv", "dedupe"])
+            .assert()
+            .success()
+            .stdout(format!(
+                "id,display_name,other_id,other_display_name,score,reasons\n\
+                 {keep},Lewis Carroll,{drop},Lewis Carroll,{score},\"same phone, same name\"\n",
+                score = duplicates[0]["score"]
+            ));
+
+        let mut cmd = create_command();
+        cmd.args([
+            "merge",
+            &keep.to_string(),
+            &drop.to_string(),
+            "--take",
+            "email",
+        ])
+        .assert()
+        .success()
+        .stdout(format!("Merged contact {drop} into {keep}\n"));
+
+        let merged = data_repo.get_contact_by_id(keep).await?;
+        assert_eq!(merged.contact.email, "carroll@oxford.ac.uk");
+        assert_eq!(merged.contact.phone_number, "212-555-0177");
+        assert_eq!(data_repo.get_merged_ids(keep).await?, [drop]);
+
+        let mut cmd = create_command();
+        cmd.arg("dedupe")
+            .assert()
+            .success()
+            .stdout("No duplicates found\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_refuse_bad_merges() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+        let id = id.to_string();
+
+        let mut cmd = create_command();
+        cmd.args(["merge", &id, &id]).assert().failure().code(16);
+
+        let mut cmd = create_command();
+        cmd.args(["merge", &id, "999"]).assert().failure().code(3);
+
+        let mut cmd = create_command();
+        cmd.args(["merge", &id, "999", "--take", "created_at"])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains(
+                "created_at cannot be taken from another contact",
+            ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_only_accept_ids_with_the_id_flag() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let data_repo = create_repo().await?;
+        let id = data_repo
+            .save_contact(create_lewis_carroll_contact()?)
+            .await?;
+
+        let mut cmd = create_command();
+        cmd.args(["get", "--id", &id.to_string()])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Lewis"));
+
+        let mut cmd = create_command();
+        cmd.args(["get", "--id", "lewis"])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("--id expects a numeric ID"));
+
+        let mut cmd = create_command();
+        cmd.args(["delete", "--id", "999"])
+            .assert()
+            .failure()
+            .code(3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_allow_only_first_name_when_creating() -> Result<()> {
+        clean_database().await.expect("Failed to clean database");
+
+        let mut cmd = create_command();
+        cmd.arg("create").arg("--first-name").arg("Test");
+
+        cmd.assert()
+            .success()
+            .stdout(predicates::str::contains("Successfully saved contact"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn should_say_already_initialized_when_db_exists() -> Result<()> {
         let mut first_cmd = create_command();
         first_cmd.arg("init");
         first_cmd.assert().success();
@@ -414,7 +2080,7 @@ mod tests {
         let temp = tempfile::TempDir::new()?;
         let config_dir = temp.path().to_path_buf();
 
-        let mut cmd = create_command();
+        let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
         cmd.env("NBD_CONFIG_DIR", &config_dir);
 
         cmd.arg("init");
@@ -423,6 +2089,254 @@ mod tests {
         let db_path = config_dir.join("contacts.db");
         assert!(db_path.exists(), "expected database file at {db_path:?}");
 
+        let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+        cmd.env("NBD_CONFIG_DIR", &config_dir)
+            .env_remove("DATABASE_URL")
+            .args(["create", "--first-name", "Ada"])
+            .assert()
+            .success();
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_open_the_book_given_by_db_and_ask_for_init_when_missing() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("books").join("work.db");
+
+        let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+        cmd.arg("--db")
+            .arg(&db_path)
+            .arg("show")
+            .assert()
+            .failure()
+            .code(18)
+            .stderr(predicates::str::contains("run `nbd-cli init` first"));
+
+        let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+        cmd.args(["init", "--db"]).arg(&db_path).assert().success();
+
+        let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+        cmd.args(["create", "--first-name", "Grace", "--db"])
+            .arg(&db_path)
+            .assert()
+            .success();
+
+        let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+        cmd.arg("--db")
+            .arg(&db_path)
+            .arg("show")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Grace"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_named_books_and_move_contacts_between_them() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let config_dir = temp.path().join("nbd");
+        let nbd = || {
+            let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+            cmd.env("NBD_CONFIG_DIR", &config_dir);
+            cmd
+        };
+
+        nbd().arg("init").assert().success();
+        nbd()
+            .args(["init", "--book", "volunteers"])
+            .assert()
+            .success();
+        assert!(config_dir.join("books/volunteers.db").exists());
+
+        nbd()
+            .args(["create", "--first-name", "Grace", "--last-name", "Hopper"])
+            .args(["--email", "grace@example.com"])
+            .assert()
+            .success();
+        nbd()
+            .args(["books", "copy", "Grace", "--to", "volunteers"])
+            .assert()
+            .success()
+            .stdout("Copied Grace Hopper to volunteers as contact 1\n");
+
+        nbd()
+            .args(["books", "ls", "--output", "csv"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("default,true,1,"))
+            .stdout(predicates::str::contains("volunteers,false,1,"));
+
+        nbd()
+            .args(["config", "set", "book", "volunteers"])
+            .assert()
+            .success();
+        nbd()
+            .args(["books", "rename", "volunteers", "helpers"])
+            .assert()
+            .success()
+            .stdout("Renamed book volunteers to helpers\n");
+        nbd()
+            .args(["config", "get", "book"])
+            .assert()
+            .success()
+            .stdout("helpers\n");
+        nbd()
+            .args(["show"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("grace@example.com"));
+
+        nbd()
+            .args(["--book", "default", "books", "move", "1", "--id"])
+            .args(["--to", "helpers"])
+            .assert()
+            .success()
+            .stdout("Moved Grace Hopper to helpers as contact 2\n");
+        nbd()
+            .args(["--book", "default", "show"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Grace").not());
+        nbd()
+            .args(["books", "copy", "1", "--to", "helpers"])
+            .assert()
+            .failure()
+            .code(20);
+        nbd()
+            .args(["--book", "../work", "show"])
+            .assert()
+            .failure()
+            .code(20);
+
+        nbd()
+            .args(["books", "rm", "helpers"])
+            .assert()
+            .failure()
+            .stderr(predicates::str::contains("Pass --yes"));
+        nbd()
+            .args(["books", "rm", "helpers", "--yes"])
+            .assert()
+            .success()
+            .stdout("Deleted book helpers\n");
+        nbd()
+            .args(["config", "get", "book"])
+            .assert()
+            .success()
+            .stdout("");
+        nbd()
+            .args(["books", "rm", "helpers", "--yes"])
+            .assert()
+            .failure()
+            .code(3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn should_show_migrations_and_check_the_book() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        let nbd = || {
+            let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+            cmd.arg("--db").arg(&db_path);
+            cmd
+        };
+
+        nbd().arg("init").assert().success();
+
+        nbd()
+            .args(["db", "status"])
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("combine contact tables"))
+            .stdout(predicates::str::contains("pending").not());
+        nbd()
+            .args(["db", "migrate"])
+            .assert()
+            .success()
+            .stdout("The book is up to date\n");
+        nbd()
+            .args(["db", "check"])
+            .assert()
+            .success()
+            .stdout("No problems found\n");
+
+        let pool = SqlitePool::connect(&format!("sqlite://{}", db_path.display())).await?;
+        let mut connection = pool.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *connection)
+            .await?;
+        sqlx::query(
+            "INSERT INTO interactions (contact_id, occurred_at, kind, note) VALUES (9, '2026-10-18', 'call', '')",
+        )
+        .execute(&mut *connection)
+        .await?;
+        drop(connection);
+        pool.close().await;
+
+        nbd()
+            .args(["db", "check"])
+            .assert()
+            .failure()
+            .stdout("Row 1 of interactions belongs to a row of contacts that no longer exists\n")
+            .stderr(predicates::str::contains("Found 1 problem(s)"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_back_up_and_restore_the_book() -> Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let db_path = temp.path().join("contacts.db");
+        let nbd = || {
+            let mut cmd = cargo::cargo_bin_cmd!("nbd-cli");
+            cmd.arg("--db").arg(&db_path);
+            cmd
+        };
+
+        nbd().arg("init").assert().success();
+        nbd()
+            .args(["create", "--first-name", "Grace", "--last-name", "Hopper"])
+            .assert()
+            .success();
+
+        let output = nbd().arg("backup").assert().success().get_output().clone();
+        let stdout = String::from_utf8(output.stdout)?;
+        let backup = stdout
+            .trim()
+            .rsplit(' ')
+            .next()
+            .map(std::path::PathBuf::from)
+            .unwrap_or_default();
+        assert!(backup.starts_with(temp.path().join("backups")));
+        assert!(backup.exists());
+
+        nbd().args(["delete", "--id", "1"]).assert().success();
+        nbd()
+            .arg("restore")
+            .arg(backup.file_name().unwrap_or_default())
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Restored "));
+        nbd()
+            .arg("show")
+            .assert()
+            .success()
+            .stdout(predicates::str::contains("Grace"));
+        assert_eq!(std::fs::read_dir(temp.path().join("backups"))?.count(), 2);
+
+        let text = temp.path().join("contacts.txt");
+        std::fs::write(&text, "Grace Hopper")?;
+        nbd()
+            .arg("restore")
+            .arg(&text)
+            .assert()
+            .failure()
+            .code(11)
+            .stderr(predicates::str::contains("it is not a contact book"));
+
         Ok(())
     }
 }